@@ -0,0 +1,261 @@
+use std::process::Command;
+
+use structopt::StructOpt;
+
+use super::config;
+use super::opt::Opt;
+
+/// The command line after expanding an alias.
+#[derive(Debug, PartialEq)]
+pub enum Expanded {
+    /// Arguments for the subcommands, with the program name first.
+    Args(Vec<String>),
+    /// A shell command from an alias starting with `!`,
+    /// with the arguments given after the alias.
+    Shell {
+        name: String,
+        command: String,
+        args: Vec<String>,
+    },
+}
+
+/// Split the words of an alias like a shell does, with quotes and
+/// backslashes.
+fn split_words(text: &str) -> Result<Vec<String>, String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut in_word = false;
+    let mut quote: Option<char> = None;
+    let mut chars = text.chars();
+    while let Some(ch) = chars.next() {
+        match (quote, ch) {
+            (Some(open), _) if ch == open => quote = None,
+            (Some('"'), '\\') | (None, '\\') => match chars.next() {
+                Some(escaped) => word.push(escaped),
+                None => word.push('\\'),
+            },
+            (Some(_), _) => word.push(ch),
+            (None, '"') | (None, '\'') => {
+                quote = Some(ch);
+                in_word = true;
+            },
+            (None, _) if ch.is_whitespace() => {
+                if in_word {
+                    words.push(word.clone());
+                    word.clear();
+                    in_word = false;
+                }
+            },
+            (None, _) => {
+                word.push(ch);
+                in_word = true;
+            },
+        }
+        if quote.is_some() {
+            in_word = true;
+        }
+    }
+    if quote.is_some() {
+        return Err(format!("Unterminated quote in {}", text));
+    }
+    if in_word {
+        words.push(word);
+    }
+    Ok(words)
+}
+
+/// Whether the name is a subcommand of the program itself.
+pub fn is_builtin(name: &str) -> bool {
+    if name == "help" {
+        return true;
+    }
+    // the help of a subcommand is displayed only when it exists
+    match Opt::clap().get_matches_from_safe(vec!["tickets", name, "--help"]) {
+        Err(error) => error.kind == structopt::clap::ErrorKind::HelpDisplayed,
+        Ok(_) => true,
+    }
+}
+
+/// The index of the subcommand in the arguments, skipping the options
+/// before it.
+pub fn index_subcommand(args: &[String]) -> Option<usize> {
+    let mut index = 1;
+    while index < args.len() {
+        let arg = &args[index];
+        if arg == "--color" {
+            index += 2;
+        }
+        else if arg.starts_with('-') {
+            index += 1;
+        }
+        else {
+            return Some(index);
+        }
+    }
+    None
+}
+
+/// Fill each `{}` in the words with the next argument,
+/// and append the rest of them.
+fn substitute(words: Vec<String>, args: &[String]) -> Vec<String> {
+    let mut args = args.iter();
+    let mut substituted: Vec<String> = words
+        .into_iter()
+        .map(|word| {
+            if word.contains("{}") {
+                let arg = args.next().map(|arg| &arg[..]).unwrap_or("");
+                word.replace("{}", arg)
+            }
+            else {
+                word
+            }
+        })
+        .collect();
+    substituted.extend(args.cloned());
+    substituted
+}
+
+/// Expand the alias of the subcommand from the options `alias.<name>`,
+/// as many times as the aliases refer to others.
+pub fn expand(args: Vec<String>) -> Result<Expanded, String> {
+    let mut args = args;
+    let mut expanded: Vec<String> = Vec::new();
+    loop {
+        let index = match index_subcommand(&args) {
+            Some(index) => index,
+            None => return Ok(Expanded::Args(args)),
+        };
+        let name = args[index].clone();
+        let alias = match config::get(&format!("alias.{}", name)) {
+            Some(alias) => alias,
+            None => return Ok(Expanded::Args(args)),
+        };
+        if is_builtin(&name) {
+            // an alias may expand to the subcommand it shadows
+            if !expanded.is_empty() {
+                return Ok(Expanded::Args(args));
+            }
+            return Err(format!("The alias {} shadows the subcommand {}.",
+                               name,
+                               name));
+        }
+        if expanded.contains(&name) {
+            return Err(format!("The alias {} expands to itself.", name));
+        }
+        expanded.push(name.clone());
+
+        if let Some(command) = alias.strip_prefix('!') {
+            return Ok(Expanded::Shell {
+                name,
+                command: command.to_string(),
+                args: args[index + 1..].to_vec(),
+            });
+        }
+        let words = split_words(&alias)?;
+        if words.is_empty() {
+            return Err(format!("The alias {} is empty.", name));
+        }
+        let mut args_expanded = args[..index].to_vec();
+        args_expanded.append(&mut substitute(words, &args[index + 1..]));
+        args = args_expanded;
+    }
+}
+
+/// Run the shell command of an alias with the arguments, and give
+/// its exit status.
+pub fn run_shell(name: &str, command: &str, args: &[String]) -> i32 {
+    let status = Command::new("sh")
+        .arg("-c")
+        .arg(format!("{} \"$@\"", command))
+        .arg(name)
+        .args(args)
+        .env("TICKETS_ROOT", super::get_path_root())
+        .status();
+    match status {
+        Ok(status) => status.code().unwrap_or(exitcode::SOFTWARE),
+        Err(error) => {
+            eprintln!("tickets :: {}\n\nERROR: {}", name, error);
+            exitcode::OSERR
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    fn strings(words: &[&str]) -> Vec<String> {
+        words.iter().map(|word| word.to_string()).collect()
+    }
+
+    #[test]
+    fn split_words() {
+        assert_eq!(super::split_words("show open/").unwrap(),
+                   strings(&["show", "open/"]));
+        assert_eq!(super::split_words(r#"new {} -m "A \"new\" one"  "#)
+                   .unwrap(),
+                   strings(&["new", "{}", "-m", "A \"new\" one"]));
+        assert_eq!(super::split_words("a '' 'b c'").unwrap(),
+                   strings(&["a", "", "b c"]));
+        assert!(super::split_words("show 'open/").is_err());
+    }
+
+    #[test]
+    fn substitute_arguments() {
+        assert_eq!(super::substitute(strings(&["move", "{}", "done/"]),
+                                     &strings(&["ID-1", "--extra"])),
+                   strings(&["move", "ID-1", "done/", "--extra"]));
+        assert_eq!(super::substitute(strings(&["show", "open/"]),
+                                     &strings(&[])),
+                   strings(&["show", "open/"]));
+    }
+
+    #[test]
+    fn find_subcommand() {
+        assert_eq!(super::index_subcommand(&strings(&["tickets", "todo"])),
+                   Some(1));
+        assert_eq!(super::index_subcommand(
+            &strings(&["tickets", "--color", "never", "todo"])),
+                   Some(3));
+        assert_eq!(super::index_subcommand(&strings(&["tickets", "-h"])),
+                   None);
+    }
+
+    #[test]
+    fn builtin_subcommands() {
+        assert!(super::is_builtin("show"));
+        assert!(super::is_builtin("help"));
+        assert!(!super::is_builtin("todo"));
+    }
+
+    #[test]
+    fn expand_aliases() {
+        super::super::purge().unwrap_or(());
+        super::super::initialize_root().unwrap_or(());
+        let local = super::config::Scope::Local;
+        super::config::set(local, "alias.todo", "show open/").unwrap();
+        super::config::set(local, "alias.done", "move {} done/").unwrap();
+        super::config::set(local, "alias.finish", "done").unwrap();
+        super::config::set(local, "alias.loop", "loop").unwrap();
+        super::config::set(local, "alias.count", "!ls | wc -l").unwrap();
+        super::config::set(local, "alias.show", "show open/").unwrap();
+
+        assert_eq!(super::expand(strings(&["tickets", "todo"])),
+                   Ok(super::Expanded::Args(
+                       strings(&["tickets", "show", "open/"]))));
+        assert_eq!(super::expand(strings(&["tickets", "finish", "ID-1"])),
+                   Ok(super::Expanded::Args(
+                       strings(&["tickets", "move", "ID-1", "done/"]))));
+        assert_eq!(super::expand(strings(&["tickets", "count", "x"])),
+                   Ok(super::Expanded::Shell {
+                       name: String::from("count"),
+                       command: String::from("ls | wc -l"),
+                       args: strings(&["x"]),
+                   }));
+        assert!(super::expand(strings(&["tickets", "loop"])).is_err());
+        assert_eq!(super::expand(strings(&["tickets", "show"])),
+                   Err(String::from("The alias show shadows \
+                                    the subcommand show.")));
+        assert_eq!(super::expand(strings(&["tickets", "new", "open/"])),
+                   Ok(super::Expanded::Args(
+                       strings(&["tickets", "new", "open/"]))));
+    }
+}
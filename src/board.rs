@@ -0,0 +1,133 @@
+use std::io;
+
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+use super::ticket::Ticket;
+
+const GAP: &str = " | ";
+const MIN_COLUMN_WIDTH: usize = 8;
+
+/// The width of the terminal, from the environment variable COLUMNS,
+/// the terminal itself, or 80 as a last resort.
+pub fn terminal_width() -> usize {
+    if let Some(columns) = std::env::var("COLUMNS")
+        .ok()
+        .and_then(|columns| columns.parse().ok()) {
+        return columns;
+    }
+    match terminal_size::terminal_size() {
+        Some((terminal_size::Width(width), _)) => width as usize,
+        None => 80,
+    }
+}
+
+/// The category IDs of the board when none is given, from the
+/// environment variable TICKETS_BOARD_COLUMNS or the option
+/// `board.columns`, separated by whitespace.
+pub fn default_columns() -> Option<Vec<String>> {
+    let columns = std::env::var("TICKETS_BOARD_COLUMNS")
+        .ok()
+        .or_else(|| super::config::get("board.columns"))?;
+    let columns: Vec<String> = columns
+        .split_whitespace()
+        .map(|column| column.to_string())
+        .collect();
+    if columns.is_empty() {
+        None
+    }
+    else {
+        Some(columns)
+    }
+}
+
+/// Cut the text to fit in the display width with an ellipsis,
+/// and pad it with spaces.
+pub fn fit(text: &str, width: usize) -> String {
+    let text_width = UnicodeWidthStr::width(text);
+    if text_width <= width {
+        return format!("{}{}", text, " ".repeat(width - text_width));
+    }
+    if width == 0 {
+        return String::new();
+    }
+    let mut fitted = String::new();
+    let mut fitted_width = 0;
+    for ch in text.chars() {
+        let ch_width = UnicodeWidthChar::width(ch).unwrap_or(0);
+        if fitted_width + ch_width + 1 > width {
+            break;
+        }
+        fitted.push(ch);
+        fitted_width += ch_width;
+    }
+    fitted.push('…');
+    fitted_width += 1;
+    format!("{}{}", fitted, " ".repeat(width - fitted_width))
+}
+
+/// Render the categories as columns side by side in the given width.
+pub fn render(categories: &[Ticket], width: usize) -> io::Result<String> {
+    if categories.is_empty() {
+        return Ok(String::from("NO TICKETS."));
+    }
+    let mut columns: Vec<Vec<String>> = Vec::new();
+    for category in categories {
+        let tickets = category.collect()?;
+        let mut column = vec![
+            format!("{}/ ({})", category.category, tickets.len()),
+        ];
+        for ticket in &tickets {
+            let id = ticket.id.as_ref().map(|id_| &id_[..]).unwrap_or("");
+            let title = ticket.title.as_ref().map(|t| &t[..]).unwrap_or("");
+            column.push(format!("[{}]{}", id, title));
+        }
+        columns.push(column);
+    }
+
+    let count = columns.len();
+    let column_width = (width.saturating_sub(GAP.len() * (count - 1)) / count)
+        .max(MIN_COLUMN_WIDTH);
+    let height = columns.iter().map(|column| column.len()).max().unwrap();
+
+    let mut lines: Vec<String> = Vec::new();
+    for row in 0..=height {
+        let cells: Vec<String> = columns
+            .iter()
+            .map(|column| {
+                if row == 1 {
+                    return "-".repeat(column_width);
+                }
+                let index = if row == 0 { 0 } else { row - 1 };
+                match column.get(index) {
+                    Some(text) => fit(text, column_width),
+                    None => " ".repeat(column_width),
+                }
+            })
+            .collect();
+        lines.push(cells.join(GAP).trim_end().to_string());
+    }
+    Ok(lines.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn fit_short_text() {
+        assert_eq!(super::fit("abc", 5), "abc  ");
+        assert_eq!(super::fit("abcde", 5), "abcde");
+    }
+
+    #[test]
+    fn fit_long_text_with_ellipsis() {
+        assert_eq!(super::fit("abcdef", 5), "abcd…");
+        assert_eq!(super::fit("abcdef", 1), "…");
+    }
+
+    #[test]
+    fn fit_wide_text() {
+        // every hangul syllable takes two columns
+        assert_eq!(super::fit("가나다라", 8), "가나다라");
+        assert_eq!(super::fit("가나다라", 7), "가나다…");
+        assert_eq!(super::fit("가나다라", 6), "가나… ");
+    }
+}
@@ -0,0 +1,118 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path;
+
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, Local, Utc};
+
+const HEADER_AUTHOR: &str = "Author: ";
+const HEADER_DATE: &str = "Date: ";
+
+/// Who is writing a comment, taken from the environment variable USER.
+pub fn current_author() -> String {
+    match std::env::var("USER") {
+        Ok(user) if !user.is_empty() => user,
+        _ => String::from("anonymous"),
+    }
+}
+
+const DEFAULT_DATE_FORMAT: &str = "%Y-%m-%d %H:%M";
+
+/// The format of the dates to display, from the option `core.date_format`
+/// in the syntax of strftime.
+pub fn date_format() -> String {
+    match super::config::get("core.date_format") {
+        Some(format) if is_valid_format(&format) => format,
+        _ => String::from(DEFAULT_DATE_FORMAT),
+    }
+}
+
+fn is_valid_format(format: &str) -> bool {
+    StrftimeItems::new(format).all(|item| item != Item::Error)
+}
+
+#[derive(Debug)]
+pub struct Comment {
+    pub author: String,
+    pub created_at: DateTime<Local>,
+    pub body: String,
+}
+
+impl fmt::Display for Comment {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "[{}] {}\n{}",
+               self.created_at.format(&date_format()),
+               self.author,
+               self.body)
+    }
+}
+
+impl Comment {
+    pub fn new(author: String, body: String) -> Comment {
+        Comment {
+            author,
+            created_at: Local::now(),
+            body: body.trim().to_string(),
+        }
+    }
+
+    /// The file name of the comment, which sorts in chronological order.
+    pub fn file_name(&self) -> String {
+        self.created_at
+            .with_timezone(&Utc)
+            .format("%Y%m%d%H%M%S%f")
+            .to_string()
+    }
+
+    pub fn write(&self, dir: &path::Path) -> io::Result<()> {
+        fs::create_dir_all(dir)?;
+        let contents = format!("{}{}\n{}{}\n\n{}\n",
+                               HEADER_AUTHOR, self.author,
+                               HEADER_DATE, self.created_at.to_rfc3339(),
+                               self.body);
+        fs::write(dir.join(self.file_name()), contents)
+    }
+
+    pub fn read(path: &path::Path) -> io::Result<Comment> {
+        let contents = fs::read_to_string(path)?;
+        Comment::parse(&contents).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidData,
+                           format!("Invalid comment {}", path.display()))
+        })
+    }
+
+    fn parse(contents: &str) -> Option<Comment> {
+        let mut lines = contents.splitn(4, '\n');
+        let author = lines.next()?.strip_prefix(HEADER_AUTHOR)?;
+        let date = lines.next()?.strip_prefix(HEADER_DATE)?;
+        let created_at = DateTime::parse_from_rfc3339(date).ok()?;
+        lines.next()?;
+        let body = lines.next().unwrap_or("");
+        Some(
+            Comment {
+                author: author.to_string(),
+                created_at: created_at.with_timezone(&Local),
+                body: body.trim().to_string(),
+            }
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn parse_a_comment() {
+        let comment = super::Comment::parse(
+            "Author: someone\nDate: 2019-01-02T03:04:05+00:00\n\nHello\nworld\n"
+        ).unwrap();
+        assert_eq!(comment.author, "someone");
+        assert_eq!(comment.body, "Hello\nworld");
+        assert_eq!(comment.file_name(), "20190102030405000000000");
+    }
+
+    #[test]
+    fn fail_to_parse_a_comment_without_headers() {
+        assert!(super::Comment::parse("Hello\nworld\n").is_none());
+    }
+}
@@ -0,0 +1,191 @@
+use std::io;
+
+use structopt::clap::Shell;
+use structopt::StructOpt;
+
+use super::opt::{Opt, ID_SUBCOMMANDS};
+
+/// Positional arguments of identifiers.
+const ID_ARGS: &[&str] = &["id", "dest_id", "ids"];
+
+const BASH_IDS: &str = r#"
+_tickets_ids() {
+    local cur="${COMP_WORDS[COMP_CWORD]}"
+    local prev="${COMP_WORDS[COMP_CWORD-1]}"
+    local i subcmd=""
+    for (( i = 1; i < COMP_CWORD; i++ )); do
+        if [[ ${COMP_WORDS[i]} != -* && ${COMP_WORDS[i-1]} != --color ]]; then
+            subcmd="${COMP_WORDS[i]}"
+            break
+        fi
+    done
+    _tickets "$@"
+    case "${subcmd}" in
+        SUBCOMMANDS)
+            if [[ ${cur} != -* && ${prev} != @(-m|--message|--color) ]]; then
+                COMPREPLY=( $(compgen -W "$(tickets __complete "${cur}" 2>/dev/null)" -- "${cur}") )
+                if [[ ${#COMPREPLY[@]} -eq 1 && ${COMPREPLY[0]} == */ ]]; then
+                    compopt -o nospace
+                fi
+            fi
+            ;;
+    esac
+}
+
+complete -F _tickets_ids -o bashdefault -o default tickets
+"#;
+
+const ZSH_IDS: &str = r#"(( $+functions[_tickets_ids] )) ||
+_tickets_ids() {
+    local -a ids
+    ids=(${(f)"$(tickets __complete "$PREFIX" 2>/dev/null)"})
+    compadd -S '' -- ${(M)ids:#*/}
+    compadd -- ${ids:#*/}
+}
+
+"#;
+
+const FISH_IDS: &str = "complete -c tickets \
+-n \"__fish_seen_subcommand_from SUBCOMMANDS\" \
+-f -a \"(tickets __complete (commandline -ct))\"\n";
+
+/// Complete the identifiers for the positional arguments of identifiers,
+/// instead of the file names.
+fn complete_zsh_ids(script: &str) -> String {
+    let lines: Vec<String> = script
+        .split('\n')
+        .map(|line| {
+            let name = line
+                .trim_start_matches(&['\'', '*', ':'][..])
+                .split(' ')
+                .next()
+                .unwrap_or("");
+            if line.starts_with('\'') && ID_ARGS.contains(&name) {
+                line.replace(":_files'", ":_tickets_ids'")
+            }
+            else {
+                line.to_string()
+            }
+        })
+        .collect();
+    lines.join("\n")
+}
+
+/// The completion script of the shell, for the subcommands and
+/// the options from the definition, with the identifiers of the store
+/// from `tickets __complete`.
+pub fn script(shell: &str) -> Result<String, String> {
+    let for_shell: Shell = shell.parse()?;
+    let mut generated = Vec::new();
+    Opt::clap().gen_completions_to("tickets", for_shell, &mut generated);
+    let generated = String::from_utf8_lossy(&generated).into_owned();
+
+    match for_shell {
+        Shell::Bash => {
+            let ids = BASH_IDS.replace("SUBCOMMANDS", &ID_SUBCOMMANDS.join("|"));
+            let generated = generated
+                .trim_end()
+                .trim_end_matches("complete -F _tickets -o bashdefault -o default tickets")
+                .trim_end();
+            Ok(format!("{}\n{}", generated, ids))
+        },
+        Shell::Zsh => {
+            let generated = complete_zsh_ids(&generated);
+            // the functions must be defined before the last call
+            let index_call = generated
+                .rfind("_tickets \"$@\"")
+                .unwrap_or(generated.len());
+            Ok(format!("{}{}{}\n",
+                       &generated[..index_call],
+                       ZSH_IDS,
+                       &generated[index_call..]))
+        },
+        Shell::Fish => {
+            Ok(format!("{}{}",
+                       generated,
+                       FISH_IDS.replace("SUBCOMMANDS", &ID_SUBCOMMANDS.join(" "))))
+        },
+        _ => Err(format!("Unsupported shell: {}", shell)),
+    }
+}
+
+/// The identifiers of the existing categories and tickets starting with
+/// the partial one: the categories without a slash, and the tickets
+/// of the category with it.
+pub fn candidates(partial: &str) -> io::Result<Vec<String>> {
+    let mut candidates: Vec<String> = Vec::new();
+    match partial.find('/') {
+        None => {
+            for category in super::collect_categories()? {
+                candidates.push(format!("{}/", category.category));
+            }
+        },
+        Some(index_slash) => {
+            let id_category = format!("{}/", &partial[..index_slash]);
+            let category = match super::ticket::Ticket::from(id_category, None) {
+                Ok(category) => category,
+                Err(_) => return Ok(candidates),
+            };
+            if !category.path.is_dir() {
+                return Ok(candidates);
+            }
+            for ticket in category.collect()? {
+                if let Some(id) = &ticket.id {
+                    candidates.push(format!("{}/{}", category.category, id));
+                }
+            }
+        },
+    }
+    candidates.retain(|candidate| candidate.starts_with(partial));
+    candidates.sort();
+    Ok(candidates)
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn complete_zsh_ids_instead_of_files() {
+        let script = "'--raw[Print]' \\\n\
+                      ':id -- Target identifier:_files' \\\n\
+                      ':dest_id -- Destination identifier:_files' \\\n\
+                      '*::paths -- Files to attach:_files' \\";
+        assert_eq!(super::complete_zsh_ids(script),
+                   "'--raw[Print]' \\\n\
+                   ':id -- Target identifier:_tickets_ids' \\\n\
+                   ':dest_id -- Destination identifier:_tickets_ids' \\\n\
+                   '*::paths -- Files to attach:_files' \\");
+    }
+
+    #[test]
+    fn id_subcommands_are_defined() {
+        use structopt::clap::ErrorKind;
+        use structopt::StructOpt;
+        let kind_of = |name: &str| super::Opt::clap()
+            .get_matches_from_safe(vec!["tickets", name, "--help"])
+            .unwrap_err()
+            .kind;
+        for name in super::ID_SUBCOMMANDS {
+            assert_eq!(kind_of(name), ErrorKind::HelpDisplayed, "{}", name);
+        }
+        assert_ne!(kind_of("undefined"), ErrorKind::HelpDisplayed);
+    }
+
+    #[test]
+    fn candidates_of_categories_and_tickets() {
+        super::super::purge().unwrap_or(());
+        super::super::initialize_root().unwrap_or(());
+        let path_root = super::super::get_path_root();
+        std::fs::create_dir(path_root.join("open/")).unwrap();
+        std::fs::create_dir(path_root.join("in-progress/")).unwrap();
+        std::fs::write(path_root.join("open/ID-1"), "One").unwrap();
+        std::fs::write(path_root.join("open/ID-2"), "Two").unwrap();
+        std::fs::write(path_root.join("open/other"), "Other").unwrap();
+
+        assert_eq!(super::candidates("").unwrap(),
+                   vec!["in-progress/", "open/"]);
+        assert_eq!(super::candidates("op").unwrap(), vec!["open/"]);
+        assert_eq!(super::candidates("open/ID").unwrap(),
+                   vec!["open/ID-1", "open/ID-2"]);
+        assert!(super::candidates("never/").unwrap().is_empty());
+    }
+}
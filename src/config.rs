@@ -0,0 +1,273 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use toml::value::{Table, Value};
+
+/// The file of the options in each scope, read in this order
+/// so that the later one overrides the former.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Scope {
+    System,
+    Global,
+    Local,
+}
+
+impl Scope {
+    pub fn path(self) -> PathBuf {
+        match self {
+            Scope::System => PathBuf::from("/etc/tickets/config.toml"),
+            Scope::Global => dirs::home_dir()
+                .unwrap()
+                .join(".config/tickets/config.toml"),
+            // inside the root, so that each store has its own
+            Scope::Local => super::get_path_root().join("config.toml"),
+        }
+    }
+}
+
+fn read_table(scope: Scope) -> io::Result<Table> {
+    let path = scope.path();
+    let content = match fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(ref error) if error.kind() == io::ErrorKind::NotFound => {
+            return Ok(Table::new());
+        },
+        Err(error) => return Err(error),
+    };
+    match content.parse::<Value>() {
+        Ok(Value::Table(table)) => Ok(table),
+        Ok(_) => Ok(Table::new()),
+        Err(error) => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{}: {}", path.display(), error))),
+    }
+}
+
+/// The options of the files read so far, by their paths, for each file
+/// to be read once however many times the options are looked up.
+static CACHE: Mutex<BTreeMap<PathBuf, BTreeMap<String, String>>> =
+    Mutex::new(BTreeMap::new());
+
+/// The options of a scope from the cache, ignoring a broken file.
+fn cached_scope(scope: Scope) -> BTreeMap<String, String> {
+    let path = scope.path();
+    let cached = CACHE.lock()
+        .unwrap_or_else(|error| error.into_inner())
+        .get(&path)
+        .cloned();
+    // read without the lock, for the local scope looks up the root
+    cached.unwrap_or_else(|| {
+        let options = list_scope(scope).unwrap_or_default();
+        CACHE.lock()
+            .unwrap_or_else(|error| error.into_inner())
+            .insert(path, options.clone());
+        options
+    })
+}
+
+fn write_table(scope: Scope, table: Table) -> io::Result<()> {
+    let path = scope.path();
+    if scope == Scope::Local && !super::get_path_root().exists() {
+        return Err(io::Error::new(io::ErrorKind::NotFound,
+                                  "NOT INITIALIZED, PLEASE init."));
+    }
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let content = toml::to_string(&Value::Table(table))
+        .map_err(|error| io::Error::new(io::ErrorKind::InvalidData,
+                                        error.to_string()))?;
+    CACHE.lock()
+        .unwrap_or_else(|error| error.into_inner())
+        .remove(&path);
+    fs::write(path, content)
+}
+
+/// The value as a plain string, with the items of an array
+/// separated by spaces.
+fn to_plain(value: &Value) -> String {
+    match value {
+        Value::String(string) => string.clone(),
+        Value::Array(array) => array
+            .iter()
+            .map(to_plain)
+            .collect::<Vec<String>>()
+            .join(" "),
+        _ => value.to_string(),
+    }
+}
+
+/// Flatten the tables into the dotted keys like `core.editor`.
+fn flatten(prefix: &str, table: &Table, options: &mut BTreeMap<String, String>) {
+    for (key, value) in table {
+        let key = if prefix.is_empty() {
+            key.clone()
+        } else {
+            format!("{}.{}", prefix, key)
+        };
+        match value {
+            Value::Table(table) => flatten(&key, table, options),
+            _ => {
+                options.insert(key, to_plain(value));
+            },
+        }
+    }
+}
+
+fn split_key(key: &str) -> Result<Vec<&str>, io::Error> {
+    let parts: Vec<&str> = key.split('.').collect();
+    if parts.len() < 2 || parts.iter().any(|part| part.is_empty()) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Invalid key {}, expected a section like core.editor",
+                    key)));
+    }
+    Ok(parts)
+}
+
+fn insert(table: &mut Table, parts: &[&str], value: String) {
+    if parts.len() == 1 {
+        table.insert(parts[0].to_string(), Value::String(value));
+        return;
+    }
+    let entry = table
+        .entry(parts[0].to_string())
+        .or_insert_with(|| Value::Table(Table::new()));
+    if !entry.is_table() {
+        *entry = Value::Table(Table::new());
+    }
+    if let Value::Table(inner) = entry {
+        insert(inner, &parts[1..], value);
+    }
+}
+
+fn remove(table: &mut Table, parts: &[&str]) -> bool {
+    if parts.len() == 1 {
+        return table.remove(parts[0]).is_some();
+    }
+    let removed = match table.get_mut(parts[0]) {
+        Some(Value::Table(inner)) => remove(inner, &parts[1..]),
+        _ => false,
+    };
+    // no empty section is left behind
+    if let Some(Value::Table(inner)) = table.get(parts[0]) {
+        if inner.is_empty() {
+            table.remove(parts[0]);
+        }
+    }
+    removed
+}
+
+/// The options of a scope.
+pub fn list_scope(scope: Scope) -> io::Result<BTreeMap<String, String>> {
+    let mut options = BTreeMap::new();
+    flatten("", &read_table(scope)?, &mut options);
+    Ok(options)
+}
+
+/// The options of all scopes merged.
+pub fn list() -> io::Result<BTreeMap<String, String>> {
+    let mut options = BTreeMap::new();
+    for scope in &[Scope::System, Scope::Global, Scope::Local] {
+        options.append(&mut list_scope(*scope)?);
+    }
+    Ok(options)
+}
+
+/// The value of an option, ignoring the broken files.
+pub fn get(key: &str) -> Option<String> {
+    [Scope::Local, Scope::Global, Scope::System]
+        .iter()
+        .find_map(|scope| cached_scope(*scope).remove(key))
+}
+
+/// The root of the store from the option `core.root`, which is read
+/// only from the system and global scopes for the local one is inside it.
+pub fn root() -> Option<PathBuf> {
+    let root = [Scope::Global, Scope::System]
+        .iter()
+        .find_map(|scope| cached_scope(*scope).remove("core.root"))?;
+    match root.strip_prefix("~/") {
+        Some(rest) => dirs::home_dir().map(|home| home.join(rest)),
+        None => Some(PathBuf::from(root)),
+    }
+}
+
+pub fn set(scope: Scope, key: &str, value: &str) -> io::Result<()> {
+    let parts = split_key(key)?;
+    let mut table = read_table(scope)?;
+    insert(&mut table, &parts, value.to_string());
+    write_table(scope, table)
+}
+
+pub fn unset(scope: Scope, key: &str) -> io::Result<()> {
+    let parts = split_key(key)?;
+    let mut table = read_table(scope)?;
+    if !remove(&mut table, &parts) {
+        return Err(io::Error::new(io::ErrorKind::NotFound,
+                                  format!("The key {} is not set.", key)));
+    }
+    write_table(scope, table)
+}
+
+#[cfg(test)]
+mod tests {
+    use toml::value::{Table, Value};
+
+    #[test]
+    fn flatten_sections() {
+        let table: Value = "[core]\neditor = \"vim\"\n\
+                            [board]\ncolumns = [\"open\", \"done\"]\n\
+                            [a.b]\nc = 1\n"
+            .parse()
+            .unwrap();
+        let mut options = std::collections::BTreeMap::new();
+        super::flatten("", table.as_table().unwrap(), &mut options);
+        assert_eq!(options.get("core.editor").unwrap(), "vim");
+        assert_eq!(options.get("board.columns").unwrap(), "open done");
+        assert_eq!(options.get("a.b.c").unwrap(), "1");
+    }
+
+    #[test]
+    fn insert_and_remove_keys() {
+        let mut table = Table::new();
+        super::insert(&mut table, &["core", "editor"], String::from("vim"));
+        super::insert(&mut table, &["core", "sort"], String::from("id"));
+        assert_eq!(toml::to_string(&Value::Table(table.clone())).unwrap(),
+                   "[core]\neditor = \"vim\"\nsort = \"id\"\n");
+        assert!(super::remove(&mut table, &["core", "editor"]));
+        assert!(!super::remove(&mut table, &["core", "editor"]));
+        assert!(super::remove(&mut table, &["core", "sort"]));
+        assert!(table.is_empty());
+        assert!(super::split_key("editor").is_err());
+        assert!(super::split_key("core.").is_err());
+    }
+
+    #[test]
+    fn set_and_unset_local_options() {
+        super::super::purge().unwrap_or(());
+        super::super::initialize_root().unwrap_or(());
+        super::set(super::Scope::Local, "core.sort", "title").unwrap();
+        assert_eq!(super::get("core.sort"), Some(String::from("title")));
+        super::unset(super::Scope::Local, "core.sort").unwrap();
+        assert_eq!(super::list_scope(super::Scope::Local).unwrap().len(), 0);
+        assert!(super::unset(super::Scope::Local, "core.sort").is_err());
+    }
+
+    #[test]
+    fn read_each_file_once() {
+        super::super::purge().unwrap_or(());
+        super::super::initialize_root().unwrap_or(());
+        super::set(super::Scope::Local, "core.sort", "id").unwrap();
+        assert_eq!(super::get("core.sort"), Some(String::from("id")));
+        std::fs::write(super::Scope::Local.path(), "[core]\nsort = \"title\"\n")
+            .unwrap();
+        assert_eq!(super::get("core.sort"), Some(String::from("id")));
+        // writing the file reads it again
+        super::set(super::Scope::Local, "core.editor", "vim").unwrap();
+        assert_eq!(super::get("core.sort"), Some(String::from("title")));
+    }
+}
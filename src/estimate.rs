@@ -0,0 +1,121 @@
+use std::io;
+
+use super::ticket::Ticket;
+use super::timelog;
+
+/// The unit of estimates, set by the environment variable
+/// TICKETS_ESTIMATE_UNIT or the option `estimate.unit`
+/// as either `points` (default) or `hours`.
+#[derive(Debug, PartialEq)]
+pub enum Unit {
+    Points,
+    Hours,
+}
+
+impl Unit {
+    pub fn current() -> Unit {
+        let unit = std::env::var("TICKETS_ESTIMATE_UNIT")
+            .ok()
+            .or_else(|| super::config::get("estimate.unit"));
+        match unit {
+            Some(ref unit) if unit == "hours" => Unit::Hours,
+            _ => Unit::Points,
+        }
+    }
+
+    pub fn parse(&self, value: &str) -> Result<f64, String> {
+        let error = || format!("Invalid estimate {}", value);
+        let estimate = match self {
+            Unit::Hours if value.ends_with(|ch: char| ch.is_alphabetic()) => {
+                timelog::parse_duration(value).map_err(|_| error())? as f64
+                    / 3600.0
+            },
+            _ => value.parse().map_err(|_| error())?,
+        };
+        if estimate < 0.0 || !estimate.is_finite() {
+            return Err(error());
+        }
+        Ok(estimate)
+    }
+
+    pub fn format(&self, estimate: f64) -> String {
+        match self {
+            Unit::Points if estimate == 1.0 => String::from("1 point"),
+            Unit::Points => format!("{} points", estimate),
+            Unit::Hours => timelog::format_duration((estimate * 3600.0) as i64),
+        }
+    }
+}
+
+/// The estimate of a ticket, if any.
+pub fn of(ticket: &Ticket) -> io::Result<Option<f64>> {
+    let meta = ticket.meta()?;
+    Ok(meta.get("estimate").and_then(|value| value.parse().ok()))
+}
+
+/// The total estimate and the logged time of the tickets, summarized
+/// in a line, unless none of them is estimated nor tracked.
+pub fn rollup(tickets: &[Ticket], unit: &Unit) -> io::Result<Option<String>> {
+    let mut estimated = 0.0;
+    let mut any_estimate = false;
+    let mut logged = 0;
+    for ticket in tickets {
+        if let Some(estimate) = of(ticket)? {
+            estimated += estimate;
+            any_estimate = true;
+        }
+        let seconds = timelog::sum(ticket
+                                   .time_entries()?
+                                   .iter()
+                                   .map(|entry| entry.seconds))?;
+        logged = timelog::sum(vec![logged, seconds])?;
+    }
+    if !any_estimate && logged == 0 {
+        return Ok(None);
+    }
+
+    let mut summary: Vec<String> = Vec::new();
+    if any_estimate {
+        summary.push(format!("{} estimated", unit.format(estimated)));
+    }
+    if logged > 0 {
+        summary.push(format!("{} logged", timelog::format_duration(logged)));
+        if *unit == Unit::Hours && any_estimate {
+            let remaining = ((estimated * 3600.0) as i64)
+                .saturating_sub(logged);
+            if remaining >= 0 {
+                summary.push(format!("{} remaining",
+                                     timelog::format_duration(remaining)));
+            }
+            else {
+                summary.push(format!("{} over",
+                                     timelog::format_duration(
+                                         remaining.saturating_neg())));
+            }
+        }
+    }
+    Ok(Some(format!("TOTAL: {}", summary.join(", "))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Unit;
+
+    #[test]
+    fn parse_estimates() {
+        assert_eq!(Unit::Points.parse("3"), Ok(3.0));
+        assert_eq!(Unit::Points.parse("0.5"), Ok(0.5));
+        assert_eq!(Unit::Hours.parse("1.5"), Ok(1.5));
+        assert_eq!(Unit::Hours.parse("1h30m"), Ok(1.5));
+        assert_eq!(Unit::Points.parse("1h30m"),
+                   Err(String::from("Invalid estimate 1h30m")));
+        assert!(Unit::Points.parse("-1").is_err());
+    }
+
+    #[test]
+    fn format_estimates() {
+        assert_eq!(Unit::Points.format(1.0), "1 point");
+        assert_eq!(Unit::Points.format(2.5), "2.5 points");
+        assert_eq!(Unit::Hours.format(1.5), "1h30m");
+    }
+}
@@ -0,0 +1,246 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::io;
+use std::time::SystemTime;
+
+use chrono::{DateTime, Local};
+
+use super::super::ticket::Ticket;
+use super::import::Record;
+
+/// The columns of every ticket, before the ones of the metadata.
+const COLUMNS: [&str; 6] = ["category", "id", "title", "message",
+                            "created_at", "modified_at"];
+
+/// Quote the field if it has a comma, a quote or a newline.
+pub fn field(field: &str) -> String {
+    if field.contains(&[',', '"', '\n', '\r'][..]) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    }
+    else {
+        field.to_string()
+    }
+}
+
+/// Parse the rows of RFC 4180, where a quoted field may have
+/// doubled quotes and line breaks.
+pub fn parse(text: &str) -> Result<Vec<Vec<String>>, String> {
+    let mut rows = Vec::new();
+    let mut row: Vec<String> = Vec::new();
+    let mut field_ = String::new();
+    let mut in_quotes = false;
+    let mut line = 1;
+    let mut chars = text.chars().peekable();
+    while let Some(ch) = chars.next() {
+        if ch == '\n' {
+            line += 1;
+        }
+        if in_quotes {
+            match ch {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field_.push('"');
+                },
+                '"' => in_quotes = false,
+                _ => field_.push(ch),
+            }
+            continue;
+        }
+        match ch {
+            '"' if field_.is_empty() => in_quotes = true,
+            ',' => row.push(std::mem::take(&mut field_)),
+            '\r' if chars.peek() == Some(&'\n') => (),
+            '\n' => {
+                row.push(std::mem::take(&mut field_));
+                rows.push(std::mem::take(&mut row));
+            },
+            _ => field_.push(ch),
+        }
+    }
+    if in_quotes {
+        return Err(format!("Unterminated quote at line {}", line));
+    }
+    if !field_.is_empty() || !row.is_empty() {
+        row.push(field_);
+        rows.push(row);
+    }
+    // blank lines are not rows
+    rows.retain(|row_| !(row_.len() == 1 && row_[0].is_empty()));
+    Ok(rows)
+}
+
+fn timestamp(time: Option<SystemTime>) -> String {
+    time.map(|time_| DateTime::<Local>::from(time_).to_rfc3339())
+        .unwrap_or_default()
+}
+
+/// One row per ticket of the categories, with the columns of the
+/// metadata of any ticket after the common ones.
+pub fn export(categories: &[Ticket]) -> io::Result<String> {
+    let mut rows: Vec<(Vec<String>, BTreeMap<String, String>)> = Vec::new();
+    let mut keys: BTreeSet<String> = BTreeSet::new();
+    for category in categories {
+        for ticket in category.collect()? {
+            let created_at = fs::metadata(&ticket.path)
+                .and_then(|metadata| metadata.created())
+                .ok();
+            let meta = ticket.meta()?;
+            keys.extend(meta.keys().cloned());
+            rows.push((
+                vec![
+                    ticket.category.clone(),
+                    ticket.id.clone().unwrap_or_default(),
+                    ticket.title.clone().unwrap_or_default(),
+                    ticket.message.clone().unwrap_or_default(),
+                    timestamp(created_at),
+                    timestamp(ticket.modified_at),
+                ],
+                meta,
+            ));
+        }
+    }
+
+    let mut header: Vec<String> = COLUMNS.iter().map(|column| field(column))
+        .collect();
+    header.extend(keys.iter().map(|key| field(key)));
+    let mut lines = vec![header.join(",")];
+    for (columns, meta) in rows {
+        let mut fields: Vec<String> = columns.iter().map(|column| field(column))
+            .collect();
+        fields.extend(keys.iter().map(|key| {
+            field(meta.get(key).map(|value| &value[..]).unwrap_or(""))
+        }));
+        lines.push(fields.join(","));
+    }
+    Ok(lines.join("\n"))
+}
+
+/// The field of the ticket which a column goes into.
+#[derive(Debug, PartialEq)]
+enum Target {
+    Category,
+    Id,
+    Title,
+    Message,
+    Meta(String),
+    Ignored,
+}
+
+impl Target {
+    fn of(name: &str) -> Target {
+        match name {
+            "category" => Target::Category,
+            "id" => Target::Id,
+            "title" => Target::Title,
+            "message" => Target::Message,
+            // the timestamps are of the files, made by the import itself
+            "created_at" | "modified_at" | "-" => Target::Ignored,
+            _ => Target::Meta(name.to_string()),
+        }
+    }
+}
+
+/// Parse the mappings like `Summary=title` from the columns
+/// to the fields.
+fn parse_mapping(mapping: &[String]) -> Result<BTreeMap<String, String>, String> {
+    mapping
+        .iter()
+        .map(|pair| match pair.find('=') {
+            Some(index) => Ok((pair[..index].to_string(),
+                               pair[index + 1..].to_string())),
+            None => Err(format!("Invalid mapping {}, expected COLUMN=FIELD",
+                                pair)),
+        })
+        .collect()
+}
+
+/// The records of the rows, with the first row as the header.
+/// A column goes into the field of its name, unless mapped to another,
+/// and the unknown ones become the metadata.
+pub fn records(text: &str,
+               mapping: &[String],
+               category: Option<&str>) -> Result<Vec<Record>, String> {
+    let mapping = parse_mapping(mapping)?;
+    let mut rows = parse(text)?.into_iter();
+    let header = match rows.next() {
+        Some(header) => header,
+        None => return Ok(Vec::new()),
+    };
+    let targets: Vec<Target> = header
+        .iter()
+        .map(|column| {
+            Target::of(mapping.get(column).map(|name| &name[..])
+                       .unwrap_or(column))
+        })
+        .collect();
+
+    Ok(rows.map(|row| {
+        let mut record = Record {
+            category: category.unwrap_or("").to_string(),
+            ..Default::default()
+        };
+        for (target, value) in targets.iter().zip(row) {
+            match target {
+                Target::Category if !value.is_empty() => {
+                    record.category = value;
+                },
+                Target::Category => (),
+                Target::Id => record.id = value,
+                Target::Title => record.title = value,
+                Target::Message if !value.is_empty() => {
+                    record.message = Some(value);
+                },
+                Target::Meta(key) if !value.is_empty() => {
+                    record.meta.insert(key.clone(), value);
+                },
+                _ => (),
+            }
+        }
+        record
+    }).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    fn strings(fields: &[&str]) -> Vec<String> {
+        fields.iter().map(|field| field.to_string()).collect()
+    }
+
+    #[test]
+    fn quote_fields() {
+        assert_eq!(super::field("plain"), "plain");
+        assert_eq!(super::field("a, b"), "\"a, b\"");
+        assert_eq!(super::field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(super::field("two\nlines"), "\"two\nlines\"");
+    }
+
+    #[test]
+    fn parse_rows() {
+        let rows = super::parse("a,b,c\r\n1,\"x, \"\"y\"\"\",\"two\nlines\"\n\n4,,\n")
+            .unwrap();
+        assert_eq!(rows, vec![strings(&["a", "b", "c"]),
+                              strings(&["1", "x, \"y\"", "two\nlines"]),
+                              strings(&["4", "", ""])]);
+        assert_eq!(super::parse("a,b").unwrap(), vec![strings(&["a", "b"])]);
+        assert!(super::parse("a,\"b\n").is_err());
+    }
+
+    #[test]
+    fn records_with_mapping() {
+        let text = "Summary,id,category,Owner,created_at,Notes\n\
+                    First,ID-1,,alice,2019-01-02T00:00:00+00:00,x\n\
+                    Second,ID-2,done,,,\n";
+        let records = super::records(text,
+                                     &strings(&["Summary=title", "Notes=-"]),
+                                     Some("open"))
+            .unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].category, "open");
+        assert_eq!(records[0].title, "First");
+        assert_eq!(records[0].meta.get("Owner").unwrap(), "alice");
+        assert_eq!(records[0].meta.len(), 1);
+        assert_eq!(records[1].category, "done");
+        assert!(records[1].meta.is_empty());
+        assert!(super::records(text, &strings(&["Summary"]), None).is_err());
+    }
+}
@@ -0,0 +1,174 @@
+use chrono::{DateTime, Local};
+use serde_json::Value;
+
+use super::super::comment::Comment;
+use super::import::Record;
+
+/// The string of the first key present, as the REST API and
+/// `gh issue list --json` name some fields differently.
+fn string(issue: &Value, keys: &[&str]) -> Option<String> {
+    keys.iter()
+        .filter_map(|key| issue.get(key)?.as_str())
+        .next()
+        .map(|value| value.to_string())
+}
+
+/// The login of a user object of either form.
+fn login(user: &Value) -> Option<String> {
+    string(user, &["login", "name"])
+}
+
+fn date(value: Option<String>) -> Option<DateTime<Local>> {
+    DateTime::parse_from_rfc3339(&value?)
+        .ok()
+        .map(|date_| date_.with_timezone(&Local))
+}
+
+/// The names of the labels, given as objects or plain strings.
+fn labels(issue: &Value) -> Vec<String> {
+    issue.get("labels")
+        .and_then(|labels_| labels_.as_array())
+        .map(|labels_| labels_
+             .iter()
+             .filter_map(|label| match label.as_str() {
+                 Some(name) => Some(name.to_string()),
+                 None => string(label, &["name"]),
+             })
+             .collect())
+        .unwrap_or_default()
+}
+
+fn assignees(issue: &Value) -> Vec<String> {
+    issue.get("assignees")
+        .and_then(|assignees_| assignees_.as_array())
+        .map(|assignees_| assignees_.iter().filter_map(login).collect())
+        .unwrap_or_default()
+}
+
+/// The comments given inline by `gh issue list --json comments`;
+/// the REST API only counts them.
+fn comments(issue: &Value) -> Vec<Comment> {
+    issue.get("comments")
+        .and_then(|comments_| comments_.as_array())
+        .map(|comments_| comments_
+             .iter()
+             .filter_map(|comment| {
+                 let author = comment.get("author")
+                     .or_else(|| comment.get("user"))
+                     .and_then(login)
+                     .unwrap_or_else(|| String::from("anonymous"));
+                 let created_at = date(string(comment, &["createdAt",
+                                                         "created_at"]))?;
+                 let body = string(comment, &["body"])?;
+                 Some(Comment { author, created_at, body: body.trim().to_string() })
+             })
+             .collect())
+        .unwrap_or_default()
+}
+
+/// The records of the issues, each of which goes into the category
+/// of its state under its number. The pull requests are left out.
+pub fn records(text: &str,
+               category_open: &str,
+               category_closed: &str) -> Result<Vec<Record>, String> {
+    let value: Value = serde_json::from_str(text)
+        .map_err(|error| format!("Invalid JSON: {}", error))?;
+    let issues = match value.as_array() {
+        Some(issues) => issues,
+        None => return Err(String::from("Expected an array of issues.")),
+    };
+
+    let mut records = Vec::new();
+    for issue in issues {
+        if issue.get("pull_request").is_some() {
+            continue;
+        }
+        let id = match issue.get("number").and_then(|number| number.as_u64()) {
+            Some(number) => number.to_string(),
+            None => String::new(),
+        };
+        let state = string(issue, &["state"]).unwrap_or_default();
+        let category = if state.eq_ignore_ascii_case("closed") {
+            category_closed
+        } else {
+            category_open
+        };
+
+        let mut record = Record {
+            category: category.to_string(),
+            id,
+            title: string(issue, &["title"]).unwrap_or_default(),
+            message: string(issue, &["body"]),
+            comments: comments(issue),
+            ..Default::default()
+        };
+        let labels = labels(issue);
+        if !labels.is_empty() {
+            record.meta.insert(String::from("labels"), labels.join(", "));
+        }
+        let assignees = assignees(issue);
+        if !assignees.is_empty() {
+            record.meta.insert(String::from("assignees"), assignees.join(", "));
+        }
+        let author = issue.get("user")
+            .or_else(|| issue.get("author"))
+            .and_then(login);
+        let fields = [
+            ("author", author),
+            ("url", string(issue, &["html_url", "url"])),
+            ("created_at", string(issue, &["created_at", "createdAt"])),
+        ];
+        for (key, value) in fields.iter() {
+            if let Some(value_) = value {
+                record.meta.insert(key.to_string(), value_.clone());
+            }
+        }
+        records.push(record);
+    }
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn records_of_the_rest_api() {
+        let text = r#"[
+            {"number": 1, "state": "open", "title": "First", "body": "Body",
+             "labels": [{"name": "bug"}, {"name": "good first issue"}],
+             "assignees": [{"login": "alice"}], "user": {"login": "bob"},
+             "html_url": "https://github.com/o/r/issues/1", "comments": 2},
+            {"number": 2, "state": "closed", "title": "Second", "body": null},
+            {"number": 3, "state": "open", "title": "A PR",
+             "pull_request": {}}
+        ]"#;
+        let records = super::records(text, "open", "closed").unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].category, "open");
+        assert_eq!(records[0].id, "1");
+        assert_eq!(records[0].message, Some(String::from("Body")));
+        assert_eq!(records[0].meta.get("labels").unwrap(),
+                   "bug, good first issue");
+        assert_eq!(records[0].meta.get("assignees").unwrap(), "alice");
+        assert_eq!(records[0].meta.get("author").unwrap(), "bob");
+        assert!(records[0].comments.is_empty());
+        assert_eq!(records[1].category, "closed");
+        assert_eq!(records[1].message, None);
+    }
+
+    #[test]
+    fn records_of_gh() {
+        let text = r#"[
+            {"number": 7, "state": "CLOSED", "title": "Done",
+             "labels": [{"name": "docs"}], "author": {"login": "carol"},
+             "comments": [{"author": {"login": "dave"}, "body": "Fixed.\n",
+                           "createdAt": "2019-01-02T03:04:05Z"}]}
+        ]"#;
+        let records = super::records(text, "todo", "done").unwrap();
+        assert_eq!(records[0].category, "done");
+        assert_eq!(records[0].meta.get("author").unwrap(), "carol");
+        assert_eq!(records[0].comments.len(), 1);
+        assert_eq!(records[0].comments[0].author, "dave");
+        assert_eq!(records[0].comments[0].body, "Fixed.");
+        assert!(super::records("{}", "open", "closed").is_err());
+    }
+}
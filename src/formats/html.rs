@@ -0,0 +1,299 @@
+use std::fs;
+use std::io;
+use std::path;
+
+use pulldown_cmark::{html, CowStr, Event, Parser, Tag};
+use serde_json::json;
+
+use super::super::comment;
+use super::super::ticket::Ticket;
+
+const STYLE: &str = "\
+body { font-family: sans-serif; max-width: 50em; margin: 2em auto; \
+padding: 0 1em; line-height: 1.5; color: #222; }
+a { color: #0645ad; text-decoration: none; }
+a:hover { text-decoration: underline; }
+nav { font-size: 0.9em; margin-bottom: 1em; }
+code, pre { background: #f4f4f4; }
+pre { padding: 0.5em; overflow-x: auto; }
+.id { font-family: monospace; color: #666; }
+.meta, .comment-header { font-size: 0.9em; color: #666; }
+.comment { border-left: 3px solid #ddd; padding-left: 1em; margin: 1em 0; }
+#search { width: 100%; padding: 0.5em; font-size: 1em; }
+";
+
+const SEARCH: &str = "\
+var input = document.getElementById('search');
+var results = document.getElementById('results');
+input.addEventListener('input', function () {
+  var words = input.value.toLowerCase().split(/\\s+/).filter(Boolean);
+  results.innerHTML = '';
+  if (words.length === 0) { return; }
+  TICKETS.filter(function (ticket) {
+    var text = (ticket.id + ' ' + ticket.title + ' ' + ticket.text)
+      .toLowerCase();
+    return words.every(function (word) { return text.indexOf(word) >= 0; });
+  }).forEach(function (ticket) {
+    var item = document.createElement('li');
+    var link = document.createElement('a');
+    link.href = ticket.url;
+    link.textContent = '[' + ticket.id + '] ' + ticket.title;
+    item.appendChild(link);
+    results.appendChild(item);
+  });
+});
+";
+
+pub fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+/// Escape a file name to be a part of a relative URL.
+fn escape_url(name: &str) -> String {
+    let mut escaped = String::new();
+    for byte in name.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9'
+                | b'-' | b'_' | b'.' | b'~' => escaped.push(byte as char),
+            _ => escaped.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    escaped
+}
+
+/// Whether the destination of a link or an image is relative,
+/// or of a scheme which runs nothing in the browser.
+fn is_safe_url(url: &str) -> bool {
+    // browsers ignore these inside a scheme, as in `java\tscript:`
+    let url: String = url
+        .chars()
+        .filter(|c| !c.is_ascii_whitespace() && !c.is_control())
+        .collect();
+    match url.find([':', '/', '?', '#']) {
+        Some(index) if url[index..].starts_with(':') => {
+            let scheme = url[..index].to_ascii_lowercase();
+            scheme == "http" || scheme == "https" || scheme == "mailto"
+        },
+        _ => true,
+    }
+}
+
+fn safe_url(url: CowStr) -> CowStr {
+    if is_safe_url(&url) {
+        url
+    } else {
+        CowStr::Borrowed("#")
+    }
+}
+
+/// Render Markdown into HTML, escaping any raw HTML in it
+/// and dropping the links and images of the other URL schemes.
+pub fn markdown(text: &str) -> String {
+    let parser = Parser::new(text).map(|event| match event {
+        Event::Html(raw) => Event::Text(raw),
+        Event::Start(Tag::Link(kind, url, title)) => {
+            Event::Start(Tag::Link(kind, safe_url(url), title))
+        },
+        Event::Start(Tag::Image(kind, url, title)) => {
+            Event::Start(Tag::Image(kind, safe_url(url), title))
+        },
+        _ => event,
+    });
+    let mut rendered = String::new();
+    html::push_html(&mut rendered, parser);
+    rendered
+}
+
+fn page(title: &str, depth: usize, body: &str) -> String {
+    let up = "../".repeat(depth);
+    format!("<!DOCTYPE html>
+<html>
+<head>
+<meta charset=\"utf-8\">
+<title>{title}</title>
+<link rel=\"stylesheet\" href=\"{up}style.css\">
+</head>
+<body>
+{body}
+</body>
+</html>
+", title = escape(title), up = up, body = body)
+}
+
+fn ticket_name(ticket: &Ticket) -> &str {
+    ticket.id.as_ref().map(|id| &id[..]).unwrap_or("")
+}
+
+fn ticket_title(ticket: &Ticket) -> &str {
+    ticket.title.as_ref().map(|title| &title[..]).unwrap_or("")
+}
+
+/// The file of the page of a ticket, prefixed so that no ticket
+/// overwrites the `index.html` of its category.
+fn page_name(name: &str) -> String {
+    format!("t-{}.html", name)
+}
+
+fn list_tickets(tickets: &[Ticket], prefix: &str) -> String {
+    if tickets.is_empty() {
+        return String::from("<p>NO TICKETS.</p>\n");
+    }
+    let items: Vec<String> = tickets
+        .iter()
+        .map(|ticket| format!(
+            "<li><a href=\"{}{}\"><span class=\"id\">[{}]</span> {}</a></li>",
+            prefix,
+            escape_url(&page_name(ticket_name(ticket))),
+            escape(ticket_name(ticket)),
+            escape(ticket_title(ticket))))
+        .collect();
+    format!("<ul>\n{}\n</ul>\n", items.join("\n"))
+}
+
+fn render_ticket(ticket: &Ticket) -> io::Result<String> {
+    let mut body = format!(
+        "<nav><a href=\"../index.html\">tickets</a> / \
+        <a href=\"index.html\">{category}/</a></nav>
+<h1><span class=\"id\">[{id}]</span> {title}</h1>
+",
+        category = escape(&ticket.category),
+        id = escape(ticket_name(ticket)),
+        title = escape(ticket_title(ticket)));
+    let meta = ticket.meta()?;
+    if !meta.is_empty() {
+        let rows: Vec<String> = meta
+            .iter()
+            .map(|(key, value)| format!("<tr><th>{}</th><td>{}</td></tr>",
+                                        escape(key),
+                                        escape(value)))
+            .collect();
+        body.push_str(&format!("<table class=\"meta\">\n{}\n</table>\n",
+                               rows.join("\n")));
+    }
+    if let Some(message) = &ticket.message {
+        body.push_str(&markdown(message));
+    }
+    let comments = ticket.read_comments()?;
+    if !comments.is_empty() {
+        body.push_str("<h2>Comments</h2>\n");
+        for comment in comments {
+            body.push_str(&format!(
+                "<div class=\"comment\">\n<div class=\"comment-header\">\
+                {} at {}</div>\n{}</div>\n",
+                escape(&comment.author),
+                comment.created_at.format(&comment::date_format()),
+                markdown(&comment.body)));
+        }
+    }
+    Ok(page(&format!("[{}] {}", ticket_name(ticket), ticket_title(ticket)),
+            1,
+            &body))
+}
+
+/// Write a browsable site of the categories into the directory,
+/// overwriting the pages which already exist.
+pub fn export(categories: &[Ticket], out_dir: &path::Path) -> io::Result<()> {
+    fs::create_dir_all(out_dir)?;
+    fs::write(out_dir.join("style.css"), STYLE)?;
+
+    let mut index = String::from(
+        "<h1>tickets</h1>
+<input id=\"search\" type=\"search\" placeholder=\"Search tickets\">
+<ul id=\"results\"></ul>
+");
+    let mut search_index = Vec::new();
+    if categories.is_empty() {
+        index.push_str("<p>NO TICKETS.</p>\n");
+    }
+    for category in categories {
+        let tickets = category.collect()?;
+        let dir_category = out_dir.join(&category.category);
+        fs::create_dir_all(&dir_category)?;
+
+        let url_category = escape_url(&category.category);
+        index.push_str(&format!("<h2><a href=\"{}/index.html\">{}/</a></h2>\n",
+                                url_category,
+                                escape(&category.category)));
+        index.push_str(&list_tickets(&tickets, &format!("{}/", url_category)));
+
+        let body_category = format!(
+            "<nav><a href=\"../index.html\">tickets</a></nav>
+<h1>{}/</h1>
+{}",
+            escape(&category.category),
+            list_tickets(&tickets, ""));
+        fs::write(dir_category.join("index.html"),
+                  page(&format!("{}/", category.category), 1, &body_category))?;
+
+        for ticket in &tickets {
+            let name = ticket_name(ticket);
+            fs::write(dir_category.join(page_name(name)),
+                      render_ticket(ticket)?)?;
+            search_index.push(json!({
+                "id": format!("{}/{}", category.category, name),
+                "title": ticket_title(ticket),
+                "text": ticket.message.as_ref().map(|m| &m[..]).unwrap_or(""),
+                "url": format!("{}/{}",
+                               url_category,
+                               escape_url(&page_name(name))),
+            }));
+        }
+    }
+    index.push_str("<script src=\"search.js\"></script>\n");
+
+    // a script rather than JSON, so that browsers load it from file:// too
+    fs::write(out_dir.join("search.js"),
+              format!("var TICKETS = {};\n{}",
+                      serde_json::Value::Array(search_index),
+                      SEARCH))?;
+    fs::write(out_dir.join("index.html"), page("tickets", 0, &index))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn escape_html() {
+        assert_eq!(super::escape("<a href=\"x\">&</a>"),
+                   "&lt;a href=&quot;x&quot;&gt;&amp;&lt;/a&gt;");
+    }
+
+    #[test]
+    fn escape_url() {
+        assert_eq!(super::escape_url("ticket-1234"), "ticket-1234");
+        assert_eq!(super::escape_url("a b#?"), "a%20b%23%3F");
+    }
+
+    #[test]
+    fn render_markdown_without_raw_html() {
+        assert_eq!(super::markdown("Some *emphasis*\n\n- one\n- two"),
+                   "<p>Some <em>emphasis</em></p>\n<ul>\n<li>one</li>\n\
+                   <li>two</li>\n</ul>\n");
+        assert_eq!(super::markdown("<script>alert(1)</script>"),
+                   "&lt;script&gt;alert(1)&lt;/script&gt;");
+    }
+
+    #[test]
+    fn render_markdown_with_safe_urls_only() {
+        assert_eq!(super::markdown("[x](https://example.com) [y](../a.html) \
+                                   [z](mailto:a@example.com)"),
+                   "<p><a href=\"https://example.com\">x</a> \
+                   <a href=\"../a.html\">y</a> \
+                   <a href=\"mailto:a@example.com\">z</a></p>\n");
+        for text in &["[x](javascript:alert(1))",
+                      "[x](JavaScript:alert(1))",
+                      "[x](java&#09;script:alert(1))",
+                      "[x](&#106;avascript:alert(1))",
+                      "<javascript:alert(1)>",
+                      "[x](data:text/html,<script>alert(1)</script>)",
+                      "![x](vbscript:alert(1))"] {
+            let rendered = super::markdown(text);
+            assert!(rendered.contains("href=\"#\"")
+                    || rendered.contains("src=\"#\""), "{}", text);
+        }
+    }
+}
@@ -0,0 +1,166 @@
+use std::collections::BTreeMap;
+use std::io;
+
+use chrono::{DateTime, Local, NaiveDate, Utc};
+
+use super::super::ticket::Ticket;
+use super::taskwarrior;
+
+const DATE_TIME_FORMAT: &str = "%Y%m%dT%H%M%SZ";
+
+/// Escape the text of a property value.
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+/// Fold the line into the ones of 75 octets at most, each continued
+/// with a space, without splitting a character.
+fn fold(line: &str) -> String {
+    let mut folded = String::new();
+    let mut length = 0;
+    for ch in line.chars() {
+        if length + ch.len_utf8() > 75 {
+            folded.push_str("\r\n ");
+            length = 1;
+        }
+        folded.push(ch);
+        length += ch.len_utf8();
+    }
+    folded
+}
+
+/// The property DUE of a date like `2019-01-10`, or of a date-time.
+fn due(text: &str) -> Option<String> {
+    if let Ok(date) = NaiveDate::parse_from_str(text, "%Y-%m-%d") {
+        return Some(format!("DUE;VALUE=DATE:{}", date.format("%Y%m%d")));
+    }
+    let date = DateTime::parse_from_rfc3339(text).ok()?;
+    Some(format!("DUE:{}", date.with_timezone(&Utc).format(DATE_TIME_FORMAT)))
+}
+
+/// The status of a todo, from the metadata of todo.txt or Taskwarrior,
+/// or else from the name of the category.
+fn status(category: &str, meta: &BTreeMap<String, String>) -> &'static str {
+    if meta.contains_key("completed") {
+        return "COMPLETED";
+    }
+    let status_of = |name: &str| match name {
+        "done" | "closed" | "completed" | "resolved" => "COMPLETED",
+        "doing" | "in-progress" | "started" | "active" => "IN-PROCESS",
+        "cancelled" | "canceled" | "deleted" | "wontfix" => "CANCELLED",
+        _ => "NEEDS-ACTION",
+    };
+    // a pending task is still in progress by its category
+    match meta.get("status").map(|status_| status_of(status_)) {
+        Some(status_) if status_ != "NEEDS-ACTION" => status_,
+        _ => status_of(category),
+    }
+}
+
+/// The UID of the todo of the ticket, the one in its metadata if any,
+/// like from an import, or else derived from its ID, not its category,
+/// so that it stays the same in every export even after a move.
+fn uid(id: &str, meta: &BTreeMap<String, String>) -> String {
+    match meta.get("uid") {
+        Some(uid_) => uid_.clone(),
+        None => format!("{}@tickets", taskwarrior::uuid_of(id)),
+    }
+}
+
+fn vtodo(ticket: &Ticket, uid: &str, due: String,
+         meta: &BTreeMap<String, String>) -> Vec<String> {
+    let title = ticket.title.as_ref().map(|title_| &title_[..]).unwrap_or("");
+    let stamp = ticket.modified_at
+        .map(DateTime::<Local>::from)
+        .unwrap_or_else(Local::now)
+        .with_timezone(&Utc)
+        .format(DATE_TIME_FORMAT)
+        .to_string();
+    let mut lines = vec![
+        String::from("BEGIN:VTODO"),
+        // the same in every export, for the calendars to update the todo
+        format!("UID:{}", escape(uid)),
+        format!("DTSTAMP:{}", stamp),
+        format!("LAST-MODIFIED:{}", stamp),
+        format!("SUMMARY:{}", escape(title)),
+        due,
+        format!("STATUS:{}", status(&ticket.category, meta)),
+        format!("CATEGORIES:{}", escape(&ticket.category)),
+    ];
+    if let Some(message) = &ticket.message {
+        lines.push(format!("DESCRIPTION:{}", escape(message)));
+    }
+    lines.push(String::from("END:VTODO"));
+    lines
+}
+
+/// A calendar of the tickets with a due date, one todo each.
+pub fn export(categories: &[Ticket]) -> io::Result<String> {
+    let mut lines = vec![
+        String::from("BEGIN:VCALENDAR"),
+        String::from("VERSION:2.0"),
+        String::from("PRODID:-//tickets//tickets//EN"),
+    ];
+    for category in categories {
+        for ticket in category.collect()? {
+            let meta = ticket.meta()?;
+            if let Some(due_) = meta.get("due").and_then(|due_| due(due_)) {
+                let uid_ = uid(&ticket.id.clone().unwrap_or_default(), &meta);
+                lines.append(&mut vtodo(&ticket, &uid_, due_, &meta));
+            }
+        }
+    }
+    lines.push(String::from("END:VCALENDAR"));
+    let folded: Vec<String> = lines.iter().map(|line| fold(line)).collect();
+    Ok(format!("{}\r\n", folded.join("\r\n")))
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn escape_and_fold() {
+        assert_eq!(super::escape("a, b; c\\d\ne"), "a\\, b\\; c\\\\d\\ne");
+        let folded = super::fold(&format!("SUMMARY:{}", "é".repeat(40)));
+        let lines: Vec<&str> = folded.split("\r\n").collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines.iter().all(|line| line.len() <= 75));
+        assert!(lines[1].starts_with(' '));
+    }
+
+    #[test]
+    fn due_dates() {
+        assert_eq!(super::due("2019-01-10").unwrap(),
+                   "DUE;VALUE=DATE:20190110");
+        assert_eq!(super::due("2019-01-10T09:00:00+09:00").unwrap(),
+                   "DUE:20190110T000000Z");
+        assert_eq!(super::due("tomorrow"), None);
+    }
+
+    #[test]
+    fn uids() {
+        let mut meta = std::collections::BTreeMap::new();
+        let uid = super::uid("RENT", &meta);
+        assert!(uid.ends_with("@tickets"));
+        assert_eq!(uid, super::uid("RENT", &meta));
+        assert_ne!(uid, super::uid("LATER", &meta));
+        meta.insert(String::from("uid"), String::from("imported@example"));
+        assert_eq!(super::uid("RENT", &meta), "imported@example");
+    }
+
+    #[test]
+    fn statuses() {
+        let mut meta = std::collections::BTreeMap::new();
+        assert_eq!(super::status("open", &meta), "NEEDS-ACTION");
+        assert_eq!(super::status("in-progress", &meta), "IN-PROCESS");
+        assert_eq!(super::status("done", &meta), "COMPLETED");
+        meta.insert(String::from("status"), String::from("pending"));
+        assert_eq!(super::status("in-progress", &meta), "IN-PROCESS");
+        meta.insert(String::from("status"), String::from("deleted"));
+        assert_eq!(super::status("open", &meta), "CANCELLED");
+        meta.insert(String::from("completed"), String::from("2019-01-03"));
+        assert_eq!(super::status("open", &meta), "COMPLETED");
+    }
+}
@@ -0,0 +1,338 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::io;
+use std::str::FromStr;
+
+use super::super::comment::Comment;
+use super::super::ticket::Ticket;
+
+/// The metadata naming the source of a ticket, like the Message-ID
+/// of a mail, whose record is imported only once wherever it is moved.
+const META_SOURCES: [&str; 1] = ["message_id"];
+
+/// What to do with a record whose ticket already exists.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Conflict {
+    Skip,
+    Overwrite,
+    /// Import it as another ticket, with a suffix like `-2`.
+    Rename,
+}
+
+impl FromStr for Conflict {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Conflict, String> {
+        match s {
+            "skip" => Ok(Conflict::Skip),
+            "overwrite" => Ok(Conflict::Overwrite),
+            "rename" => Ok(Conflict::Rename),
+            _ => Err(format!("Invalid conflict handling {}", s)),
+        }
+    }
+}
+
+/// A ticket read from another format, to be imported.
+#[derive(Debug, Default)]
+pub struct Record {
+    pub category: String,
+    pub id: String,
+    pub title: String,
+    pub message: Option<String>,
+    pub meta: BTreeMap<String, String>,
+    pub comments: Vec<Comment>,
+    /// The names and the contents of the files to attach.
+    pub attachments: Vec<(String, Vec<u8>)>,
+}
+
+impl Record {
+    fn content(&self) -> String {
+        match &self.message {
+            Some(message) if !message.trim().is_empty() => {
+                format!("{}\n\n{}", self.title, message.trim())
+            },
+            _ => self.title.clone(),
+        }
+    }
+
+    /// Check the fields which make the identifier and the title.
+    fn validate(&self) -> Result<(), String> {
+        if self.category.is_empty() {
+            return Err(String::from("The category is missing."));
+        }
+        if self.id.is_empty() {
+            return Err(String::from("The id is missing."));
+        }
+        if self.category.contains('/') || self.id.contains('/') {
+            return Err(String::from("The category and the id cannot contain /."));
+        }
+        if self.category.starts_with('.') || self.id.starts_with('.') {
+            return Err(String::from("The category and the id cannot \
+                                     start with a dot."));
+        }
+        if self.category == super::super::hooks::DIR_HOOKS {
+            return Err(format!("The category {} is reserved for the hooks.",
+                               self.category));
+        }
+        if self.title.trim().is_empty() {
+            return Err(String::from("The title is missing."));
+        }
+        if self.title.contains(['\n', '\r']) {
+            return Err(String::from("The title cannot span lines."));
+        }
+        if self.meta.keys().any(|key| key.is_empty() || key.contains(':')
+                                || key.contains(['\n', '\r'])) {
+            return Err(String::from("The metadata keys cannot be empty \
+                                     or contain : and line breaks."));
+        }
+        // a line break would start another key in the file of the metadata
+        if self.meta.values().any(|value| value.contains(['\n', '\r'])) {
+            return Err(String::from("The metadata values cannot contain \
+                                     line breaks."));
+        }
+        if self.attachments.iter().any(|(name, _)| {
+            name.is_empty() || name.contains('/') || name.starts_with('.')
+        }) {
+            return Err(String::from("The attachment names cannot be empty, \
+                                     contain / or start with a dot."));
+        }
+        Ok(())
+    }
+}
+
+/// The first free identifier of the category, with a suffix like `-2`.
+fn rename(category: &str, id: &str, planned: &HashSet<String>) -> String {
+    let mut count = 2;
+    loop {
+        let renamed = format!("{}/{}-{}", category, id, count);
+        let exists = Ticket::from(renamed.clone(), None)
+            .map(|ticket| ticket.path.exists())
+            .unwrap_or(true);
+        if !exists && !planned.contains(&renamed) {
+            return renamed;
+        }
+        count += 1;
+    }
+}
+
+fn write(record: &Record, id: &str, overwrite: bool) -> io::Result<()> {
+    let ticket = Ticket::from(id.to_string(), Some(record.content()))
+        .map_err(|error| io::Error::new(io::ErrorKind::InvalidInput, error))?;
+    if overwrite {
+        ticket.write()?;
+        for key in ticket.meta()?.keys() {
+            ticket.set_meta(key, None)?;
+        }
+    }
+    else {
+        ticket.new()?;
+    }
+    for (key, value) in &record.meta {
+        ticket.set_meta(key, Some(value))?;
+    }
+    let path_comments = ticket.sidecar_path().join("comments");
+    for comment in &record.comments {
+        comment.write(&path_comments)?;
+    }
+    if !record.attachments.is_empty() {
+        let path_attachments = ticket.sidecar_path().join("attachments");
+        fs::create_dir_all(&path_attachments)?;
+        for (name, contents) in &record.attachments {
+            fs::write(path_attachments.join(name), contents)?;
+        }
+    }
+    Ok(())
+}
+
+/// The sources of the tickets in the store, by key and value.
+fn collect_sources() -> io::Result<HashMap<(String, String), String>> {
+    let mut sources = HashMap::new();
+    for ticket in super::super::collect_all()? {
+        let meta = ticket.meta()?;
+        for key in META_SOURCES.iter() {
+            if let Some(value) = meta.get(*key) {
+                sources.insert((key.to_string(), value.clone()),
+                               format!("{}/{}",
+                                       ticket.category,
+                                       ticket.id.clone().unwrap_or_default()));
+            }
+        }
+    }
+    Ok(sources)
+}
+
+fn sources_of(record: &Record) -> Vec<(String, String)> {
+    META_SOURCES.iter()
+        .filter_map(|key| {
+            record.meta.get(*key).map(|value| (key.to_string(), value.clone()))
+        })
+        .collect()
+}
+
+/// Import the records, creating the categories as needed, and report
+/// what is done to each of them. Nothing is written on a dry run.
+pub fn apply(records: &[Record],
+             conflict: Conflict,
+             dry_run: bool) -> io::Result<Vec<String>> {
+    let mut report = Vec::new();
+    let mut planned: HashSet<String> = HashSet::new();
+    let mut sources = if records.iter().any(|record| {
+        !sources_of(record).is_empty()
+    }) {
+        collect_sources()?
+    } else {
+        HashMap::new()
+    };
+    for record in records {
+        let id = format!("{}/{}", record.category, record.id);
+        if let Err(error) = record.validate() {
+            report.push(format!("INVALID {}: {}", id, error));
+            continue;
+        }
+        let imported = sources_of(record).into_iter()
+            .find_map(|source| sources.get(&source).cloned());
+        if let Some(imported_) = imported {
+            report.push(format!("SKIPPED {}, which is imported as {}",
+                                id,
+                                imported_));
+            continue;
+        }
+
+        let id_category = format!("{}/", record.category);
+        let category = Ticket::from(id_category.clone(), None)
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidInput,
+                                            error))?;
+        if !category.path.exists() && planned.insert(id_category.clone()) {
+            if !dry_run {
+                category.new()?;
+            }
+            report.push(format!("CREATED {}", id_category));
+        }
+
+        let exists = planned.contains(&id) || match Ticket::from(id.clone(),
+                                                                 None) {
+            Ok(ticket) => ticket.path.exists(),
+            Err(error) => {
+                report.push(format!("INVALID {}: {}", id, error));
+                continue;
+            },
+        };
+        let (id_written, line) = match (exists, conflict) {
+            (false, _) => (id.clone(), format!("CREATED {}", id)),
+            (true, Conflict::Skip) => {
+                report.push(format!("SKIPPED {}, which already exists", id));
+                continue;
+            },
+            (true, Conflict::Overwrite) => {
+                (id.clone(), format!("OVERWRITTEN {}", id))
+            },
+            (true, Conflict::Rename) => {
+                let renamed = rename(&record.category, &record.id, &planned);
+                (renamed.clone(), format!("RENAMED {} => {}", id, renamed))
+            },
+        };
+        if !dry_run {
+            write(record, &id_written, exists && id_written == id)?;
+        }
+        for source in sources_of(record) {
+            sources.insert(source, id_written.clone());
+        }
+        planned.insert(id_written);
+        report.push(line);
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    fn record(category: &str, id: &str, title: &str) -> super::Record {
+        super::Record {
+            category: category.to_string(),
+            id: id.to_string(),
+            title: title.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn apply_records() {
+        super::super::super::purge().unwrap_or(());
+        super::super::super::initialize_root().unwrap_or(());
+        let mut first = record("open", "ID-1", "First");
+        first.message = Some(String::from("Body"));
+        first.meta.insert(String::from("priority"), String::from("high"));
+        let records = vec![first,
+                           record("open", "ID-1", "Again"),
+                           record("open", "", "No id")];
+
+        let report = super::apply(&records, super::Conflict::Skip, true)
+            .unwrap();
+        assert_eq!(report, vec!["CREATED open/",
+                                "CREATED open/ID-1",
+                                "SKIPPED open/ID-1, which already exists",
+                                "INVALID open/: The id is missing."]);
+        assert!(!super::super::super::get_path_root().join("open").exists());
+
+        super::apply(&records, super::Conflict::Rename, false).unwrap();
+        let mut ticket = super::Ticket::from(String::from("open/ID-1"), None)
+            .unwrap();
+        ticket.read().unwrap();
+        assert_eq!(ticket.title, Some(String::from("First")));
+        assert_eq!(ticket.message, Some(String::from("Body")));
+        assert_eq!(ticket.meta().unwrap().get("priority").unwrap(), "high");
+        let mut renamed = super::Ticket::from(String::from("open/ID-1-2"),
+                                              None).unwrap();
+        renamed.read().unwrap();
+        assert_eq!(renamed.title, Some(String::from("Again")));
+
+        let report = super::apply(&records[1..2],
+                                  super::Conflict::Overwrite,
+                                  false).unwrap();
+        assert_eq!(report, vec!["OVERWRITTEN open/ID-1"]);
+        let mut ticket = super::Ticket::from(String::from("open/ID-1"), None)
+            .unwrap();
+        ticket.read().unwrap();
+        assert_eq!(ticket.title, Some(String::from("Again")));
+        assert!(ticket.meta().unwrap().is_empty());
+    }
+
+    #[test]
+    fn reject_line_breaks_in_metadata() {
+        let mut evil = record("open", "ID-1", "Evil");
+        evil.meta.insert(String::from("from"),
+                         String::from("a@example.com\r\nstatus: done"));
+        assert_eq!(evil.validate(),
+                   Err(String::from("The metadata values cannot contain \
+                                     line breaks.")));
+        let mut evil = record("open", "ID-1", "Evil\rtitle");
+        assert!(evil.validate().is_err());
+        evil.title = String::from("Fine");
+        assert_eq!(evil.validate(), Ok(()));
+    }
+
+    #[test]
+    fn reject_records_out_of_the_categories() {
+        super::super::super::purge().unwrap_or(());
+        super::super::super::initialize_root().unwrap_or(());
+        let records = super::super::todotxt::records(
+            "Evil +.. id:escaped\nHidden +open id:.hidden\n\
+            Current +. id:here\nHooked +hooks id:pre-new\n",
+            "inbox");
+
+        let report = super::apply(&records, super::Conflict::Overwrite, false)
+            .unwrap();
+        assert_eq!(report, vec![
+            "INVALID ../escaped: The category and the id cannot start \
+            with a dot.",
+            "INVALID open/.hidden: The category and the id cannot start \
+            with a dot.",
+            "INVALID ./here: The category and the id cannot start \
+            with a dot.",
+            "INVALID hooks/pre-new: The category hooks is reserved \
+            for the hooks."]);
+        let path_root = super::super::super::get_path_root();
+        assert!(!path_root.join("../escaped").exists());
+        assert!(!path_root.join("here").exists());
+        assert!(!path_root.join("hooks").exists());
+    }
+}
@@ -0,0 +1,182 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use mail_parser::mailbox::{maildir, mbox};
+use mail_parser::{Message, MessageParser, MimeHeaders};
+
+use super::import::Record;
+
+/// The contents of the messages of a Maildir, in the order of their
+/// file names, which begin with the time of delivery.
+fn read_maildir(path: &Path) -> io::Result<Vec<Vec<u8>>> {
+    let mut messages: Vec<maildir::Message> = maildir::MessageIterator::new(path)?
+        .collect::<io::Result<_>>()?;
+    messages.sort_by(|a, b| a.path().file_name().cmp(&b.path().file_name()));
+    Ok(messages.into_iter().map(|message| message.unwrap_contents()).collect())
+}
+
+fn read_mbox(path: &Path) -> io::Result<Vec<Vec<u8>>> {
+    let file = io::BufReader::new(fs::File::open(path)?);
+    mbox::MessageIterator::new(file)
+        .map(|message| message
+             .map(|message_| message_.unwrap_contents())
+             .map_err(|_| io::Error::new(io::ErrorKind::InvalidData,
+                                         "Invalid mbox.")))
+        .collect()
+}
+
+/// An identifier of the Message-ID, the same in every import,
+/// like `MAIL-0a1b2c3d4e5f6a7b`, long enough not to collide
+/// in any mailbox.
+fn id_of(message_id: &str) -> String {
+    let hash = message_id.bytes().fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    });
+    format!("MAIL-{:016x}", hash)
+}
+
+/// A name of an attachment to be a file of the ticket.
+fn file_name(name: Option<&str>, index: usize) -> String {
+    let name = name
+        .map(|name_| name_.rsplit(['/', '\\']).next().unwrap_or(""))
+        .map(|name_| name_.trim_start_matches('.').trim())
+        .unwrap_or("");
+    if name.is_empty() {
+        format!("attachment-{}", index + 1)
+    }
+    else {
+        name.to_string()
+    }
+}
+
+fn record(message: &Message, contents: &[u8], category: &str) -> Record {
+    // a message without one is known by its contents
+    let message_id = message.message_id()
+        .map(|message_id_| message_id_.to_string())
+        .unwrap_or_else(|| id_of(&String::from_utf8_lossy(contents)));
+    let title = message.subject()
+        .map(|subject| subject.split_whitespace().collect::<Vec<&str>>()
+             .join(" "))
+        .filter(|subject| !subject.is_empty())
+        .unwrap_or_else(|| String::from("(no subject)"));
+    let mut record = Record {
+        category: category.to_string(),
+        id: id_of(&message_id),
+        title,
+        message: message.body_text(0).map(|body| body.replace("\r\n", "\n")),
+        ..Default::default()
+    };
+    if let Some(from) = message.from().and_then(|from_| from_.first()) {
+        let from = match (from.name(), from.address()) {
+            (Some(name), Some(address)) => format!("{} <{}>", name, address),
+            (Some(name), None) => name.to_string(),
+            (None, Some(address)) => address.to_string(),
+            (None, None) => String::new(),
+        };
+        if !from.is_empty() {
+            record.meta.insert(String::from("from"), from);
+        }
+    }
+    if let Some(date) = message.date() {
+        record.meta.insert(String::from("date"), date.to_rfc3339());
+    }
+    record.meta.insert(String::from("message_id"), message_id);
+
+    for (index, part) in message.attachments().enumerate() {
+        let mut name = file_name(part.attachment_name(), index);
+        // the names are unique among the files of a ticket
+        let mut count = 2;
+        while record.attachments.iter().any(|(name_, _)| *name_ == name) {
+            name = format!("{}-{}", file_name(part.attachment_name(), index),
+                           count);
+            count += 1;
+        }
+        record.attachments.push((name, part.contents().to_vec()));
+    }
+    record
+}
+
+/// The records of the messages of a Maildir or an mbox file, each of
+/// which is named after its Message-ID, with the sender and the date
+/// as its metadata and the attachments as its files.
+pub fn records(path: &Path, category: &str) -> Result<Vec<Record>, String> {
+    let messages = if path.is_dir() {
+        read_maildir(path)
+    } else {
+        read_mbox(path)
+    }.map_err(|error| error.to_string())?;
+    let parser = MessageParser::default();
+    messages.iter()
+        .enumerate()
+        .map(|(index, contents)| {
+            parser.parse(contents)
+                .map(|message| record(&message, contents, category))
+                .ok_or_else(|| format!("The message {} is invalid.",
+                                       index + 1))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    const MBOX: &str = "From alice@example.com Mon Jan  7 09:00:00 2019
+From: Alice <alice@example.com>
+Subject: The build
+ is broken
+Date: Mon, 7 Jan 2019 09:00:00 +0000
+Message-ID: <1@example.com>
+MIME-Version: 1.0
+Content-Type: multipart/mixed; boundary=\"b\"
+
+--b
+Content-Type: text/plain
+
+It fails since yesterday.
+--b
+Content-Type: text/plain; name=\"build.log\"
+Content-Disposition: attachment; filename=\"build.log\"
+
+error: no space left
+--b--
+
+From bob@example.com Mon Jan  7 10:00:00 2019
+From: bob@example.com
+Message-ID: <2@example.com>
+
+>From now on, it works.
+";
+
+    #[test]
+    fn parse_mbox() {
+        let path = std::env::temp_dir().join("tickets-test-mail.mbox");
+        std::fs::write(&path, MBOX).unwrap();
+        let records = super::records(&path, "inbox").unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].category, "inbox");
+        assert_eq!(records[0].id, super::id_of("1@example.com"));
+        assert_eq!(records[0].title, "The build is broken");
+        assert_eq!(records[0].message.as_deref().unwrap().trim(),
+                   "It fails since yesterday.");
+        assert_eq!(records[0].meta.get("from").unwrap(),
+                   "Alice <alice@example.com>");
+        assert_eq!(records[0].meta.get("message_id").unwrap(),
+                   "1@example.com");
+        assert_eq!(records[0].meta.get("date").unwrap(),
+                   "2019-01-07T09:00:00Z");
+        assert_eq!(records[0].attachments.len(), 1);
+        assert_eq!(records[0].attachments[0].0, "build.log");
+        assert_eq!(records[1].title, "(no subject)");
+        assert_eq!(records[1].meta.get("from").unwrap(), "bob@example.com");
+        assert_eq!(records[1].message.as_deref().unwrap().trim(),
+                   "From now on, it works.");
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn file_names() {
+        assert_eq!(super::file_name(Some("../.secret"), 0), "secret");
+        assert_eq!(super::file_name(Some("C:\\a\\b.txt"), 0), "b.txt");
+        assert_eq!(super::file_name(None, 1), "attachment-2");
+    }
+}
@@ -0,0 +1,9 @@
+pub mod csv;
+pub mod github;
+pub mod html;
+pub mod ics;
+pub mod import;
+pub mod mail;
+pub mod outline;
+pub mod taskwarrior;
+pub mod todotxt;
@@ -0,0 +1,209 @@
+use std::collections::BTreeMap;
+use std::io;
+
+use chrono::{DateTime, NaiveDate};
+
+use super::super::ticket::Ticket;
+
+/// A ticket with its metadata, to be written as an entry.
+struct Entry {
+    ticket: Ticket,
+    meta: BTreeMap<String, String>,
+}
+
+impl Entry {
+    fn tags(&self) -> Vec<&str> {
+        self.meta
+            .get("tags")
+            .map(|tags| tags.split_whitespace().collect())
+            .unwrap_or_default()
+    }
+
+    /// Whether the ticket is completed, as todo.txt or Taskwarrior tells.
+    fn is_done(&self) -> bool {
+        self.meta.contains_key("completed")
+            || self.meta.get("status").is_some_and(|status| {
+                status == "completed" || status == "done"
+            })
+    }
+
+    fn id(&self) -> String {
+        format!("{}/{}",
+                self.ticket.category,
+                self.ticket.id.clone().unwrap_or_default())
+    }
+
+    fn title(&self) -> &str {
+        self.ticket.title.as_ref().map(|title| &title[..]).unwrap_or("")
+    }
+}
+
+/// The categories in the order of `show`, with their tickets having
+/// the tag, if given.
+fn collect(categories: &[Ticket], tag: Option<&str>)
+           -> io::Result<Vec<(String, Vec<Entry>)>> {
+    let mut sections = Vec::new();
+    for category in categories {
+        let mut entries = Vec::new();
+        for ticket in category.collect()? {
+            let meta = ticket.meta()?;
+            let entry = Entry { ticket, meta };
+            if tag.is_none_or(|tag_| entry.tags().contains(&tag_)) {
+                entries.push(entry);
+            }
+        }
+        // a report of a tag has no room for the categories without it
+        if tag.is_none() || !entries.is_empty() {
+            sections.push((category.category.clone(), entries));
+        }
+    }
+    Ok(sections)
+}
+
+fn indent(text: &str, prefix: &str) -> String {
+    text.lines()
+        .map(|line| if line.is_empty() {
+            String::new()
+        } else {
+            format!("{}{}", prefix, line)
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+fn deadline(due: &str) -> Option<String> {
+    let date = match DateTime::parse_from_rfc3339(due) {
+        Ok(date_) => date_.date_naive(),
+        Err(_) => NaiveDate::parse_from_str(due, "%Y-%m-%d").ok()?,
+    };
+    Some(date.format("<%Y-%m-%d %a>").to_string())
+}
+
+/// A document of Org-mode, with a heading per category and
+/// a TODO or DONE heading per ticket.
+pub fn org(categories: &[Ticket], tag: Option<&str>) -> io::Result<String> {
+    let mut lines = Vec::new();
+    for (category, entries) in collect(categories, tag)? {
+        lines.push(format!("* {}/", category));
+        for entry in entries {
+            let keyword = if entry.is_done() { "DONE" } else { "TODO" };
+            let tags = entry.tags();
+            let mut heading = format!("** {} {}", keyword, entry.title());
+            if !tags.is_empty() {
+                heading.push_str(&format!(" :{}:", tags.join(":")));
+            }
+            lines.push(heading);
+            if let Some(deadline_) = entry.meta.get("due")
+                .and_then(|due| deadline(due))
+            {
+                lines.push(format!("   DEADLINE: {}", deadline_));
+            }
+            lines.push(String::from("   :PROPERTIES:"));
+            lines.push(format!("   :ID: {}", entry.id()));
+            for (key, value) in &entry.meta {
+                if key != "tags" && key != "due" {
+                    lines.push(format!("   :{}: {}", key, value));
+                }
+            }
+            lines.push(String::from("   :END:"));
+            if let Some(message) = &entry.ticket.message {
+                // indented, not to be read as headings
+                lines.push(indent(message, "   "));
+            }
+        }
+    }
+    Ok(lines.join("\n"))
+}
+
+/// A document of Markdown, with a heading per category and
+/// a checklist item per ticket.
+pub fn markdown(categories: &[Ticket], tag: Option<&str>)
+                -> io::Result<String> {
+    let mut sections = Vec::new();
+    for (category, entries) in collect(categories, tag)? {
+        let mut lines = vec![format!("# {}/", category), String::new()];
+        for entry in entries {
+            let check = if entry.is_done() { "x" } else { " " };
+            lines.push(format!("- [{}] **{}** `{}`",
+                               check,
+                               entry.title(),
+                               entry.id()));
+            if let Some(message) = &entry.ticket.message {
+                lines.push(String::new());
+                lines.push(indent(message, "  "));
+            }
+            if !entry.meta.is_empty() {
+                lines.push(String::new());
+                for (key, value) in &entry.meta {
+                    lines.push(format!("  - {}: {}", key, value));
+                }
+                lines.push(String::new());
+            }
+        }
+        sections.push(lines.join("\n").trim_end().to_string());
+    }
+    Ok(sections.join("\n\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    fn prepare() {
+        super::super::super::purge().unwrap_or(());
+        super::super::super::initialize_root().unwrap_or(());
+        let path_root = super::super::super::get_path_root();
+        std::fs::create_dir(path_root.join("open")).unwrap();
+        std::fs::create_dir(path_root.join("done")).unwrap();
+        std::fs::write(path_root.join("open/ID-1"), "Fix it\n\n* Quickly")
+            .unwrap();
+        std::fs::write(path_root.join("done/ID-2"), "Ship it").unwrap();
+        let ticket = super::Ticket::from(String::from("open/ID-1"), None)
+            .unwrap();
+        ticket.set_meta("tags", Some("bug urgent")).unwrap();
+        ticket.set_meta("due", Some("2019-01-10")).unwrap();
+        let ticket = super::Ticket::from(String::from("done/ID-2"), None)
+            .unwrap();
+        ticket.set_meta("status", Some("completed")).unwrap();
+    }
+
+    fn categories() -> Vec<super::Ticket> {
+        ["done/", "open/"]
+            .iter()
+            .map(|id| super::Ticket::from(id.to_string(), None).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn write_org() {
+        prepare();
+        assert_eq!(super::org(&categories(), Some("bug")).unwrap(),
+                   "* open/
+** TODO Fix it :bug:urgent:
+   DEADLINE: <2019-01-10 Thu>
+   :PROPERTIES:
+   :ID: open/ID-1
+   :END:
+   * Quickly");
+        assert!(super::org(&categories(), None).unwrap()
+                .starts_with("* done/\n** DONE Ship it\n"));
+    }
+
+    #[test]
+    fn write_markdown() {
+        prepare();
+        assert_eq!(super::markdown(&categories(), None).unwrap(),
+                   "# done/
+
+- [x] **Ship it** `done/ID-2`
+
+  - status: completed
+
+# open/
+
+- [ ] **Fix it** `open/ID-1`
+
+  * Quickly
+
+  - due: 2019-01-10
+  - tags: bug urgent");
+    }
+}
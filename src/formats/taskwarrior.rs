@@ -0,0 +1,297 @@
+use std::io;
+
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, TimeZone, Timelike, Utc};
+use serde_json::{json, Map, Value};
+
+use super::super::comment::{self, Comment};
+use super::super::ticket::Ticket;
+use super::import::Record;
+
+/// The format of the dates of Taskwarrior, always in UTC.
+const DATE_FORMAT: &str = "%Y%m%dT%H%M%SZ";
+
+/// The start of the annotation of the message, which is read back
+/// as the message rather than as a comment.
+const MESSAGE_MARK: &str = "[message] ";
+
+/// The metadata of the dates, as `2019-01-02` at midnight
+/// or in RFC 3339 otherwise.
+const META_DATES: [(&str, &str); 3] = [("entry", "created"),
+                                       ("end", "completed"),
+                                       ("due", "due")];
+
+fn parse_date(text: &str) -> Option<DateTime<Local>> {
+    if let Ok(date) = NaiveDateTime::parse_from_str(text, DATE_FORMAT) {
+        return Some(Utc.from_utc_datetime(&date).with_timezone(&Local));
+    }
+    if let Ok(date) = DateTime::parse_from_rfc3339(text) {
+        return Some(date.with_timezone(&Local));
+    }
+    let date = NaiveDate::parse_from_str(text, "%Y-%m-%d").ok()?;
+    Local.from_local_datetime(&date.and_hms_opt(0, 0, 0)?).earliest()
+}
+
+fn to_meta(date: DateTime<Local>) -> String {
+    if date.num_seconds_from_midnight() == 0 {
+        date.format("%Y-%m-%d").to_string()
+    }
+    else {
+        date.to_rfc3339()
+    }
+}
+
+fn to_taskwarrior(date: DateTime<Local>) -> String {
+    date.with_timezone(&Utc).format(DATE_FORMAT).to_string()
+}
+
+/// The tasks of `task export`, given as an array
+/// or as one object per line by the older versions.
+fn tasks(text: &str) -> Result<Vec<Value>, String> {
+    match serde_json::from_str::<Value>(text) {
+        Ok(Value::Array(tasks_)) => Ok(tasks_),
+        Ok(Value::Object(task)) => Ok(vec![Value::Object(task)]),
+        Ok(_) => Err(String::from("Expected an array of tasks.")),
+        Err(_) => text
+            .lines()
+            .map(|line| line.trim().trim_end_matches(','))
+            .filter(|line| !line.is_empty() && *line != "[" && *line != "]")
+            .map(|line| serde_json::from_str(line)
+                 .map_err(|error| format!("Invalid JSON: {}", error)))
+            .collect(),
+    }
+}
+
+/// The records of the tasks, each of which goes into the category
+/// of its project, or the given one without, named by the first
+/// eight characters of its UUID as Taskwarrior shows.
+pub fn records(text: &str, category: &str) -> Result<Vec<Record>, String> {
+    let mut records = Vec::new();
+    for task in tasks(text)? {
+        let string = |key: &str| task[key].as_str().map(|value| value.to_string());
+        let uuid = string("uuid").unwrap_or_default();
+        let mut record = Record {
+            category: string("project").unwrap_or_else(|| category.to_string()),
+            id: uuid.chars().take(8).collect(),
+            title: string("description").unwrap_or_default(),
+            ..Default::default()
+        };
+        for key in &["uuid", "status", "priority"] {
+            if let Some(value) = string(key) {
+                record.meta.insert(key.to_string(), value);
+            }
+        }
+        for (key, meta) in META_DATES.iter() {
+            let date = string(key).and_then(|text_| parse_date(&text_));
+            if let Some(date) = date {
+                record.meta.insert(meta.to_string(), to_meta(date));
+            }
+        }
+        if let Some(tags) = task["tags"].as_array() {
+            let tags: Vec<&str> = tags.iter().filter_map(|tag| tag.as_str())
+                .collect();
+            if !tags.is_empty() {
+                record.meta.insert(String::from("tags"), tags.join(" "));
+            }
+        }
+        if let Some(annotations) = task["annotations"].as_array() {
+            for annotation in annotations {
+                let created_at = annotation["entry"]
+                    .as_str()
+                    .and_then(parse_date)
+                    .unwrap_or_else(Local::now);
+                let body = match annotation["description"].as_str() {
+                    Some(body) => body,
+                    None => continue,
+                };
+                match body.strip_prefix(MESSAGE_MARK) {
+                    Some(message) if record.message.is_none() => {
+                        record.message = Some(message.trim().to_string());
+                    },
+                    _ => {
+                        record.comments.push(Comment {
+                            author: comment::current_author(),
+                            created_at,
+                            body: body.trim().to_string(),
+                        });
+                    },
+                }
+            }
+        }
+        records.push(record);
+    }
+    Ok(records)
+}
+
+/// A UUID of the identifier, the same in every export so that
+/// Taskwarrior updates the task rather than adding another.
+pub fn uuid_of(id: &str) -> String {
+    let hash = |seed: u64| {
+        id.bytes().fold(seed, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+        })
+    };
+    let high = hash(0xcbf2_9ce4_8422_2325);
+    let low = hash(0x8422_2325_cbf2_9ce4);
+    // the version 4 and the variant of RFC 4122
+    let high = (high & !0xf000) | 0x4000;
+    let low = (low & !(0xc << 60)) | (0x8 << 60);
+    format!("{:08x}-{:04x}-{:04x}-{:04x}-{:012x}",
+            high >> 32,
+            (high >> 16) & 0xffff,
+            high & 0xffff,
+            low >> 48,
+            low & 0xffff_ffff_ffff)
+}
+
+fn task(ticket: &Ticket) -> io::Result<Value> {
+    let mut meta = ticket.meta()?;
+    let id = format!("{}/{}",
+                     ticket.category,
+                     ticket.id.clone().unwrap_or_default());
+    let mut task = Map::new();
+    task.insert(String::from("uuid"),
+                json!(meta.remove("uuid").unwrap_or_else(|| uuid_of(&id))));
+    task.insert(String::from("description"),
+                json!(ticket.title.clone().unwrap_or_default()));
+    task.insert(String::from("project"), json!(ticket.category));
+    let status = meta.remove("status").unwrap_or_else(|| {
+        if meta.contains_key("completed") {
+            String::from("completed")
+        }
+        else {
+            String::from("pending")
+        }
+    });
+    task.insert(String::from("status"), json!(status));
+    if let Some(priority) = meta.remove("priority") {
+        task.insert(String::from("priority"), json!(priority));
+    }
+
+    let modified_at = ticket.modified_at.map(DateTime::<Local>::from);
+    let entry = meta.get("created")
+        .and_then(|created| parse_date(created))
+        .or(modified_at);
+    for (key, meta_) in META_DATES.iter() {
+        let date = match *key {
+            "entry" => entry,
+            _ => meta.get(*meta_).and_then(|date_| parse_date(date_)),
+        };
+        if let Some(date_) = date {
+            task.insert(key.to_string(), json!(to_taskwarrior(date_)));
+        }
+    }
+    if let Some(modified_at) = modified_at {
+        task.insert(String::from("modified"), json!(to_taskwarrior(modified_at)));
+    }
+    if let Some(tags) = meta.get("tags") {
+        task.insert(String::from("tags"),
+                    json!(tags.split_whitespace().collect::<Vec<&str>>()));
+    }
+
+    // the message has no place but an annotation, marked before the comments
+    let mut annotations = Vec::new();
+    if let (Some(message), Some(entry_)) = (&ticket.message, entry) {
+        annotations.push(json!({
+            "entry": to_taskwarrior(entry_),
+            "description": format!("{}{}", MESSAGE_MARK, message),
+        }));
+    }
+    for comment in ticket.read_comments()? {
+        annotations.push(json!({
+            "entry": to_taskwarrior(comment.created_at),
+            "description": comment.body,
+        }));
+    }
+    if !annotations.is_empty() {
+        task.insert(String::from("annotations"), json!(annotations));
+    }
+    Ok(Value::Object(task))
+}
+
+/// The tasks of the tickets of the categories, for `task import`.
+pub fn export(categories: &[Ticket]) -> io::Result<String> {
+    let mut tasks = Vec::new();
+    for category in categories {
+        for ticket in category.collect()? {
+            tasks.push(task(&ticket)?);
+        }
+    }
+    serde_json::to_string_pretty(&tasks)
+        .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn parse_tasks() {
+        let text = r#"[
+{"id":1,"description":"Buy milk","entry":"20190101T000000Z","project":"home",
+ "status":"pending","uuid":"0a1b2c3d-0000-4000-8000-000000000000",
+ "priority":"H","tags":["shop","errand"],
+ "annotations":[{"entry":"20190102T030405Z","description":"Whole milk"}]},
+{"id":0,"description":"Call","status":"completed","end":"20190103T120000Z",
+ "uuid":"ffffffff-0000-4000-8000-000000000000"}
+]"#;
+        let records = super::records(text, "inbox").unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].category, "home");
+        assert_eq!(records[0].id, "0a1b2c3d");
+        assert_eq!(records[0].title, "Buy milk");
+        assert_eq!(records[0].meta.get("priority").unwrap(), "H");
+        assert_eq!(records[0].meta.get("tags").unwrap(), "shop errand");
+        assert_eq!(records[0].comments[0].body, "Whole milk");
+        assert_eq!(records[1].category, "inbox");
+        assert_eq!(records[1].meta.get("status").unwrap(), "completed");
+        assert!(records[1].meta.contains_key("completed"));
+
+        // one object per line
+        let records = super::records(
+            "{\"description\":\"A\",\"uuid\":\"12345678-x\"}\n\
+             {\"description\":\"B\",\"uuid\":\"87654321-x\"}\n",
+            "inbox").unwrap();
+        assert_eq!(records[1].id, "87654321");
+        assert!(super::records("nonsense", "inbox").is_err());
+    }
+
+    #[test]
+    fn dates_round_trip() {
+        let date = super::parse_date("20190102T030405Z").unwrap();
+        assert_eq!(super::to_taskwarrior(date), "20190102T030405Z");
+        let midnight = super::parse_date("2019-01-02").unwrap();
+        assert_eq!(super::to_meta(midnight), "2019-01-02");
+        assert_eq!(super::parse_date(&super::to_meta(date)), Some(date));
+    }
+
+    #[test]
+    fn message_round_trip() {
+        super::super::super::purge().unwrap_or(());
+        super::super::super::initialize_root().unwrap_or(());
+        let path_root = super::super::super::get_path_root();
+        std::fs::create_dir(path_root.join("home/")).unwrap();
+        std::fs::write(path_root.join("home/milk"),
+                       "Buy milk\n\nWhole milk\nfrom the farm").unwrap();
+        let ticket = super::Ticket::from(String::from("home/milk"), None)
+            .unwrap();
+        ticket.comment(Some(String::from("Done yet?"))).unwrap();
+
+        let category = super::Ticket::from(String::from("home/"), None)
+            .unwrap();
+        let text = super::export(&[category]).unwrap();
+        assert!(text.contains("[message] Whole milk"));
+        let records = super::records(&text, "inbox").unwrap();
+        assert_eq!(records[0].title, "Buy milk");
+        assert_eq!(records[0].message,
+                   Some(String::from("Whole milk\nfrom the farm")));
+        assert_eq!(records[0].comments.len(), 1);
+        assert_eq!(records[0].comments[0].body, "Done yet?");
+    }
+
+    #[test]
+    fn stable_uuids() {
+        let uuid = super::uuid_of("home/milk");
+        assert_eq!(uuid, super::uuid_of("home/milk"));
+        assert_ne!(uuid, super::uuid_of("home/bread"));
+        assert_eq!(uuid.len(), 36);
+        assert_eq!(&uuid[14..15], "4");
+    }
+}
@@ -0,0 +1,228 @@
+use std::io;
+
+use chrono::NaiveDate;
+
+use super::super::ticket::Ticket;
+use super::import::Record;
+
+/// The metadata written in the syntax of todo.txt itself,
+/// rather than as `key:value`.
+const META_PRIORITY: &str = "priority";
+const META_CREATED: &str = "created";
+const META_COMPLETED: &str = "completed";
+/// The contexts `@context`, separated by spaces.
+const META_TAGS: &str = "tags";
+/// The projects `+project` other than the category.
+const META_PROJECTS: &str = "projects";
+
+fn is_date(word: &str) -> bool {
+    NaiveDate::parse_from_str(word, "%Y-%m-%d").is_ok()
+}
+
+fn is_priority(word: &str) -> bool {
+    let bytes = word.as_bytes();
+    bytes.len() == 3 && bytes[0] == b'(' && bytes[2] == b')'
+        && bytes[1].is_ascii_uppercase()
+}
+
+/// The pair of a `key:value`, which is neither a URL nor a time.
+fn key_value(word: &str) -> Option<(&str, &str)> {
+    let index = word.find(':')?;
+    let (key, value) = (&word[..index], &word[index + 1..]);
+    if key.is_empty() || value.is_empty() || value.starts_with("//")
+        || !key.chars().all(|ch| ch.is_alphanumeric() || ch == '_' || ch == '-')
+    {
+        return None;
+    }
+    Some((key, value))
+}
+
+/// A line of todo.txt for the ticket, with its identifier as `id:`
+/// to be imported again as the same ticket.
+fn line(ticket: &Ticket) -> io::Result<String> {
+    let mut meta = ticket.meta()?;
+    let mut words: Vec<String> = Vec::new();
+    let priority = meta.remove(META_PRIORITY);
+    if let Some(completed) = meta.remove(META_COMPLETED) {
+        words.push(String::from("x"));
+        if is_date(&completed) {
+            words.push(completed);
+        }
+    }
+    let completed = !words.is_empty();
+    match priority {
+        Some(priority_) if !completed => words.push(format!("({})", priority_)),
+        // a completed task keeps its priority as a key
+        Some(priority_) => {
+            meta.insert(String::from("pri"), priority_);
+        },
+        None => (),
+    }
+    if let Some(created) = meta.remove(META_CREATED) {
+        words.push(created);
+    }
+    words.push(ticket.title.clone().unwrap_or_default());
+    words.push(format!("+{}", ticket.category));
+    if let Some(projects) = meta.remove(META_PROJECTS) {
+        words.extend(projects.split_whitespace().map(|project| {
+            format!("+{}", project)
+        }));
+    }
+    if let Some(tags) = meta.remove(META_TAGS) {
+        words.extend(tags.split_whitespace().map(|tag| format!("@{}", tag)));
+    }
+    for (key, value) in &meta {
+        // todo.txt has no room for the spaces
+        if key != "id" && !value.is_empty()
+            && !value.contains(char::is_whitespace)
+        {
+            words.push(format!("{}:{}", key, value));
+        }
+    }
+    words.push(format!("id:{}", ticket.id.clone().unwrap_or_default()));
+    Ok(words.join(" "))
+}
+
+/// One line per ticket of the categories.
+pub fn export(categories: &[Ticket]) -> io::Result<String> {
+    let mut lines = Vec::new();
+    for category in categories {
+        for ticket in category.collect()? {
+            lines.push(line(&ticket)?);
+        }
+    }
+    Ok(lines.join("\n"))
+}
+
+/// The record of a line, or None for a blank one. A line without
+/// `id:` is named after its number, like `TODO-3`.
+fn record(text: &str, number: usize, category: &str) -> Option<Record> {
+    let mut words = text.split_whitespace().peekable();
+    words.peek()?;
+    let mut record = Record {
+        category: category.to_string(),
+        id: format!("TODO-{}", number),
+        ..Default::default()
+    };
+
+    if words.peek() == Some(&"x") {
+        words.next();
+        let completed = match words.peek() {
+            Some(date) if is_date(date) => words.next().unwrap().to_string(),
+            _ => String::from("yes"),
+        };
+        record.meta.insert(META_COMPLETED.to_string(), completed);
+    }
+    if let Some(word) = words.peek() {
+        if is_priority(word) {
+            record.meta.insert(META_PRIORITY.to_string(), word[1..2].to_string());
+            words.next();
+        }
+    }
+    if let Some(word) = words.peek() {
+        if is_date(word) {
+            record.meta.insert(META_CREATED.to_string(), word.to_string());
+            words.next();
+        }
+    }
+
+    let mut title: Vec<&str> = Vec::new();
+    let mut projects: Vec<&str> = Vec::new();
+    let mut tags: Vec<&str> = Vec::new();
+    for word in words {
+        if let Some(project) = word.strip_prefix('+').filter(|p| !p.is_empty()) {
+            projects.push(project);
+        }
+        else if let Some(tag) = word.strip_prefix('@').filter(|t| !t.is_empty()) {
+            tags.push(tag);
+        }
+        else if let Some((key, value)) = key_value(word) {
+            match key {
+                "id" => record.id = value.to_string(),
+                "pri" => {
+                    record.meta.insert(META_PRIORITY.to_string(),
+                                       value.to_string());
+                },
+                _ => {
+                    record.meta.insert(key.to_string(), value.to_string());
+                },
+            }
+        }
+        else {
+            title.push(word);
+        }
+    }
+    record.title = title.join(" ");
+    if !projects.is_empty() {
+        record.category = projects.remove(0).to_string();
+    }
+    if !projects.is_empty() {
+        record.meta.insert(META_PROJECTS.to_string(), projects.join(" "));
+    }
+    if !tags.is_empty() {
+        record.meta.insert(META_TAGS.to_string(), tags.join(" "));
+    }
+    Some(record)
+}
+
+/// The records of the lines, each of which goes into the category
+/// of its first `+project`, or the given one without.
+pub fn records(text: &str, category: &str) -> Vec<Record> {
+    text.lines()
+        .enumerate()
+        .filter_map(|(index, text_)| record(text_, index + 1, category))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn parse_lines() {
+        let records = super::records(
+            "(A) 2019-01-01 Call mom +family +phone @home due:2019-01-10\n\
+             \n\
+             x 2019-01-03 2019-01-02 Pay rent id:RENT-1 pri:B \
+             http://example.com\n",
+            "inbox");
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].category, "family");
+        assert_eq!(records[0].id, "TODO-1");
+        assert_eq!(records[0].title, "Call mom");
+        assert_eq!(records[0].meta.get("priority").unwrap(), "A");
+        assert_eq!(records[0].meta.get("created").unwrap(), "2019-01-01");
+        assert_eq!(records[0].meta.get("projects").unwrap(), "phone");
+        assert_eq!(records[0].meta.get("tags").unwrap(), "home");
+        assert_eq!(records[0].meta.get("due").unwrap(), "2019-01-10");
+        assert_eq!(records[1].category, "inbox");
+        assert_eq!(records[1].id, "RENT-1");
+        assert_eq!(records[1].title, "Pay rent http://example.com");
+        assert_eq!(records[1].meta.get("completed").unwrap(), "2019-01-03");
+        assert_eq!(records[1].meta.get("created").unwrap(), "2019-01-02");
+        assert_eq!(records[1].meta.get("priority").unwrap(), "B");
+    }
+
+    #[test]
+    fn write_lines() {
+        super::super::super::purge().unwrap_or(());
+        super::super::super::initialize_root().unwrap_or(());
+        let path_root = super::super::super::get_path_root();
+        std::fs::create_dir(path_root.join("family")).unwrap();
+        std::fs::write(path_root.join("family/CALL"), "Call mom").unwrap();
+        let ticket = super::Ticket::from(String::from("family/CALL"), None)
+            .unwrap();
+        ticket.set_meta("priority", Some("A")).unwrap();
+        ticket.set_meta("tags", Some("home phone")).unwrap();
+        ticket.set_meta("due", Some("2019-01-10")).unwrap();
+        ticket.set_meta("note", Some("two words")).unwrap();
+        let mut ticket = super::Ticket::from(String::from("family/CALL"), None)
+            .unwrap();
+        ticket.read().unwrap();
+        assert_eq!(super::line(&ticket).unwrap(),
+                   "(A) Call mom +family @home @phone due:2019-01-10 id:CALL");
+
+        ticket.set_meta("completed", Some("2019-01-03")).unwrap();
+        assert_eq!(super::line(&ticket).unwrap(),
+                   "x 2019-01-03 Call mom +family @home @phone \
+                    due:2019-01-10 pri:A id:CALL");
+    }
+}
@@ -0,0 +1,259 @@
+use std::ffi::OsStr;
+use std::fs;
+use std::io::{self, Write};
+use std::path;
+use std::process::{Command, Stdio};
+use std::sync::Once;
+
+use serde_json::{json, Value};
+
+use super::opt::Action;
+use super::resolve;
+use super::ticket::Ticket;
+use super::timelog::Timer;
+
+/// The directory of the hooks under the root, which is not a category.
+pub const DIR_HOOKS: &str = "hooks";
+
+/// Whether the entry of the root is the directory of the hooks.
+pub fn is_hooks(name: &OsStr) -> bool {
+    name == DIR_HOOKS
+}
+
+/// Fail if the directory of the hooks looks like a category made before
+/// it was reserved, with files which are neither hooks, even disabled ones,
+/// nor hidden, telling how to move its tickets by hand.
+pub fn check(path_hooks: &path::Path) -> io::Result<()> {
+    let mut names: Vec<String> = Vec::new();
+    for entry in fs::read_dir(path_hooks)? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if entry.path().is_file() && !is_executable(&entry.path())
+            && !name.starts_with('.') && !name.starts_with("pre-")
+            && !name.starts_with("post-") {
+            names.push(name);
+        }
+    }
+    if names.is_empty() {
+        return Ok(());
+    }
+    names.sort();
+    Err(io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("The directory {dir}/ is reserved for the hooks, \
+                but has the tickets {names}, which are not listed. \
+                Move each of them with its sidecar into a category, \
+                like `mv {path}/ID {path}/.ID {root}/<category>/`.",
+                dir = DIR_HOOKS,
+                names = names.join(", "),
+                path = path_hooks.display(),
+                root = path_hooks.parent().unwrap_or(path_hooks).display())))
+}
+
+/// Warn once of the tickets in the directory of the hooks, which are
+/// left out of the listings rather than failing all of them.
+pub fn warn_tickets(path_hooks: &path::Path) {
+    static WARNED: Once = Once::new();
+    if let Err(error) = check(path_hooks) {
+        WARNED.call_once(|| eprintln!("WARNING: {}", error));
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Stage {
+    Pre,
+    Post,
+}
+
+/// An operation on a ticket or a category, run around by the hooks
+/// like `pre-new` and `post-new`.
+#[derive(Debug, PartialEq)]
+pub struct Hook {
+    pub operation: &'static str,
+    pub id: String,
+    pub dest_id: Option<String>,
+    /// The message given to the operation, if any.
+    pub message: Option<String>,
+}
+
+impl Hook {
+    /// The hook of the action which changes the store, with the identifiers
+    /// resolved as the action does. The bulk changes of `import`, `sync`,
+    /// `serve` and `tui` have none, for they are not of a single ticket.
+    pub fn of(action: &Action) -> Option<Hook> {
+        let resolved = |id: &String| resolve::resolve(id).ok();
+        let (operation, id, dest_id, message) = match action {
+            Action::New { id, message } => {
+                ("new", id.clone(), None, message.clone())
+            },
+            Action::Edit { id, message } => {
+                ("edit", resolved(id)?, None, message.clone())
+            },
+            Action::Comment { id, message } => {
+                ("comment", id.clone(), None, message.clone())
+            },
+            Action::Attach { id, .. } => ("attach", id.clone(), None, None),
+            Action::Detach { id, .. } => ("detach", id.clone(), None, None),
+            Action::Start { id } => ("start", id.clone(), None, None),
+            Action::Stop { .. } => {
+                ("stop", Timer::read().ok()??.id, None, None)
+            },
+            Action::LogTime { id, .. } => ("log-time", id.clone(), None, None),
+            Action::Estimate { id, value } => {
+                ("estimate", id.clone(), None, Some(value.clone()))
+            },
+            Action::Move { id, dest_id } => {
+                ("move",
+                 resolved(id)?,
+                 Some(resolve::resolve_destination(dest_id).ok()?),
+                 None)
+            },
+            Action::Remove { id } => ("remove", resolved(id)?, None, None),
+            _ => return None,
+        };
+        Some(Hook { operation, id, dest_id, message })
+    }
+
+    fn name(&self, stage: Stage) -> String {
+        match stage {
+            Stage::Pre => format!("pre-{}", self.operation),
+            Stage::Post => format!("post-{}", self.operation),
+        }
+    }
+
+    fn payload(&self, stage: Stage) -> Value {
+        let describe = |id: &str| match Ticket::from(id.to_string(), None) {
+            Ok(ticket) => json!({
+                "id": id,
+                "category": ticket.category,
+                "name": ticket.id,
+                "path": ticket.path,
+            }),
+            Err(_) => json!({ "id": id }),
+        };
+        json!({
+            "hook": self.name(stage),
+            "operation": self.operation,
+            "ticket": describe(&self.id),
+            "destination": self.dest_id.as_ref().map(|id| describe(id)),
+            "message": self.message,
+        })
+    }
+
+    /// Run the executable hook of the stage, if any. A failing `pre-*`
+    /// hook is an error, to abort the operation.
+    pub fn run(&self, stage: Stage) -> io::Result<()> {
+        let root = super::get_path_root();
+        let path_hook = root.join(DIR_HOOKS).join(self.name(stage));
+        if !is_executable(&path_hook) {
+            return Ok(());
+        }
+
+        let mut cmd = Command::new(&path_hook);
+        cmd
+            .current_dir(&root)
+            .stdin(Stdio::piped())
+            .env("TICKETS_ROOT", &root)
+            .env("TICKETS_HOOK", self.name(stage))
+            .env("TICKETS_OPERATION", self.operation)
+            .env("TICKETS_ID", &self.id);
+        if let Ok(ticket) = Ticket::from(self.id.clone(), None) {
+            cmd
+                .env("TICKETS_CATEGORY", &ticket.category)
+                .env("TICKETS_PATH", &ticket.path);
+        }
+        if let Some(dest_id) = &self.dest_id {
+            cmd.env("TICKETS_DEST_ID", dest_id);
+            if let Ok(dest_ticket) = Ticket::from(dest_id.clone(), None) {
+                cmd
+                    .env("TICKETS_DEST_CATEGORY", &dest_ticket.category)
+                    .env("TICKETS_DEST_PATH", &dest_ticket.path);
+            }
+        }
+
+        let mut child = cmd.spawn()?;
+        if let Some(mut stdin) = child.stdin.take() {
+            // the hook may not read it at all
+            stdin
+                .write_all(self.payload(stage).to_string().as_bytes())
+                .unwrap_or(());
+        }
+        let status = child.wait()?;
+        if stage == Stage::Pre && !status.success() {
+            return Err(io::Error::other(format!(
+                "The hook {} failed with {}.",
+                self.name(stage),
+                status)));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(unix)]
+pub fn is_executable(path: &path::Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    match path.metadata() {
+        Ok(metadata) => {
+            metadata.is_file() && metadata.permissions().mode() & 0o111 != 0
+        },
+        Err(_) => false,
+    }
+}
+
+#[cfg(not(unix))]
+pub fn is_executable(path: &path::Path) -> bool {
+    path.is_file()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::opt::Action;
+
+    #[test]
+    fn hooks_of_actions() {
+        let hook = super::Hook::of(&Action::New {
+            id: String::from("open/ID-1"),
+            message: Some(String::from("New")),
+        }).unwrap();
+        assert_eq!(hook.operation, "new");
+        assert_eq!(hook.id, "open/ID-1");
+        assert_eq!(hook.message, Some(String::from("New")));
+        assert_eq!(super::Hook::of(&Action::Tui), None);
+    }
+
+    #[test]
+    fn fail_on_tickets_among_hooks() {
+        super::super::purge().unwrap_or(());
+        super::super::initialize_root().unwrap_or(());
+        let path_hooks = super::super::get_path_root().join(super::DIR_HOOKS);
+        std::fs::create_dir(&path_hooks).unwrap();
+        std::fs::write(path_hooks.join("pre-new.disabled"), "#!/bin/sh")
+            .unwrap();
+        std::fs::write(path_hooks.join(".state"), "").unwrap();
+        assert!(super::check(&path_hooks).is_ok());
+        std::fs::write(path_hooks.join("ID-1"), "Hidden ticket").unwrap();
+        assert_eq!(super::check(&path_hooks).unwrap_err().to_string(),
+                   format!("The directory hooks/ is reserved for the hooks, \
+                           but has the tickets ID-1, which are not listed. \
+                           Move each of them with its sidecar into a category, \
+                           like `mv {path}/ID {path}/.ID {root}/<category>/`.",
+                           path = path_hooks.display(),
+                           root = super::super::get_path_root().display()));
+    }
+
+    #[test]
+    fn payload_of_a_move() {
+        let hook = super::Hook {
+            operation: "move",
+            id: String::from("open/ID-1"),
+            dest_id: Some(String::from("done/")),
+            message: None,
+        };
+        let payload = hook.payload(super::Stage::Pre);
+        assert_eq!(payload["hook"], "pre-move");
+        assert_eq!(payload["ticket"]["category"], "open");
+        assert_eq!(payload["ticket"]["name"], "ID-1");
+        assert_eq!(payload["destination"]["category"], "done");
+        assert_eq!(payload["destination"]["name"], serde_json::Value::Null);
+    }
+}
@@ -1,13 +1,40 @@
+extern crate chrono;
+extern crate crossterm;
 extern crate dirs;
 extern crate exitcode;
+extern crate mail_parser;
+extern crate notify;
+extern crate pulldown_cmark;
+extern crate serde_json;
+extern crate terminal_size;
+extern crate tiny_http;
+extern crate unicode_width;
+extern crate ureq;
 
 use std::fs;
-use std::io;
+use std::io::{self, IsTerminal};
 use std::path::PathBuf;
 use std::process::Command;
 
+pub mod alias;
+pub mod board;
+pub mod comment;
+pub mod completion;
+pub mod config;
+pub mod estimate;
+pub mod formats;
+pub mod hooks;
+pub mod markdown;
 pub mod opt;
+pub mod plugin;
+pub mod remote;
+pub mod resolve;
+pub mod server;
+pub mod style;
 pub mod ticket;
+pub mod timelog;
+pub mod tui;
+pub mod watch;
 
 fn format_header_init() -> String {
     String::from("tickets :: init")
@@ -43,6 +70,86 @@ fn format_header_edit(ticket: &ticket::Ticket) -> String {
             id)
 }
 
+fn format_header_comment(ticket: &ticket::Ticket) -> String {
+    let id = match &ticket.id {
+        Some(id_) => id_,
+        None => "",
+    };
+    format!("tickets :: comment :: {}/{}",
+            ticket.category,
+            id)
+}
+
+fn format_header_attach(ticket: &ticket::Ticket) -> String {
+    let id = match &ticket.id {
+        Some(id_) => id_,
+        None => "",
+    };
+    format!("tickets :: attach :: {}/{}",
+            ticket.category,
+            id)
+}
+
+fn format_header_attachments(ticket: &ticket::Ticket) -> String {
+    let id = match &ticket.id {
+        Some(id_) => id_,
+        None => "",
+    };
+    format!("tickets :: attachments :: {}/{}",
+            ticket.category,
+            id)
+}
+
+fn format_header_detach(ticket: &ticket::Ticket) -> String {
+    let id = match &ticket.id {
+        Some(id_) => id_,
+        None => "",
+    };
+    format!("tickets :: detach :: {}/{}",
+            ticket.category,
+            id)
+}
+
+fn format_header_start(ticket: &ticket::Ticket) -> String {
+    let id = match &ticket.id {
+        Some(id_) => id_,
+        None => "",
+    };
+    format!("tickets :: start :: {}/{}",
+            ticket.category,
+            id)
+}
+
+fn format_header_stop(ticket: &ticket::Ticket) -> String {
+    let id = match &ticket.id {
+        Some(id_) => id_,
+        None => "",
+    };
+    format!("tickets :: stop :: {}/{}",
+            ticket.category,
+            id)
+}
+
+fn format_header_log_time(ticket: &ticket::Ticket) -> String {
+    let id = match &ticket.id {
+        Some(id_) => id_,
+        None => "",
+    };
+    format!("tickets :: log-time :: {}/{}",
+            ticket.category,
+            id)
+}
+
+fn format_header_estimate(ticket: &ticket::Ticket) -> String {
+    let id = match &ticket.id {
+        Some(id_) => id_,
+        None => "",
+    };
+    format!("tickets :: estimate :: {}/{}",
+            ticket.category,
+            id)
+}
+
 fn format_header_move(start_ticket: &ticket::Ticket,
                       dest_ticket: &ticket::Ticket) -> String {
     let start_id = match &start_ticket.id {
@@ -68,23 +175,69 @@ fn format_header_remove(ticket: &ticket::Ticket) -> String {
             id)
 }
 
+/// The scope of the config given by the flags, if any.
+fn config_scope(scope: &opt::ConfigScope)
+    -> Result<Option<config::Scope>, String> {
+    let scopes: Vec<config::Scope> = vec![
+        (scope.system, config::Scope::System),
+        (scope.global, config::Scope::Global),
+        (scope.local, config::Scope::Local),
+    ]
+        .into_iter()
+        .filter(|(given, _)| *given)
+        .map(|(_, scope_)| scope_)
+        .collect();
+    match scopes.len() {
+        0 => Ok(None),
+        1 => Ok(Some(scopes[0])),
+        _ => Err(String::from("Only one of --system, --global and --local \
+                              can be given.")),
+    }
+}
+
 pub fn purge() -> io::Result<()> {
     let root = get_path_root();
     fs::remove_dir_all(root)?;
     Ok(())
 }
 
+/// The root of the store: the environment variable TICKETS_ROOT unless
+/// empty, or else the option `core.root`, or else `~/.tickets`.
+fn root_of(env_root: Option<std::ffi::OsString>,
+           config_root: impl FnOnce() -> Option<PathBuf>,
+           home: impl FnOnce() -> PathBuf) -> PathBuf {
+    if let Some(root) = env_root.filter(|root| !root.is_empty()) {
+        return PathBuf::from(root);
+    }
+    if let Some(root) = config_root() {
+        return root;
+    }
+    home().join(".tickets")
+}
+
+#[cfg(not(test))]
 fn get_path_root() -> PathBuf {
-    let home = dirs::home_dir().unwrap();
-    let root = home.join(".tickets");
-    root
+    root_of(std::env::var_os("TICKETS_ROOT"),
+            config::root,
+            || dirs::home_dir().unwrap())
+}
+
+/// A root of its own for each unit test, named after the thread
+/// running it, so that the tests never touch the store of the user
+/// and run in parallel.
+#[cfg(test)]
+fn get_path_root() -> PathBuf {
+    let thread = std::thread::current();
+    let name = thread.name().unwrap_or("main").replace("::", "-");
+    std::env::temp_dir().join(format!("tickets-test-{}", name))
 }
 
 fn initialize_root() -> io::Result<()> {
     let root = get_path_root();
     if root.exists() {
         return Err(io::Error::new(io::ErrorKind::AlreadyExists,
-                                  "The root ~/.tickets already exists."));
+                                  format!("The root {} already exists.",
+                                          root.display())));
     }
     fs::create_dir(root)?;
     Ok(())
@@ -92,7 +245,10 @@ fn initialize_root() -> io::Result<()> {
 
 // TODO: propagate smoothly the native env::Error
 fn open_editor(path: &PathBuf) -> io::Result<()> {
-    let result_env_editor = std::env::var("EDITOR");
+    let result_env_editor = match config::get("core.editor") {
+        Some(editor) => Ok(editor),
+        None => std::env::var("EDITOR"),
+    };
     let cmd_editor;
     match result_env_editor {
         Ok(env_value) => {
@@ -126,16 +282,20 @@ fn open_editor(path: &PathBuf) -> io::Result<()> {
     Ok(())
 }
 
-fn show_all() -> io::Result<String> {
+/// All categories under the root, in the order of their modification.
+fn collect_categories() -> io::Result<Vec<ticket::Ticket>> {
     let root = get_path_root();
-    let mut strings_display: Vec<String> = Vec::new();
     let mut categories: Vec<ticket::Ticket> = Vec::new();
 
     let iter_dir = fs::read_dir(&root)?;
     for entry in iter_dir {
         let entry = entry?;
         let path_entry = entry.path();
-        if path_entry.is_dir() {
+        let name = entry.file_name();
+        if path_entry.is_dir() && hooks::is_hooks(&name) {
+            hooks::warn_tickets(&path_entry);
+        }
+        else if path_entry.is_dir() && !ticket::is_sidecar(&name) {
             let id_ticket = path_entry.strip_prefix(&root).unwrap();
             let id_ticket = format!("{}/", id_ticket.to_string_lossy());
             let mut ticket = ticket::Ticket::from(id_ticket, None)
@@ -144,15 +304,86 @@ fn show_all() -> io::Result<String> {
             categories.push(ticket);
         }
     }
+    ticket::sort(&mut categories);
+    Ok(categories)
+}
+
+/// All tickets of all categories.
+fn collect_all() -> io::Result<Vec<ticket::Ticket>> {
+    let mut tickets: Vec<ticket::Ticket> = Vec::new();
+    for category in collect_categories()? {
+        tickets.append(&mut category.collect()?);
+    }
+    Ok(tickets)
+}
+
+/// The category of the identifier, or all categories without one.
+fn resolve_categories(id: Option<String>) -> io::Result<Vec<ticket::Ticket>> {
+    match id {
+        Some(id_) => {
+            let id_ = resolve::resolve(&id_)
+                .map_err(|error| io::Error::new(io::ErrorKind::NotFound,
+                                                error))?;
+            let category = ticket::Ticket::from(id_, None)
+                .map_err(|error| io::Error::new(io::ErrorKind::InvalidInput,
+                                                error))?;
+            if !category.is_dir {
+                return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                                          "Expected a category."));
+            }
+            if !category.path.exists() {
+                return Err(io::Error::new(io::ErrorKind::NotFound,
+                                          "The category does not exist."));
+            }
+            Ok(vec![category])
+        },
+        None => collect_categories(),
+    }
+}
+
+/// The tickets of a category, or of all categories, in the format,
+/// written into the file if given.
+fn export_text(id: Option<String>,
+               output: Option<PathBuf>,
+               format: impl Fn(&[ticket::Ticket]) -> io::Result<String>)
+               -> io::Result<Option<String>> {
+    let categories = resolve_categories(id)?;
+    let content = format(&categories)?;
+    match output {
+        Some(path) => {
+            if content.ends_with('\n') {
+                fs::write(path, content)?;
+            } else {
+                fs::write(path, format!("{}\n", content))?;
+            }
+            Ok(None)
+        },
+        // the last line break is printed by die
+        None => Ok(Some(content
+                        .strip_suffix('\n')
+                        .map(|content_| content_.to_string())
+                        .unwrap_or(content))),
+    }
+}
+
+fn show_all() -> io::Result<String> {
+    let mut strings_display: Vec<String> = Vec::new();
+    let categories = collect_categories()?;
     if categories.is_empty() {
         return Ok("NO TICKETS.".to_string());
     }
-    categories.sort_by_key(|tic| tic.modified_at);
 
     for ticket in &categories {
-        let str_category = format!("{}/\n{}",
-                                   ticket.category,
-                                   ticket);
+        let header = style::paint(style::Style::Category,
+                                  &format!("{}/", ticket.category));
+        // an unreadable category does not hide the others
+        let str_category = match ticket.collect() {
+            Ok(_) => format!("{}\n{}", header, ticket),
+            Err(error) => format!("{}\n{} {}",
+                                  header,
+                                  style::paint(style::Style::Error, "ERROR:"),
+                                  error),
+        };
         strings_display.push(str_category);
     }
     if strings_display.is_empty() {
@@ -163,16 +394,56 @@ fn show_all() -> io::Result<String> {
 }
 
 pub fn die(status: exitcode::ExitCode, message: String) {
+    if message.is_empty() {
+        std::process::exit(status);
+    }
     if exitcode::is_error(status) {
-        eprintln!("{}", message);
+        eprintln!("{}", style::paint_message(style::enabled_stderr(),
+                                             true,
+                                             &message));
     }
     else {
-        println!("{}", message);
+        println!("{}", style::paint_message(style::enabled(),
+                                            false,
+                                            &message));
     }
     std::process::exit(status);
 }
 
+/// The hidden helper `tickets __complete <partial>` for the completion
+/// scripts, printing the matching identifiers line by line.
+///
+/// It stays silent on any failure not to disturb the shell.
+pub fn complete(partial: Option<&str>) -> (exitcode::ExitCode, String) {
+    match completion::candidates(partial.unwrap_or("")) {
+        Ok(candidates) => (exitcode::OK, candidates.join("\n")),
+        Err(_) => (exitcode::OK, String::new()),
+    }
+}
+
+/// Dispatch the action between its `pre-*` and `post-*` hooks.
 pub fn match_action(opt: opt::Opt) -> (exitcode::ExitCode, String) {
+    let hook = opt.action.as_ref().and_then(hooks::Hook::of);
+    if let Some(hook) = &hook {
+        if let Err(error) = hook.run(hooks::Stage::Pre) {
+            return (exitcode::IOERR,
+                    format!("tickets :: {} :: {}\n\nERROR: {}",
+                            hook.operation,
+                            hook.id,
+                            error));
+        }
+    }
+    let (exit_code, sys_message) = dispatch_action(opt);
+    if let Some(hook) = &hook {
+        if exit_code == exitcode::OK {
+            // the operation is done whatever the hook results
+            hook.run(hooks::Stage::Post).unwrap_or(());
+        }
+    }
+    (exit_code, sys_message)
+}
+
+fn dispatch_action(opt: opt::Opt) -> (exitcode::ExitCode, String) {
     let exit_code: exitcode::ExitCode;
     let sys_message: String;
 
@@ -220,7 +491,501 @@ pub fn match_action(opt: opt::Opt) -> (exitcode::ExitCode, String) {
                     },
                 };
             },
+            opt::Action::Board { ids } => {
+                let format_header = String::from("tickets :: board");
+                let ids = if ids.is_empty() {
+                    board::default_columns().unwrap_or_default()
+                } else {
+                    ids
+                };
+                let result_categories = if ids.is_empty() {
+                    collect_categories().map(|mut categories| {
+                        categories.sort_by(|a, b| a.category.cmp(&b.category));
+                        categories
+                    })
+                } else {
+                    let mut categories: Vec<ticket::Ticket> = Vec::new();
+                    for id in ids {
+                        match ticket::Ticket::from(id.clone(), None) {
+                            Ok(ref category) if !category.is_dir
+                                || !category.path.exists() => {
+                                exit_code = exitcode::USAGE;
+                                sys_message = format!(
+                                    "{}\n\nERROR: {} is not an existing \
+                                    category.",
+                                    format_header,
+                                    id);
+                                return (exit_code, sys_message);
+                            },
+                            Ok(category) => {
+                                categories.push(category);
+                            },
+                            Err(error) => {
+                                exit_code = exitcode::USAGE;
+                                sys_message = format!("{}\n\nERROR: {}",
+                                                      format_header,
+                                                      error);
+                                return (exit_code, sys_message);
+                            },
+                        };
+                    }
+                    Ok(categories)
+                };
+                let result_board = result_categories.and_then(|categories| {
+                    board::render(&categories, board::terminal_width())
+                });
+                match result_board {
+                    Ok(str_board) => {
+                        exit_code = exitcode::OK;
+                        sys_message = format!("{}\n\n{}",
+                                              format_header,
+                                              str_board);
+                    },
+                    Err(error) => {
+                        exit_code = exitcode::IOERR;
+                        sys_message = format!("{}\n\nERROR: {}",
+                                              format_header,
+                                              error);
+                    },
+                };
+            },
+            opt::Action::Tui => {
+                if !get_path_root().exists() {
+                    exit_code = exitcode::IOERR;
+                    sys_message = String::from(
+                        "ERROR: NOT INITIALIZED, PLEASE init.");
+                    return (exit_code, sys_message);
+                }
+                match tui::run() {
+                    Ok(()) => {
+                        exit_code = exitcode::OK;
+                        sys_message = String::new();
+                    },
+                    Err(error) => {
+                        exit_code = exitcode::IOERR;
+                        sys_message = format!("tickets :: tui\n\nERROR: {}",
+                                              error);
+                    },
+                };
+            },
+            opt::Action::Serve { bind } => {
+                let format_header = format!("tickets :: serve :: {}", bind);
+                if !get_path_root().exists() {
+                    exit_code = exitcode::IOERR;
+                    sys_message = String::from(
+                        "ERROR: NOT INITIALIZED, PLEASE init.");
+                    return (exit_code, sys_message);
+                }
+                let result_serve = server::bind(&bind).and_then(|server_| {
+                    println!("{}\n\nLISTENING.", format_header);
+                    server::serve(server_)
+                });
+                match result_serve {
+                    Ok(()) => {
+                        exit_code = exitcode::OK;
+                        sys_message = String::new();
+                    },
+                    Err(error) => {
+                        exit_code = exitcode::UNAVAILABLE;
+                        sys_message = format!("{}\n\nERROR: {}",
+                                              format_header,
+                                              error);
+                    },
+                };
+            },
+            opt::Action::Watch { id, json } => {
+                let format_header = match &id {
+                    Some(id_) => format!("tickets :: watch :: {}", id_),
+                    None => String::from("tickets :: watch"),
+                };
+                if !get_path_root().exists() {
+                    exit_code = exitcode::IOERR;
+                    sys_message = String::from(
+                        "ERROR: NOT INITIALIZED, PLEASE init.");
+                    return (exit_code, sys_message);
+                }
+                let result_watch = watch::Watch::new(id).map(|watch_| {
+                    // NDJSON has no room for the header
+                    if !json {
+                        println!("{}\n\nWATCHING.\n", format_header);
+                    }
+                    // the errors go along the changes, not to end the watch
+                    watch_.run(|event| if json {
+                        println!("{}", event.to_json());
+                    } else {
+                        println!("{}", event);
+                    }, |error| if json {
+                        println!("{}", serde_json::json!({
+                            "error": error.to_string(),
+                        }));
+                    } else {
+                        println!("ERROR: {}", error);
+                    })
+                });
+                match result_watch {
+                    Ok(()) => {
+                        exit_code = exitcode::OK;
+                        sys_message = String::new();
+                    },
+                    Err(error) => {
+                        exit_code = exitcode::IOERR;
+                        sys_message = format!("{}\n\nERROR: {}",
+                                              format_header,
+                                              error);
+                    },
+                };
+            },
+            opt::Action::Export { format } => {
+                let (format_header, result_export) = match format {
+                    opt::Export::Html { out_dir } => (
+                        String::from("tickets :: export :: html"),
+                        collect_categories().and_then(|categories| {
+                            formats::html::export(&categories, &out_dir)
+                        }).map(|()| None),
+                    ),
+                    opt::Export::Csv { id, output } => (
+                        String::from("tickets :: export :: csv"),
+                        export_text(id, output, formats::csv::export),
+                    ),
+                    opt::Export::Ics { id, output } => (
+                        String::from("tickets :: export :: ics"),
+                        export_text(id, output, formats::ics::export),
+                    ),
+                    opt::Export::Org { id, tag, output } => (
+                        String::from("tickets :: export :: org"),
+                        export_text(id, output, |categories| {
+                            formats::outline::org(categories, tag.as_deref())
+                        }),
+                    ),
+                    opt::Export::Markdown { id, tag, output } => (
+                        String::from("tickets :: export :: markdown"),
+                        export_text(id, output, |categories| {
+                            formats::outline::markdown(categories,
+                                                       tag.as_deref())
+                        }),
+                    ),
+                    opt::Export::Taskwarrior { id, output } => (
+                        String::from("tickets :: export :: taskwarrior"),
+                        export_text(id, output, formats::taskwarrior::export),
+                    ),
+                    opt::Export::Todotxt { id, output } => (
+                        String::from("tickets :: export :: todotxt"),
+                        export_text(id, output, formats::todotxt::export),
+                    ),
+                };
+                match result_export {
+                    // printed as is, to be piped
+                    Ok(Some(content)) => {
+                        exit_code = exitcode::OK;
+                        sys_message = content;
+                    },
+                    Ok(None) => {
+                        exit_code = exitcode::OK;
+                        sys_message = format!("{}\n\nSUCCEEDED.",
+                                              format_header);
+                    },
+                    Err(error) => {
+                        exit_code = exitcode::IOERR;
+                        sys_message = format!("{}\n\nERROR: {}",
+                                              format_header,
+                                              error);
+                    },
+                };
+            },
+            opt::Action::Import { format } => {
+                let (format_header, result_records, options) = match format {
+                    opt::Import::Csv { file, map, category, options } => (
+                        format!("tickets :: import :: csv :: {}",
+                                file.display()),
+                        fs::read_to_string(&file)
+                            .map_err(|error| error.to_string())
+                            .and_then(|text| {
+                                formats::csv::records(&text,
+                                                      &map,
+                                                      category.as_deref())
+                            }),
+                        options,
+                    ),
+                    opt::Import::Todotxt { file, category, options } => (
+                        format!("tickets :: import :: todotxt :: {}",
+                                file.display()),
+                        fs::read_to_string(&file)
+                            .map(|text| {
+                                formats::todotxt::records(&text, &category)
+                            })
+                            .map_err(|error| error.to_string()),
+                        options,
+                    ),
+                    opt::Import::Taskwarrior { file, category, options } => (
+                        format!("tickets :: import :: taskwarrior :: {}",
+                                file.display()),
+                        fs::read_to_string(&file)
+                            .map_err(|error| error.to_string())
+                            .and_then(|text| {
+                                formats::taskwarrior::records(&text, &category)
+                            }),
+                        options,
+                    ),
+                    opt::Import::Github { file,
+                                          open_category,
+                                          closed_category,
+                                          options } => (
+                        format!("tickets :: import :: github :: {}",
+                                file.display()),
+                        fs::read_to_string(&file)
+                            .map_err(|error| error.to_string())
+                            .and_then(|text| {
+                                formats::github::records(&text,
+                                                         &open_category,
+                                                         &closed_category)
+                            }),
+                        options,
+                    ),
+                    opt::Import::Mail { path, category, options } => (
+                        format!("tickets :: import :: mail :: {}",
+                                path.display()),
+                        formats::mail::records(&path, &category),
+                        options,
+                    ),
+                };
+                let result_import = result_records.and_then(|records| {
+                    let conflict = options.on_conflict.parse()?;
+                    formats::import::apply(&records,
+                                           conflict,
+                                           options.dry_run)
+                        .map_err(|error| error.to_string())
+                });
+                match result_import {
+                    Ok(report) => {
+                        exit_code = exitcode::OK;
+                        let status = if options.dry_run {
+                            "DRY RUN."
+                        } else {
+                            "SUCCEEDED."
+                        };
+                        sys_message = if report.is_empty() {
+                            format!("{}\n\nNO TICKETS.\n\n{}",
+                                    format_header,
+                                    status)
+                        } else {
+                            format!("{}\n\n{}\n\n{}",
+                                    format_header,
+                                    report.join("\n"),
+                                    status)
+                        };
+                    },
+                    Err(error) => {
+                        exit_code = exitcode::DATAERR;
+                        sys_message = format!("{}\n\nERROR: {}",
+                                              format_header,
+                                              error);
+                    },
+                };
+            },
+            opt::Action::Remote { action } => {
+                let (format_header, result_remote) = match action {
+                    opt::Remote::Add { name,
+                                       url,
+                                       kind,
+                                       project,
+                                       user,
+                                       categories } => {
+                        let remote = remote::Remote {
+                            name,
+                            kind,
+                            url,
+                            project,
+                            user,
+                            categories,
+                        };
+                        (format!("tickets :: remote :: add :: {}", remote.name),
+                         remote.add().map(|()| None))
+                    },
+                    opt::Remote::List => (
+                        String::from("tickets :: remote :: list"),
+                        remote::Remote::names().and_then(|names| {
+                            let lines = names
+                                .iter()
+                                .map(|name| {
+                                    remote::Remote::load(name).map(|remote| {
+                                        format!("{}\t{}", remote.name, remote.url)
+                                    })
+                                })
+                                .collect::<io::Result<Vec<String>>>()?;
+                            Ok(Some(lines.join("\n")))
+                        }),
+                    ),
+                    opt::Remote::Remove { name } => (
+                        format!("tickets :: remote :: remove :: {}", name),
+                        remote::Remote::remove(&name).map(|()| None),
+                    ),
+                };
+                match result_remote {
+                    // printed as is, like config list
+                    Ok(Some(lines)) => {
+                        exit_code = exitcode::OK;
+                        sys_message = lines;
+                    },
+                    Ok(None) => {
+                        exit_code = exitcode::OK;
+                        sys_message = format!("{}\n\nSUCCEEDED.",
+                                              format_header);
+                    },
+                    Err(error) => {
+                        exit_code = exitcode::CONFIG;
+                        sys_message = format!("{}\n\nERROR: {}",
+                                              format_header,
+                                              error);
+                    },
+                };
+            },
+            opt::Action::Sync { name, prefer, dry_run } => {
+                let result_name = match name {
+                    Some(name_) => Ok(name_),
+                    None => remote::Remote::names()
+                        .map_err(|error| error.to_string())
+                        .and_then(|names| match &names[..] {
+                            [name_] => Ok(name_.clone()),
+                            [] => Err(String::from("No remote is added.")),
+                            _ => Err(format!("Specify one of the remotes: {}",
+                                             names.join(", "))),
+                        }),
+                };
+                let name = match result_name {
+                    Ok(name_) => name_,
+                    Err(error) => {
+                        exit_code = exitcode::CONFIG;
+                        sys_message = format!("tickets :: sync\n\nERROR: {}",
+                                              error);
+                        return (exit_code, sys_message);
+                    },
+                };
+                let format_header = format!("tickets :: sync :: {}", name);
+                let result_sync = remote::Remote::load(&name)
+                    .map_err(|error| error.to_string())
+                    .and_then(|remote_| {
+                        let prefer = match prefer {
+                            Some(prefer_) => Some(prefer_.parse()?),
+                            None => None,
+                        };
+                        let adapter = remote_.adapter()?;
+                        remote::sync(&remote_, adapter.as_ref(), prefer, dry_run)
+                            .map_err(|error| error.to_string())
+                    });
+                match result_sync {
+                    Ok(report) => {
+                        exit_code = exitcode::OK;
+                        let status = if dry_run {
+                            "DRY RUN."
+                        } else {
+                            "SUCCEEDED."
+                        };
+                        sys_message = if report.is_empty() {
+                            format!("{}\n\nUP TO DATE.\n\n{}",
+                                    format_header,
+                                    status)
+                        } else {
+                            format!("{}\n\n{}\n\n{}",
+                                    format_header,
+                                    report.join("\n"),
+                                    status)
+                        };
+                    },
+                    Err(error) => {
+                        exit_code = exitcode::UNAVAILABLE;
+                        sys_message = format!("{}\n\nERROR: {}",
+                                              format_header,
+                                              error);
+                    },
+                };
+            },
+            opt::Action::Config { action } => {
+                let (format_header, scope) = match &action {
+                    opt::Config::Get { scope, key }
+                        | opt::Config::Unset { scope, key }
+                        | opt::Config::Set { scope, key, .. } => (
+                        format!("tickets :: config :: {}", key),
+                        scope,
+                    ),
+                    opt::Config::List { scope } => (
+                        String::from("tickets :: config"),
+                        scope,
+                    ),
+                };
+                let scope = match config_scope(scope) {
+                    Ok(scope_) => scope_,
+                    Err(error) => {
+                        exit_code = exitcode::USAGE;
+                        sys_message = format!("{}\n\nERROR: {}",
+                                              format_header,
+                                              error);
+                        return (exit_code, sys_message);
+                    },
+                };
+                let options = || match scope {
+                    Some(scope_) => config::list_scope(scope_),
+                    None => config::list(),
+                };
+                // the values are printed alone, like git config
+                let result_config = match action {
+                    opt::Config::Get { key, .. } => options().and_then(|opts| {
+                        opts.get(&key).cloned().ok_or_else(|| io::Error::new(
+                            io::ErrorKind::NotFound,
+                            format!("The key {} is not set.", key)))
+                    }),
+                    opt::Config::List { .. } => options().map(|opts| {
+                        opts.iter()
+                            .map(|(key, value)| format!("{}={}", key, value))
+                            .collect::<Vec<String>>()
+                            .join("\n")
+                    }),
+                    opt::Config::Set { key, value, .. } => config::set(
+                        scope.unwrap_or(config::Scope::Global),
+                        &key,
+                        &value).map(|()| format!("{}\n\nSUCCEEDED.",
+                                                 format_header)),
+                    opt::Config::Unset { key, .. } => config::unset(
+                        scope.unwrap_or(config::Scope::Global),
+                        &key).map(|()| format!("{}\n\nSUCCEEDED.",
+                                               format_header)),
+                };
+                match result_config {
+                    Ok(message) => {
+                        exit_code = exitcode::OK;
+                        sys_message = message;
+                    },
+                    Err(error) => {
+                        exit_code = exitcode::CONFIG;
+                        sys_message = format!("{}\n\nERROR: {}",
+                                              format_header,
+                                              error);
+                    },
+                };
+            },
+            opt::Action::Completions { shell } => {
+                match completion::script(&shell) {
+                    Ok(script) => {
+                        exit_code = exitcode::OK;
+                        sys_message = script.trim_end().to_string();
+                    },
+                    Err(error) => {
+                        exit_code = exitcode::USAGE;
+                        sys_message = format!(
+                            "tickets :: completions\n\nERROR: {}",
+                            error);
+                    },
+                };
+            },
             opt::Action::Edit { id, message } => {
+                let id = match resolve::resolve(&id) {
+                    Ok(resolved) => resolved,
+                    Err(error) => {
+                        exit_code = exitcode::IOERR;
+                        sys_message = format!("tickets :: edit\n\nERROR: {}",
+                                              error);
+                        return (exit_code, sys_message);
+                    },
+                };
                 let result_ticket = ticket::Ticket::from(id, message);
                 match result_ticket {
                     Ok(ticket) => {
@@ -246,7 +1011,16 @@ pub fn match_action(opt: opt::Opt) -> (exitcode::ExitCode, String) {
                     },
                 };
             },
-            opt::Action::Show { id } => {
+            opt::Action::Show { id, no_comments, raw } => {
+                let id = match resolve::resolve(&id) {
+                    Ok(resolved) => resolved,
+                    Err(error) => {
+                        exit_code = exitcode::IOERR;
+                        sys_message = format!("tickets :: show\n\nERROR: {}",
+                                              error);
+                        return (exit_code, sys_message);
+                    },
+                };
                 let result_ticket = ticket::Ticket::from(id, None);
                 match result_ticket {
                     Ok(mut ticket) => {
@@ -257,27 +1031,95 @@ pub fn match_action(opt: opt::Opt) -> (exitcode::ExitCode, String) {
                                                   format_header);
                         }
                         else if ticket.is_dir {
-                            exit_code = exitcode::OK;
-                            sys_message = format!("{}\n\n{}",
-                                                  format_header,
-                                                  ticket);
+                            match ticket.collect() {
+                                Ok(tickets) => {
+                                    let unit = estimate::Unit::current();
+                                    if let Err(error) =
+                                        estimate::rollup(&tickets, &unit) {
+                                        eprintln!("WARNING: No total of {}/: \
+                                                   {}",
+                                                  ticket.category,
+                                                  error);
+                                    }
+                                    exit_code = exitcode::OK;
+                                    sys_message = format!("{}\n\n{}",
+                                                          format_header,
+                                                          ticket);
+                                },
+                                Err(error) => {
+                                    exit_code = exitcode::IOERR;
+                                    sys_message = format!("{}\n\nERROR: {}",
+                                                          format_header,
+                                                          error);
+                                },
+                            }
+                        }
+                        else if raw {
+                            match fs::read_to_string(&ticket.path) {
+                                Ok(content) => {
+                                    print!("{}", content);
+                                    exit_code = exitcode::OK;
+                                    sys_message = String::new();
+                                },
+                                Err(error) => {
+                                    exit_code = exitcode::IOERR;
+                                    sys_message = format!("{}\n\nERROR: {}",
+                                                          format_header,
+                                                          error);
+                                },
+                            }
                         }
                         else {
                             // TODO: Display includes this individual read
                             ticket.read().unwrap();
+                            // Markdown is rendered only on the terminal,
+                            // so that the piped output stays plain,
+                            // and styled only with the colors
+                            let rendered = io::stdout().is_terminal();
+                            let colored = style::enabled();
                             let ticket_title = match &ticket.title {
                                 Some(title) => &title[..],
                                 None => "",
                             };
-                            let ticket_message = match ticket.message {
+                            let ticket_message = match &ticket.message {
+                                Some(message) if rendered => {
+                                    format!("\n\n{}",
+                                            markdown::render(message, colored))
+                                },
                                 Some(message) => format!("\n\n{}", message),
                                 None => String::new(),
                             };
+                            let ticket_comments = if no_comments {
+                                String::new()
+                            } else {
+                                match ticket.read_comments() {
+                                    Ok(comments) => comments
+                                        .into_iter()
+                                        .map(|mut comment| {
+                                            if rendered {
+                                                comment.body = markdown::render(
+                                                    &comment.body,
+                                                    colored);
+                                            }
+                                            format!("\n\n{}", comment)
+                                        })
+                                        .collect(),
+                                    Err(error) => {
+                                        exit_code = exitcode::IOERR;
+                                        sys_message = format!(
+                                            "{}\n\nERROR: {}",
+                                            format_header,
+                                            error);
+                                        return (exit_code, sys_message);
+                                    },
+                                }
+                            };
                             exit_code = exitcode::OK;
-                            sys_message = format!("{}\n\n{}{}",
+                            sys_message = format!("{}\n\n{}{}{}",
                                                   format_header,
                                                   ticket_title,
-                                                  ticket_message);
+                                                  ticket_message,
+                                                  ticket_comments);
                         }
                     },
                     Err(error) => {
@@ -287,26 +1129,328 @@ pub fn match_action(opt: opt::Opt) -> (exitcode::ExitCode, String) {
                     },
                 };
             },
-            opt::Action::Move { id, dest_id } => {
-                let result_start_ticket = ticket::Ticket::from(id, None);
-                let result_dest_ticket = ticket::Ticket::from(dest_id, None);
-                let start_ticket;
-                let dest_ticket;
-                match result_start_ticket {
+            opt::Action::Comment { id, message } => {
+                let result_ticket = ticket::Ticket::from(id, None);
+                match result_ticket {
                     Ok(ticket) => {
-                        start_ticket = ticket;
+                        let format_header = format_header_comment(&ticket);
+                        match ticket.comment(message) {
+                            Ok(()) => {
+                                exit_code = exitcode::OK;
+                                sys_message = format!("{}\n\nSUCCEEDED.",
+                                                      format_header);
+                            },
+                            Err(error) => {
+                                exit_code = exitcode::IOERR;
+                                sys_message = format!("{}\n\nERROR: {}",
+                                                      format_header,
+                                                      error);
+                            },
+                        };
                     },
                     Err(error) => {
                         exit_code = exitcode::IOERR;
-                        sys_message = format!("tickets :: move\n\nERROR: {}",
+                        sys_message = format!("tickets :: comment\n\nERROR: {}",
+                                              error);
+                    },
+                };
+            },
+            opt::Action::Attach { id, files } => {
+                let result_ticket = ticket::Ticket::from(id, None);
+                match result_ticket {
+                    Ok(ticket) => {
+                        let format_header = format_header_attach(&ticket);
+                        match ticket.attach(&files) {
+                            Ok(()) => {
+                                exit_code = exitcode::OK;
+                                sys_message = format!("{}\n\nSUCCEEDED.",
+                                                      format_header);
+                            },
+                            Err(error) => {
+                                exit_code = exitcode::IOERR;
+                                sys_message = format!("{}\n\nERROR: {}",
+                                                      format_header,
+                                                      error);
+                            },
+                        };
+                    },
+                    Err(error) => {
+                        exit_code = exitcode::IOERR;
+                        sys_message = format!("tickets :: attach\n\nERROR: {}",
+                                              error);
+                    },
+                };
+            },
+            opt::Action::Attachments { id } => {
+                let result_ticket = ticket::Ticket::from(id, None);
+                match result_ticket {
+                    Ok(ticket) => {
+                        let format_header = format_header_attachments(&ticket);
+                        match ticket.attachments() {
+                            Ok(names) => {
+                                let str_names = if names.is_empty() {
+                                    String::from("NO ATTACHMENTS.")
+                                } else {
+                                    names.join("\n")
+                                };
+                                exit_code = exitcode::OK;
+                                sys_message = format!("{}\n\n{}",
+                                                      format_header,
+                                                      str_names);
+                            },
+                            Err(error) => {
+                                exit_code = exitcode::IOERR;
+                                sys_message = format!("{}\n\nERROR: {}",
+                                                      format_header,
+                                                      error);
+                            },
+                        };
+                    },
+                    Err(error) => {
+                        exit_code = exitcode::IOERR;
+                        sys_message = format!(
+                            "tickets :: attachments\n\nERROR: {}",
+                            error);
+                    },
+                };
+            },
+            opt::Action::Detach { id, name } => {
+                let result_ticket = ticket::Ticket::from(id, None);
+                match result_ticket {
+                    Ok(ticket) => {
+                        let format_header = format_header_detach(&ticket);
+                        match ticket.detach(&name) {
+                            Ok(()) => {
+                                exit_code = exitcode::OK;
+                                sys_message = format!("{}\n\nSUCCEEDED.",
+                                                      format_header);
+                            },
+                            Err(error) => {
+                                exit_code = exitcode::IOERR;
+                                sys_message = format!("{}\n\nERROR: {}",
+                                                      format_header,
+                                                      error);
+                            },
+                        };
+                    },
+                    Err(error) => {
+                        exit_code = exitcode::IOERR;
+                        sys_message = format!("tickets :: detach\n\nERROR: {}",
+                                              error);
+                    },
+                };
+            },
+            opt::Action::Start { id } => {
+                let result_ticket = ticket::Ticket::from(id, None);
+                match result_ticket {
+                    Ok(ticket) => {
+                        let format_header = format_header_start(&ticket);
+                        match timelog::Timer::start(&ticket) {
+                            Ok(_) => {
+                                exit_code = exitcode::OK;
+                                sys_message = format!("{}\n\nSUCCEEDED.",
+                                                      format_header);
+                            },
+                            Err(error) => {
+                                exit_code = exitcode::IOERR;
+                                sys_message = format!("{}\n\nERROR: {}",
+                                                      format_header,
+                                                      error);
+                            },
+                        };
+                    },
+                    Err(error) => {
+                        exit_code = exitcode::IOERR;
+                        sys_message = format!("tickets :: start\n\nERROR: {}",
+                                              error);
+                    },
+                };
+            },
+            opt::Action::Stop { discard: true } => {
+                match timelog::Timer::discard() {
+                    Ok(timer) => {
+                        // a broken timer has no ticket to tell
+                        let format_header = if timer.id.is_empty() {
+                            String::from("tickets :: stop")
+                        } else {
+                            format!("tickets :: stop :: {}", timer.id)
+                        };
+                        exit_code = exitcode::OK;
+                        sys_message = format!("{}\n\nDISCARDED.\n\nSUCCEEDED.",
+                                              format_header);
+                    },
+                    Err(error) => {
+                        exit_code = exitcode::IOERR;
+                        sys_message = format!("tickets :: stop\n\nERROR: {}",
+                                              error);
+                    },
+                };
+            },
+            opt::Action::Stop { discard: false } => {
+                match timelog::Timer::stop() {
+                    Ok((ticket, entry)) => {
+                        exit_code = exitcode::OK;
+                        sys_message = format!(
+                            "{}\n\n{} LOGGED.\n\nSUCCEEDED.",
+                            format_header_stop(&ticket),
+                            timelog::format_duration(entry.seconds));
+                    },
+                    Err(error) => {
+                        exit_code = exitcode::IOERR;
+                        sys_message = format!("tickets :: stop\n\nERROR: {}",
+                                              error);
+                    },
+                };
+            },
+            opt::Action::LogTime { id, duration } => {
+                let result_ticket = ticket::Ticket::from(id, None);
+                match result_ticket {
+                    Ok(ticket) => {
+                        let format_header = format_header_log_time(&ticket);
+                        let result_log = timelog::parse_duration(&duration)
+                            .map_err(|error| {
+                                io::Error::new(io::ErrorKind::InvalidInput,
+                                               error)
+                            })
+                            .and_then(|seconds| {
+                                let entry = timelog::Entry {
+                                    started_at: chrono::Local::now(),
+                                    seconds,
+                                };
+                                ticket.log_time(&entry)
+                            });
+                        match result_log {
+                            Ok(()) => {
+                                exit_code = exitcode::OK;
+                                sys_message = format!("{}\n\nSUCCEEDED.",
+                                                      format_header);
+                            },
+                            Err(error) => {
+                                exit_code = exitcode::IOERR;
+                                sys_message = format!("{}\n\nERROR: {}",
+                                                      format_header,
+                                                      error);
+                            },
+                        };
+                    },
+                    Err(error) => {
+                        exit_code = exitcode::IOERR;
+                        sys_message = format!(
+                            "tickets :: log-time\n\nERROR: {}",
+                            error);
+                    },
+                };
+            },
+            opt::Action::Timesheet { since, by, format } => {
+                let format_header = String::from("tickets :: timesheet");
+                let by: timelog::GroupBy = by.parse().unwrap();
+                let result_since = match since {
+                    Some(str_since) => timelog::parse_since(&str_since)
+                        .map(Some),
+                    None => Ok(None),
+                };
+                let since = match result_since {
+                    Ok(since_) => since_,
+                    Err(error) => {
+                        exit_code = exitcode::USAGE;
+                        sys_message = format!("{}\n\nERROR: {}",
+                                              format_header,
                                               error);
                         return (exit_code, sys_message);
                     },
                 };
-                match result_dest_ticket {
+                let result_totals = collect_all()
+                    .and_then(|tickets| timelog::timesheet(&tickets,
+                                                           since,
+                                                           &by));
+                match result_totals {
+                    Ok(totals) => {
+                        exit_code = exitcode::OK;
+                        sys_message = if format == "csv" {
+                            timelog::format_csv(&totals, &by)
+                        } else {
+                            format!("{}\n\n{}",
+                                    format_header,
+                                    timelog::format_text(&totals))
+                        };
+                    },
+                    Err(error) => {
+                        exit_code = exitcode::IOERR;
+                        sys_message = format!("{}\n\nERROR: {}",
+                                              format_header,
+                                              error);
+                    },
+                };
+            },
+            opt::Action::Estimate { id, value } => {
+                let result_ticket = ticket::Ticket::from(id, None);
+                match result_ticket {
                     Ok(ticket) => {
-                        dest_ticket = ticket;
+                        let format_header = format_header_estimate(&ticket);
+                        let result_estimate = estimate::Unit::current()
+                            .parse(&value)
+                            .map_err(|error| {
+                                io::Error::new(io::ErrorKind::InvalidInput,
+                                               error)
+                            })
+                            .and_then(|estimate_| {
+                                ticket.set_meta("estimate",
+                                                Some(&estimate_.to_string()))
+                            });
+                        match result_estimate {
+                            Ok(()) => {
+                                exit_code = exitcode::OK;
+                                sys_message = format!("{}\n\nSUCCEEDED.",
+                                                      format_header);
+                            },
+                            Err(error) => {
+                                exit_code = exitcode::IOERR;
+                                sys_message = format!("{}\n\nERROR: {}",
+                                                      format_header,
+                                                      error);
+                            },
+                        };
+                    },
+                    Err(error) => {
+                        exit_code = exitcode::IOERR;
+                        sys_message = format!(
+                            "tickets :: estimate\n\nERROR: {}",
+                            error);
+                    },
+                };
+            },
+            opt::Action::Move { id, dest_id } => {
+                let id = match resolve::resolve(&id) {
+                    Ok(resolved) => resolved,
+                    Err(error) => {
+                        exit_code = exitcode::IOERR;
+                        sys_message = format!("tickets :: move\n\nERROR: {}",
+                                              error);
+                        return (exit_code, sys_message);
+                    },
+                };
+                let dest_id = match resolve::resolve_destination(&dest_id) {
+                    Ok(resolved) => resolved,
+                    Err(error) => {
+                        exit_code = exitcode::IOERR;
+                        sys_message = format!("tickets :: move\n\nERROR: {}",
+                                              error);
+                        return (exit_code, sys_message);
+                    },
+                };
+                let result_start_ticket = ticket::Ticket::from(id, None);
+                let result_dest_ticket = ticket::Ticket::from(dest_id, None);
+                let start_ticket = match result_start_ticket {
+                    Ok(ticket) => ticket,
+                    Err(error) => {
+                        exit_code = exitcode::IOERR;
+                        sys_message = format!("tickets :: move\n\nERROR: {}",
+                                              error);
+                        return (exit_code, sys_message);
                     },
+                };
+                let dest_ticket = match result_dest_ticket {
+                    Ok(ticket) => ticket,
                     Err(error) => {
                         exit_code = exitcode::IOERR;
                         sys_message = format!("tickets :: move\n\nERROR: {}",
@@ -348,12 +1492,18 @@ pub fn match_action(opt: opt::Opt) -> (exitcode::ExitCode, String) {
                 }
             },
             opt::Action::Remove { id } => {
-                let result_ticket = ticket::Ticket::from(id, None);
-                let ticket;
-                match result_ticket {
-                    Ok(ticket_) => {
-                        ticket = ticket_;
+                let id = match resolve::resolve(&id) {
+                    Ok(resolved) => resolved,
+                    Err(error) => {
+                        exit_code = exitcode::IOERR;
+                        sys_message = format!("tickets :: remove\n\nERROR: {}",
+                                              error);
+                        return (exit_code, sys_message);
                     },
+                };
+                let result_ticket = ticket::Ticket::from(id, None);
+                let ticket = match result_ticket {
+                    Ok(ticket_) => ticket_,
                     Err(error) => {
                         exit_code = exitcode::IOERR;
                         sys_message = format!("tickets :: remove\n\nERROR: {}",
@@ -387,7 +1537,7 @@ pub fn match_action(opt: opt::Opt) -> (exitcode::ExitCode, String) {
         match show_all() {
             Ok(str_show_all) => {
                 exit_code = exitcode::OK;
-                sys_message = format!("{}", str_show_all);
+                sys_message = str_show_all;
             },
             Err(error) => {
                 exit_code = exitcode::IOERR;
@@ -404,9 +1554,19 @@ mod tests{
 
     #[test]
     fn get_path_root_gives_the_constant_root_path() {
-        let root = super::get_path_root();
-        let answer = dirs::home_dir().unwrap().join(".tickets");
-        assert_eq!(root, answer);
+        let home = || PathBuf::from("/home/user");
+        assert_eq!(super::root_of(None, || None, home),
+                   PathBuf::from("/home/user/.tickets"));
+        assert_eq!(super::root_of(Some("".into()), || None, home),
+                   PathBuf::from("/home/user/.tickets"));
+        assert_eq!(super::root_of(None,
+                                  || Some(PathBuf::from("/srv/tickets")),
+                                  home),
+                   PathBuf::from("/srv/tickets"));
+        assert_eq!(super::root_of(Some("/tmp/tickets".into()),
+                                  || Some(PathBuf::from("/srv/tickets")),
+                                  home),
+                   PathBuf::from("/tmp/tickets"));
     }
 
     #[test]
@@ -432,7 +1592,8 @@ mod tests{
             },
             Err(error) => {
                 assert_eq!(error.to_string(),
-                           "The root ~/.tickets already exists.");
+                           format!("The root {} already exists.",
+                                   super::get_path_root().display()));
             },
         };
     }
@@ -5,8 +5,57 @@ use structopt::StructOpt;
 use tickets::{self, opt};
 
 fn main() {
-    let opt = opt::Opt::from_args();
+    // the helper for the completion scripts, out of the definition
+    // to be hidden from the scripts themselves
+    let args: Vec<String> = std::env::args().collect();
+    if args.get(1).map(|arg| &arg[..]) == Some("__complete") {
+        let (exit_code, sys_message) =
+            tickets::complete(args.get(2).map(|arg| &arg[..]));
+        tickets::die(exit_code, sys_message);
+    }
+
+    let args = match tickets::alias::expand(args) {
+        Ok(tickets::alias::Expanded::Args(args)) => args,
+        Ok(tickets::alias::Expanded::Shell { name, command, args }) => {
+            let exit_code = tickets::alias::run_shell(&name, &command, &args);
+            tickets::die(exit_code, String::new());
+            return;
+        },
+        Err(error) => {
+            tickets::die(exitcode::CONFIG,
+                         format!("tickets :: alias\n\nERROR: {}", error));
+            return;
+        },
+    };
+
+    if let Some((path, args_plugin)) = tickets::plugin::external(&args) {
+        let exit_code = tickets::plugin::run(&path, &args_plugin);
+        tickets::die(exit_code, String::new());
+        return;
+    }
+
+    // look for the plugins only to show them in the help
+    let wants_help = args
+        .iter()
+        .skip(1)
+        .any(|arg| arg == "help" || arg == "-h" || arg == "--help");
+    let help_plugins = if wants_help {
+        tickets::plugin::help()
+    } else {
+        String::new()
+    };
+    let mut app = opt::Opt::clap();
+    if !help_plugins.is_empty() {
+        app = app.after_help(&help_plugins[..]);
+    }
+    let opt = opt::Opt::from_clap(&app.get_matches_from(args));
     // println!("{:?}", opt);
+    let color = opt.color
+        .clone()
+        .or_else(|| tickets::config::get("color.ui"))
+        .and_then(|color| color.parse().ok())
+        .unwrap_or(tickets::style::Choice::Auto);
+    tickets::style::set_choice(color);
 
     let (exit_code, sys_message) = tickets::match_action(opt);
     tickets::die(exit_code, sys_message);
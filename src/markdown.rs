@@ -0,0 +1,287 @@
+use pulldown_cmark::{Event, HeadingLevel, Parser, Tag};
+
+const BOLD: &str = "\x1b[1m";
+const DIM: &str = "\x1b[2m";
+const ITALIC: &str = "\x1b[3m";
+const UNDERLINE: &str = "\x1b[4m";
+const CODE: &str = "\x1b[36m";
+const RESET: &str = "\x1b[0m";
+
+struct Renderer {
+    out: String,
+    /// Whether the styles are written as escapes, or only the layout.
+    colored: bool,
+    styles: Vec<&'static str>,
+    /// The next number of each nested list, or None for bullets.
+    lists: Vec<Option<u64>>,
+    quotes: usize,
+    /// Whether the list item has nothing but its bullet yet.
+    item_start: bool,
+    in_code: bool,
+    link: Option<String>,
+}
+
+impl Renderer {
+    fn escape(&mut self, escape: &str) {
+        if self.colored {
+            self.out.push_str(escape);
+        }
+    }
+
+    /// Resume the styles open, after a reset.
+    fn restyle(&mut self) {
+        if self.colored {
+            for style in &self.styles {
+                self.out.push_str(style);
+            }
+        }
+    }
+
+    fn push_style(&mut self, style: &'static str) {
+        self.styles.push(style);
+        self.escape(style);
+    }
+
+    fn pop_style(&mut self) {
+        self.styles.pop();
+        self.escape(RESET);
+        self.restyle();
+    }
+
+    /// Break the line, continuing the quotes and the list indentation.
+    fn newline(&mut self) {
+        self.out.push('\n');
+        if self.quotes > 0 {
+            self.escape(DIM);
+            self.out.push_str(&"│ ".repeat(self.quotes));
+            self.escape(RESET);
+            self.restyle();
+        }
+        for list in &self.lists {
+            self.out.push_str(if list.is_some() { "   " } else { "  " });
+        }
+        if self.in_code {
+            self.out.push_str("    ");
+        }
+    }
+
+    /// Start a block, separated from the previous one by blank lines.
+    fn start_block(&mut self, blank: bool) {
+        if self.item_start {
+            self.item_start = false;
+            return;
+        }
+        if self.out.is_empty() {
+            return;
+        }
+        self.newline();
+        if blank {
+            self.newline();
+        }
+    }
+
+    fn text(&mut self, text: &str) {
+        self.item_start = false;
+        let mut lines = text.split('\n');
+        if let Some(first) = lines.next() {
+            self.out.push_str(first);
+        }
+        for line in lines {
+            self.newline();
+            self.out.push_str(line);
+        }
+    }
+
+    fn start(&mut self, tag: Tag) {
+        match tag {
+            Tag::Paragraph => self.start_block(self.lists.is_empty()),
+            Tag::Heading(level, _, _) => {
+                self.start_block(true);
+                if level == HeadingLevel::H1 {
+                    self.push_style(UNDERLINE);
+                }
+                self.push_style(BOLD);
+            },
+            Tag::BlockQuote => {
+                self.start_block(true);
+                self.quotes += 1;
+                self.escape(DIM);
+                self.out.push_str("│ ");
+                self.escape(RESET);
+                self.item_start = true;
+            },
+            Tag::CodeBlock(_) => {
+                self.start_block(self.lists.is_empty());
+                self.out.push_str("    ");
+                self.in_code = true;
+                self.push_style(CODE);
+            },
+            Tag::List(start) => {
+                if self.lists.is_empty() {
+                    self.start_block(true);
+                    self.item_start = true;
+                }
+                self.lists.push(start);
+            },
+            Tag::Item => {
+                let marker = match self.lists.last_mut() {
+                    Some(Some(number)) => {
+                        *number += 1;
+                        format!("{}. ", *number - 1)
+                    },
+                    _ => String::from("• "),
+                };
+                // the marker takes the place of its own indentation
+                let list = self.lists.pop();
+                self.start_block(false);
+                self.lists.extend(list);
+                self.out.push_str(&marker);
+                self.item_start = true;
+            },
+            Tag::Emphasis => self.push_style(ITALIC),
+            Tag::Strong => self.push_style(BOLD),
+            Tag::Link(_, url, _) => {
+                self.link = Some(url.to_string());
+                self.push_style(UNDERLINE);
+            },
+            _ => (),
+        }
+    }
+
+    fn end(&mut self, tag: Tag) {
+        match tag {
+            Tag::Heading(level, _, _) => {
+                self.pop_style();
+                if level == HeadingLevel::H1 {
+                    self.pop_style();
+                }
+            },
+            Tag::BlockQuote => {
+                self.quotes -= 1;
+            },
+            Tag::CodeBlock(_) => {
+                // the code block ends with its own line break
+                let trimmed = self.out.trim_end_matches(&['\n', ' '][..]);
+                self.out.truncate(trimmed.len());
+                self.in_code = false;
+                self.pop_style();
+            },
+            Tag::List(_) => {
+                self.lists.pop();
+            },
+            Tag::Emphasis | Tag::Strong => self.pop_style(),
+            Tag::Link(_, _, _) => {
+                let url = self.link.take().unwrap_or_default();
+                let is_autolink = self.out.ends_with(&url[..]);
+                self.pop_style();
+                if !is_autolink {
+                    self.out.push(' ');
+                    self.escape(DIM);
+                    self.out.push_str(&format!("({})", url));
+                    self.escape(RESET);
+                    self.restyle();
+                }
+            },
+            _ => (),
+        }
+    }
+}
+
+/// Render Markdown for the terminal, with its styles only if colored.
+pub fn render(text: &str, colored: bool) -> String {
+    let mut renderer = Renderer {
+        out: String::new(),
+        colored,
+        styles: Vec::new(),
+        lists: Vec::new(),
+        quotes: 0,
+        item_start: false,
+        in_code: false,
+        link: None,
+    };
+    for event in Parser::new(text) {
+        match event {
+            Event::Start(tag) => renderer.start(tag),
+            Event::End(tag) => renderer.end(tag),
+            Event::Text(text) | Event::Html(text) => renderer.text(&text),
+            Event::Code(code) => {
+                renderer.push_style(CODE);
+                renderer.text(&code);
+                renderer.pop_style();
+            },
+            Event::SoftBreak | Event::HardBreak => renderer.newline(),
+            Event::Rule => {
+                renderer.start_block(true);
+                renderer.escape(DIM);
+                renderer.out.push_str(&"─".repeat(20));
+                renderer.escape(RESET);
+            },
+            _ => (),
+        }
+    }
+    renderer.out
+}
+
+#[cfg(test)]
+mod tests {
+    fn strip(text: &str) -> String {
+        let mut stripped = String::new();
+        let mut in_escape = false;
+        for ch in text.chars() {
+            match ch {
+                '\x1b' => in_escape = true,
+                'm' if in_escape => in_escape = false,
+                _ if in_escape => (),
+                _ => stripped.push(ch),
+            }
+        }
+        stripped
+    }
+
+    #[test]
+    fn render_emphasis() {
+        assert_eq!(super::render("Some *emphasis* and **strong**", true),
+                   "Some \x1b[3memphasis\x1b[0m and \x1b[1mstrong\x1b[0m");
+    }
+
+    #[test]
+    fn render_headings_and_paragraphs() {
+        assert_eq!(super::render("# Title\n\nSome\ntext\n\n## Section", true),
+                   "\x1b[4m\x1b[1mTitle\x1b[0m\x1b[4m\x1b[0m\n\nSome\ntext\n\n\
+                   \x1b[1mSection\x1b[0m");
+    }
+
+    #[test]
+    fn render_lists() {
+        assert_eq!(strip(&super::render("Some suggestions:\n\
+                                         - More detailed\n\
+                                         - Colored\n  \
+                                           - Nested\n\n\
+                                         1. first\n\
+                                         2. second", true)),
+                   "Some suggestions:\n\n\
+                   • More detailed\n\
+                   • Colored\n  \
+                     • Nested\n\n\
+                   1. first\n\
+                   2. second");
+    }
+
+    #[test]
+    fn render_code_and_links() {
+        assert_eq!(strip(&super::render("Run `cargo`:\n\n```\ncargo test\n\
+                                         cargo run\n```\n\n\
+                                         See [docs](https://docs.rs) \
+                                         or <https://crates.io>", true)),
+                   "Run cargo:\n\n    cargo test\n    cargo run\n\n\
+                   See docs (https://docs.rs) or https://crates.io");
+    }
+
+    #[test]
+    fn render_without_colors() {
+        let text = "# Title\n\n> Some *quote*\n\n- [docs](https://docs.rs)\n\n---";
+        let plain = super::render(text, false);
+        assert!(!plain.contains('\x1b'));
+        assert_eq!(plain, strip(&super::render(text, true)));
+    }
+}
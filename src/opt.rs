@@ -1,3 +1,5 @@
+use std::path::PathBuf;
+
 use structopt::StructOpt;
 
 #[derive(Debug, StructOpt)]
@@ -12,6 +14,16 @@ use structopt::StructOpt;
 ///
 /// And without SUBCOMMANDS, tickets will show all of your tickets.
 pub struct Opt {
+    #[structopt(long = "color",
+                raw(possible_values = r#"&["auto", "always", "never"]"#,
+                    global = "true"))]
+    /// When to color the output, auto by default or the option color.ui
+    ///
+    /// With auto, the output is colored on a terminal, unless the
+    /// environment variable NO_COLOR is set; CLICOLOR_FORCE colors it
+    /// even when piped.
+    pub color: Option<String>,
+
     #[structopt(subcommand)]
     pub action: Option<Action>,
 }
@@ -40,6 +52,119 @@ pub enum Action {
     Show {
         /// Target identifier
         id: String,
+        #[structopt(long = "no-comments")]
+        /// Do not show the comments of the ticket
+        no_comments: bool,
+        #[structopt(long = "raw")]
+        /// Print the ticket file exactly as stored, without Markdown rendering
+        raw: bool,
+    },
+
+    #[structopt(name = "board")]
+    /// Show categories side by side as a kanban board
+    ///
+    /// Without categories given, the board shows the categories
+    /// in the environment variable TICKETS_BOARD_COLUMNS or the option
+    /// board.columns, or all of them in alphabetical order.
+    Board {
+        /// Category identifiers, in the order of columns
+        ids: Vec<String>,
+    },
+
+    #[structopt(name = "tui")]
+    /// Browse and manage tickets in an interactive interface
+    Tui,
+
+    #[structopt(name = "serve")]
+    /// Serve a REST API of the tickets over HTTP
+    ///
+    /// GET /categories, GET/PUT/DELETE /categories/{category},
+    /// GET/PUT/DELETE /tickets/{category}/{id},
+    /// and POST /tickets/{category}/{id}/move are available.
+    Serve {
+        #[structopt(long = "bind", default_value = "127.0.0.1:8080")]
+        /// Address to listen on
+        bind: String,
+    },
+
+    #[structopt(name = "watch")]
+    /// Print the tickets created, edited, moved and removed as they are
+    ///
+    /// Each change is printed on a line, or as an object of NDJSON with
+    /// --json, until the program is interrupted.
+    Watch {
+        /// Category to watch, or all categories if omitted
+        id: Option<String>,
+        #[structopt(long = "json")]
+        /// Print the changes as NDJSON
+        json: bool,
+    },
+
+    #[structopt(name = "export")]
+    /// Export tickets to other formats
+    Export {
+        #[structopt(subcommand)]
+        format: Export,
+    },
+
+    #[structopt(name = "import")]
+    /// Import tickets from other formats
+    Import {
+        #[structopt(subcommand)]
+        format: Import,
+    },
+
+    #[structopt(name = "remote")]
+    /// Manage the remote issue trackers to sync with
+    Remote {
+        #[structopt(subcommand)]
+        action: Remote,
+    },
+
+    #[structopt(name = "sync")]
+    /// Pull the issues changed on a remote and push the tickets changed
+    /// locally
+    ///
+    /// The issues go into the categories of their statuses, like
+    /// in-progress for In Progress, and moving a ticket transitions its
+    /// issue. A ticket changed on both sides is left as a conflict,
+    /// unless --prefer tells which side wins.
+    Sync {
+        /// Name of the remote, which may be omitted if it is the only one
+        name: Option<String>,
+        #[structopt(long = "prefer",
+                    raw(possible_values = r#"&["local", "remote"]"#))]
+        /// Resolve the conflicts in favor of a side
+        prefer: Option<String>,
+        #[structopt(long = "dry-run")]
+        /// Only report what would be pulled and pushed
+        dry_run: bool,
+    },
+
+    #[structopt(name = "completions")]
+    /// Print the completion script for a shell
+    ///
+    /// The script completes the subcommands, the options and also
+    /// the identifiers of the existing categories and tickets, e.g.
+    /// for bash: source <(tickets completions bash)
+    Completions {
+        #[structopt(raw(possible_values = r#"&["bash", "zsh", "fish"]"#))]
+        /// Target shell
+        shell: String,
+    },
+
+    #[structopt(name = "config")]
+    /// Get and set the options, like git config
+    ///
+    /// The options are read from /etc/tickets/config.toml,
+    /// ~/.config/tickets/config.toml and config.toml in ~/.tickets,
+    /// the latter overriding the former.
+    ///
+    /// The root ~/.tickets is moved by the option core.root, and
+    /// the environment variable TICKETS_ROOT overrides both.
+    Config {
+        #[structopt(subcommand)]
+        action: Config,
     },
 
     #[structopt(name = "edit")]
@@ -55,6 +180,97 @@ pub enum Action {
         message: Option<String>,
     },
 
+    #[structopt(name = "comment")]
+    /// Add a comment to a certain ticket
+    Comment {
+        /// Target identifier
+        id: String,
+        #[structopt(short = "m", long = "message")]
+        /// Message of the comment
+        ///
+        /// Without this option, the program will open your EDITOR
+        /// (from environmental variables) to write the comment.
+        message: Option<String>,
+    },
+
+    #[structopt(name = "attach")]
+    /// Attach files to a certain ticket
+    Attach {
+        /// Target identifier
+        id: String,
+        #[structopt(name = "FILE", parse(from_os_str), raw(required = "true"))]
+        /// Files to be attached
+        files: Vec<PathBuf>,
+    },
+
+    #[structopt(name = "attachments")]
+    /// List the attachments of a certain ticket
+    Attachments {
+        /// Target identifier
+        id: String,
+    },
+
+    #[structopt(name = "detach")]
+    /// Remove an attachment from a certain ticket
+    Detach {
+        /// Target identifier
+        id: String,
+        /// Name of the attachment
+        name: String,
+    },
+
+    #[structopt(name = "start")]
+    /// Start working on a certain ticket, tracking the time
+    Start {
+        /// Target identifier
+        id: String,
+    },
+
+    #[structopt(name = "stop")]
+    /// Stop working on the tracked ticket, and log the time
+    Stop {
+        #[structopt(long = "discard")]
+        /// Stop without logging the time, even if the ticket is gone
+        discard: bool,
+    },
+
+    #[structopt(name = "log-time")]
+    /// Log the time spent on a certain ticket, such as 1h30m
+    LogTime {
+        /// Target identifier
+        id: String,
+        /// Spent time in hours, minutes and seconds, e.g. 1h30m, 45m
+        duration: String,
+    },
+
+    #[structopt(name = "timesheet")]
+    /// Show the total time logged, grouped by ticket, category or day
+    Timesheet {
+        #[structopt(long = "since")]
+        /// Count from today, yesterday, a weekday like monday,
+        /// or a date like 2019-01-02
+        since: Option<String>,
+        #[structopt(long = "by", default_value = "ticket",
+                    raw(possible_values = r#"&["ticket", "category", "day"]"#))]
+        /// How to group the time
+        by: String,
+        #[structopt(long = "format", default_value = "text",
+                    raw(possible_values = r#"&["text", "csv"]"#))]
+        /// Output format
+        format: String,
+    },
+
+    #[structopt(name = "estimate")]
+    /// Estimate a certain ticket
+    Estimate {
+        /// Target identifier
+        id: String,
+        /// Estimate in story points, or in hours such as 1.5 or 1h30m
+        /// when the environment variable TICKETS_ESTIMATE_UNIT
+        /// or the option estimate.unit is hours
+        value: String,
+    },
+
     #[structopt(name = "move")]
     /// Move a certain ticket to another category, and rename if specified
     Move {
@@ -71,3 +287,296 @@ pub enum Action {
         id: String,
     },
 }
+
+/// Subcommands of `Action` taking identifiers of categories or tickets,
+/// to be completed with the existing ones. Keep it along the definition.
+pub const ID_SUBCOMMANDS: &[&str] = &[
+    "new", "show", "board", "watch", "edit", "comment", "attach",
+    "attachments", "detach", "start", "log-time", "estimate", "move", "remove",
+];
+
+#[derive(Debug, StructOpt)]
+pub struct ConfigScope {
+    #[structopt(long = "system")]
+    /// Use /etc/tickets/config.toml
+    pub system: bool,
+    #[structopt(long = "global")]
+    /// Use ~/.config/tickets/config.toml (default when writing)
+    pub global: bool,
+    #[structopt(long = "local")]
+    /// Use config.toml in the root ~/.tickets
+    pub local: bool,
+}
+
+#[derive(Debug, StructOpt)]
+pub enum Config {
+    #[structopt(name = "get")]
+    /// Print the value of an option
+    Get {
+        #[structopt(flatten)]
+        scope: ConfigScope,
+        /// Option key with its section, like core.editor
+        key: String,
+    },
+
+    #[structopt(name = "set")]
+    /// Set an option
+    Set {
+        #[structopt(flatten)]
+        scope: ConfigScope,
+        /// Option key with its section, like core.editor
+        key: String,
+        /// Option value
+        value: String,
+    },
+
+    #[structopt(name = "unset")]
+    /// Remove an option
+    Unset {
+        #[structopt(flatten)]
+        scope: ConfigScope,
+        /// Option key with its section, like core.editor
+        key: String,
+    },
+
+    #[structopt(name = "list")]
+    /// Print all options as key=value
+    List {
+        #[structopt(flatten)]
+        scope: ConfigScope,
+    },
+}
+
+#[derive(Debug, StructOpt)]
+pub enum Export {
+    #[structopt(name = "html")]
+    /// Generate a static site of all tickets, with a search
+    Html {
+        #[structopt(parse(from_os_str))]
+        /// Directory to write the site into
+        out_dir: PathBuf,
+    },
+
+    #[structopt(name = "csv")]
+    /// Write one row per ticket, with the metadata as extra columns
+    Csv {
+        /// Category identifier, all categories by default
+        id: Option<String>,
+        #[structopt(short = "o", long = "output", parse(from_os_str))]
+        /// File to write into, instead of the standard output
+        output: Option<PathBuf>,
+    },
+
+    #[structopt(name = "ics")]
+    /// Write an iCalendar of a todo per ticket with a due date,
+    /// for the calendars to subscribe to
+    Ics {
+        /// Category identifier, all categories by default
+        id: Option<String>,
+        #[structopt(short = "o", long = "output", parse(from_os_str))]
+        /// File to write into, instead of the standard output
+        output: Option<PathBuf>,
+    },
+
+    #[structopt(name = "org")]
+    /// Write a document of Org-mode, with a heading per category
+    /// and a TODO per ticket
+    Org {
+        /// Category identifier, all categories by default
+        id: Option<String>,
+        #[structopt(long = "tag")]
+        /// Only the tickets with the tag in the metadata tags
+        tag: Option<String>,
+        #[structopt(short = "o", long = "output", parse(from_os_str))]
+        /// File to write into, instead of the standard output
+        output: Option<PathBuf>,
+    },
+
+    #[structopt(name = "markdown")]
+    /// Write a document of Markdown, with a heading per category
+    /// and a checklist item per ticket
+    Markdown {
+        /// Category identifier, all categories by default
+        id: Option<String>,
+        #[structopt(long = "tag")]
+        /// Only the tickets with the tag in the metadata tags
+        tag: Option<String>,
+        #[structopt(short = "o", long = "output", parse(from_os_str))]
+        /// File to write into, instead of the standard output
+        output: Option<PathBuf>,
+    },
+
+    #[structopt(name = "taskwarrior")]
+    /// Write the tasks of Taskwarrior as JSON, for task import
+    ///
+    /// The category is the project, and the message and the comments
+    /// are the annotations.
+    Taskwarrior {
+        /// Category identifier, all categories by default
+        id: Option<String>,
+        #[structopt(short = "o", long = "output", parse(from_os_str))]
+        /// File to write into, instead of the standard output
+        output: Option<PathBuf>,
+    },
+
+    #[structopt(name = "todotxt")]
+    /// Write one line of todo.txt per ticket, with the category
+    /// as its +project
+    Todotxt {
+        /// Category identifier, all categories by default
+        id: Option<String>,
+        #[structopt(short = "o", long = "output", parse(from_os_str))]
+        /// File to write into, instead of the standard output
+        output: Option<PathBuf>,
+    },
+}
+
+#[derive(Debug, StructOpt)]
+pub struct ImportOptions {
+    #[structopt(long = "on-conflict", default_value = "skip",
+                raw(possible_values = r#"&["skip", "overwrite", "rename"]"#))]
+    /// What to do with a ticket which already exists
+    ///
+    /// With rename, the ticket is imported with a suffix like ID-1-2.
+    pub on_conflict: String,
+    #[structopt(long = "dry-run")]
+    /// Only report what would be imported
+    pub dry_run: bool,
+}
+
+#[derive(Debug, StructOpt)]
+pub enum Import {
+    #[structopt(name = "csv")]
+    /// Create tickets from the rows of a CSV file with a header
+    ///
+    /// The columns category, id, title and message make the tickets,
+    /// and the others become their metadata.
+    Csv {
+        #[structopt(parse(from_os_str))]
+        /// CSV file to read
+        file: PathBuf,
+        #[structopt(long = "map", raw(number_of_values = "1"))]
+        /// Read a column as another field, like Summary=title,
+        /// or ignore it with Notes=-
+        map: Vec<String>,
+        #[structopt(long = "category")]
+        /// Category of the rows without one
+        category: Option<String>,
+        #[structopt(flatten)]
+        options: ImportOptions,
+    },
+
+    #[structopt(name = "todotxt")]
+    /// Create tickets from the lines of a todo.txt file
+    ///
+    /// The first +project is the category, and the priority, the dates,
+    /// the @contexts as tags and the other key:value pairs become
+    /// the metadata. An id:value names the ticket.
+    Todotxt {
+        #[structopt(parse(from_os_str))]
+        /// todo.txt file to read
+        file: PathBuf,
+        #[structopt(long = "category", default_value = "inbox")]
+        /// Category of the lines without a +project
+        category: String,
+        #[structopt(flatten)]
+        options: ImportOptions,
+    },
+
+    #[structopt(name = "taskwarrior")]
+    /// Create tickets from the JSON of task export
+    ///
+    /// The project is the category, the description the title,
+    /// and the annotations the comments, except the one starting with
+    /// [message], the message of an exported ticket. The status, the priority,
+    /// the dates, the tags and the UUID become the metadata, and the
+    /// first eight characters of the UUID name the ticket.
+    Taskwarrior {
+        #[structopt(parse(from_os_str))]
+        /// JSON file to read
+        file: PathBuf,
+        #[structopt(long = "category", default_value = "inbox")]
+        /// Category of the tasks without a project
+        category: String,
+        #[structopt(flatten)]
+        options: ImportOptions,
+    },
+
+    #[structopt(name = "github")]
+    /// Create tickets from the issues exported by the GitHub API
+    ///
+    /// The file is the JSON array from the REST API or from
+    /// gh issue list --json number,state,title,body,labels,assignees,
+    /// author,comments,url,createdAt. Each issue is named by its number,
+    /// with the labels, the assignees and the author as its metadata.
+    Github {
+        #[structopt(parse(from_os_str))]
+        /// JSON file to read
+        file: PathBuf,
+        #[structopt(long = "open-category", default_value = "open")]
+        /// Category of the open issues
+        open_category: String,
+        #[structopt(long = "closed-category", default_value = "closed")]
+        /// Category of the closed issues
+        closed_category: String,
+        #[structopt(flatten)]
+        options: ImportOptions,
+    },
+
+    #[structopt(name = "mail")]
+    /// Create tickets from the messages of a Maildir or an mbox file
+    ///
+    /// The subject is the title, the plain text the message, and the
+    /// attachments the files of the ticket. The sender, the date and
+    /// the Message-ID become the metadata, and the messages already
+    /// imported are skipped by their Message-ID.
+    Mail {
+        #[structopt(parse(from_os_str))]
+        /// Maildir directory or mbox file to read
+        path: PathBuf,
+        #[structopt(long = "category", default_value = "inbox")]
+        /// Category of the messages
+        category: String,
+        #[structopt(flatten)]
+        options: ImportOptions,
+    },
+}
+
+#[derive(Debug, StructOpt)]
+pub enum Remote {
+    #[structopt(name = "add")]
+    /// Add a remote, kept in the option remote.<name> of the store
+    ///
+    /// The token is read from the environment variable
+    /// TICKETS_REMOTE_TOKEN, or the option remote.<name>.token.
+    Add {
+        /// Name of the remote
+        name: String,
+        /// Base URL of the tracker, like https://example.atlassian.net
+        url: String,
+        #[structopt(long = "kind", default_value = "jira",
+                    raw(possible_values = r#"&["jira"]"#))]
+        /// Kind of the tracker
+        kind: String,
+        #[structopt(long = "project")]
+        /// Key of the project to sync with
+        project: String,
+        #[structopt(long = "user")]
+        /// User to authenticate as, with the token as the password
+        user: Option<String>,
+        #[structopt(long = "category", raw(number_of_values = "1"))]
+        /// Category whose new tickets are created on the remote
+        categories: Vec<String>,
+    },
+
+    #[structopt(name = "list")]
+    /// Print the remotes with their URLs
+    List,
+
+    #[structopt(name = "remove")]
+    /// Remove a remote, leaving the tickets as they are
+    Remove {
+        /// Name of the remote
+        name: String,
+    },
+}
@@ -0,0 +1,103 @@
+use std::env;
+use std::path::PathBuf;
+use std::process::Command;
+
+use super::alias;
+use super::config;
+use super::hooks;
+
+const PREFIX: &str = "tickets-";
+
+/// The executable of the plugin on PATH, like `tickets-report`
+/// for the subcommand `report`.
+pub fn find(name: &str) -> Option<PathBuf> {
+    let paths = env::var_os("PATH")?;
+    env::split_paths(&paths)
+        .map(|dir| dir.join(format!("{}{}", PREFIX, name)))
+        .find(|path| hooks::is_executable(path))
+}
+
+/// The names of all plugins on PATH.
+pub fn discover() -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    let paths = match env::var_os("PATH") {
+        Some(paths) => paths,
+        None => return names,
+    };
+    for dir in env::split_paths(&paths) {
+        let entries = match dir.read_dir() {
+            Ok(entries) => entries,
+            Err(_) => continue,
+        };
+        for entry in entries.filter_map(|entry| entry.ok()) {
+            let file_name = entry.file_name().to_string_lossy().into_owned();
+            if let Some(name) = file_name.strip_prefix(PREFIX) {
+                if !name.is_empty() && hooks::is_executable(&entry.path()) {
+                    names.push(name.to_string());
+                }
+            }
+        }
+    }
+    names.sort();
+    names.dedup();
+    names
+}
+
+/// The list of the plugins to append to the help, if any.
+pub fn help() -> String {
+    let names: Vec<String> = discover()
+        .into_iter()
+        .filter(|name| !alias::is_builtin(name))
+        .map(|name| format!("    {}", name))
+        .collect();
+    if names.is_empty() {
+        return String::new();
+    }
+    format!("PLUGINS:\n{}", names.join("\n"))
+}
+
+/// The plugin for the subcommand which is not built in, with the
+/// arguments after it.
+pub fn external(args: &[String]) -> Option<(PathBuf, Vec<String>)> {
+    let index = alias::index_subcommand(args)?;
+    let name = &args[index];
+    if alias::is_builtin(name) {
+        return None;
+    }
+    let path = find(name)?;
+    Some((path, args[index + 1..].to_vec()))
+}
+
+/// Run the plugin with the arguments, and give its exit status.
+pub fn run(path: &PathBuf, args: &[String]) -> i32 {
+    let status = Command::new(path)
+        .args(args)
+        .env("TICKETS_ROOT", super::get_path_root())
+        .env("TICKETS_CONFIG_SYSTEM", config::Scope::System.path())
+        .env("TICKETS_CONFIG_GLOBAL", config::Scope::Global.path())
+        .env("TICKETS_CONFIG_LOCAL", config::Scope::Local.path())
+        .status();
+    match status {
+        Ok(status) => status.code().unwrap_or(exitcode::SOFTWARE),
+        Err(error) => {
+            eprintln!("tickets :: {}\n\nERROR: {}", path.display(), error);
+            exitcode::OSERR
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    fn strings(words: &[&str]) -> Vec<String> {
+        words.iter().map(|word| word.to_string()).collect()
+    }
+
+    #[test]
+    fn no_plugin_for_builtins() {
+        assert_eq!(super::external(&strings(&["tickets", "show", "open/"])),
+                   None);
+        assert_eq!(super::external(&strings(&["tickets", "--color", "never"])),
+                   None);
+        assert_eq!(super::find("surely-not-a-plugin"), None);
+    }
+}
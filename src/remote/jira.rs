@@ -0,0 +1,360 @@
+use std::time::Duration;
+
+use serde_json::{json, Value};
+
+use super::{category_of, Adapter, Issue};
+
+/// The issues of a project on JIRA, by its REST API version 2.
+#[derive(Debug)]
+pub struct Jira {
+    pub url: String,
+    pub project: String,
+    /// With a user, the token is an API token of JIRA Cloud;
+    /// without, a personal access token of JIRA Server.
+    pub user: Option<String>,
+    pub token: Option<String>,
+    /// The type of the issues created, like Task.
+    pub issue_type: String,
+}
+
+const FIELDS: &str = "summary,description,status,updated";
+const PAGE: usize = 50;
+
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8] =
+        b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut encoded = String::new();
+    for chunk in bytes.chunks(3) {
+        let triple = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |acc, (index, byte)| {
+                acc | u32::from(*byte) << (16 - 8 * index)
+            });
+        for index in 0..4 {
+            if index <= chunk.len() {
+                let sextet = (triple >> (18 - 6 * index)) & 0x3f;
+                encoded.push(ALPHABET[sextet as usize] as char);
+            }
+            else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+fn issue_of(value: &Value) -> Result<Issue, String> {
+    let fields = &value["fields"];
+    let string = |field: &Value| field.as_str().unwrap_or("").to_string();
+    let key = string(&value["key"]);
+    if key.is_empty() {
+        return Err(String::from("The remote gave an issue without a key."));
+    }
+    Ok(Issue {
+        key,
+        title: string(&fields["summary"]),
+        description: string(&fields["description"]),
+        status: string(&fields["status"]["name"]),
+        updated: string(&fields["updated"]),
+    })
+}
+
+impl Jira {
+    fn request(&self, method: &str, path: &str) -> ureq::Request {
+        let request = ureq::AgentBuilder::new()
+            .timeout(Duration::from_secs(30))
+            .build()
+            .request(method, &format!("{}/rest/api/2/{}", self.url, path))
+            .set("Accept", "application/json");
+        match (&self.user, &self.token) {
+            (Some(user), Some(token)) => {
+                let credentials = format!("{}:{}", user, token);
+                request.set("Authorization",
+                            &format!("Basic {}", base64(credentials.as_bytes())))
+            },
+            (None, Some(token)) => {
+                request.set("Authorization", &format!("Bearer {}", token))
+            },
+            _ => request,
+        }
+    }
+
+    /// Send the request with the JSON body, if any, and give the JSON
+    /// of the response, which is null when empty.
+    fn send(&self, request: ureq::Request, body: Option<Value>)
+            -> Result<Value, String> {
+        let result = match body {
+            Some(body) => request
+                .set("Content-Type", "application/json")
+                .send_string(&body.to_string()),
+            None => request.call(),
+        };
+        let response = match result {
+            Ok(response) => response,
+            Err(ureq::Error::Status(status, response)) => {
+                return Err(format!("The remote answered {}: {}",
+                                   status,
+                                   response.into_string().unwrap_or_default()));
+            },
+            Err(error) => return Err(error.to_string()),
+        };
+        let text = response.into_string().map_err(|error| error.to_string())?;
+        if text.trim().is_empty() {
+            return Ok(Value::Null);
+        }
+        serde_json::from_str(&text)
+            .map_err(|error| format!("Invalid JSON from the remote: {}", error))
+    }
+}
+
+impl Adapter for Jira {
+    fn list(&self) -> Result<Vec<Issue>, String> {
+        let jql = format!("project = \"{}\" ORDER BY key", self.project);
+        let mut issues = Vec::new();
+        loop {
+            let request = self.request("GET", "search")
+                .query("jql", &jql)
+                .query("fields", FIELDS)
+                .query("startAt", &issues.len().to_string())
+                .query("maxResults", &PAGE.to_string());
+            let page = self.send(request, None)?;
+            let values = page["issues"].as_array().cloned().unwrap_or_default();
+            for value in &values {
+                issues.push(issue_of(value)?);
+            }
+            let total = page["total"].as_u64().unwrap_or(0) as usize;
+            if values.is_empty() || issues.len() >= total {
+                return Ok(issues);
+            }
+        }
+    }
+
+    fn get(&self, key: &str) -> Result<Issue, String> {
+        let request = self.request("GET", &format!("issue/{}", key))
+            .query("fields", FIELDS);
+        issue_of(&self.send(request, None)?)
+    }
+
+    fn create(&self, title: &str, description: &str) -> Result<String, String> {
+        let body = json!({
+            "fields": {
+                "project": { "key": self.project },
+                "issuetype": { "name": self.issue_type },
+                "summary": title,
+                "description": description,
+            },
+        });
+        let created = self.send(self.request("POST", "issue"), Some(body))?;
+        match created["key"].as_str() {
+            Some(key) => Ok(key.to_string()),
+            None => Err(String::from("The remote gave no key of the issue.")),
+        }
+    }
+
+    fn update(&self, key: &str, title: &str, description: &str)
+              -> Result<(), String> {
+        let body = json!({
+            "fields": {
+                "summary": title,
+                "description": description,
+            },
+        });
+        self.send(self.request("PUT", &format!("issue/{}", key)), Some(body))
+            .map(|_| ())
+    }
+
+    fn transition(&self, key: &str, category: &str) -> Result<(), String> {
+        let path = format!("issue/{}/transitions", key);
+        let transitions = self.send(self.request("GET", &path), None)?;
+        let transition = transitions["transitions"]
+            .as_array()
+            .and_then(|transitions_| transitions_.iter().find(|transition| {
+                category_of(transition["to"]["name"].as_str().unwrap_or(""))
+                    == category
+            }))
+            .ok_or_else(|| format!("No transition of {} to {}.", key, category))?;
+        let body = json!({ "transition": { "id": transition["id"] } });
+        self.send(self.request("POST", &path), Some(body)).map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+
+    use serde_json::{json, Value};
+
+    use super::super::Adapter;
+
+    /// A JIRA of a few issues, answering from a local port.
+    fn mock_jira(issues: Value) -> (String, Arc<Mutex<Value>>) {
+        let server = tiny_http::Server::http("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", server.server_addr());
+        let state = Arc::new(Mutex::new(issues));
+        let state_server = Arc::clone(&state);
+        thread::spawn(move || {
+            for mut request in server.incoming_requests() {
+                let mut body = String::new();
+                request.as_reader().read_to_string(&mut body).unwrap();
+                let path = request.url().split('?').next().unwrap().to_string();
+                let mut issues = state_server.lock().unwrap();
+                let count = issues.as_array().unwrap().len();
+                let (status, answer) = match (request.method().as_str(),
+                                              &path[..]) {
+                    ("GET", "/rest/api/2/search") => (200, json!({
+                        "total": count, "issues": issues.clone(),
+                    })),
+                    ("POST", "/rest/api/2/issue") => {
+                        let body: Value = serde_json::from_str(&body).unwrap();
+                        let key = format!("PROJ-{}", count + 1);
+                        let mut fields = body["fields"].clone();
+                        fields["status"] = json!({ "name": "To Do" });
+                        fields["updated"] = json!("1");
+                        issues.as_array_mut().unwrap()
+                            .push(json!({ "key": key, "fields": fields }));
+                        (201, json!({ "key": key }))
+                    },
+                    (method, path_) => {
+                        let key = path_.split('/').nth(5).unwrap_or("");
+                        let issue = issues.as_array_mut().unwrap()
+                            .iter_mut()
+                            .find(|issue| issue["key"] == key);
+                        match (method, issue) {
+                            ("GET", Some(_)) if path_.ends_with("/transitions") => {
+                                (200, json!({ "transitions": [
+                                    { "id": "31", "to": { "name": "Done" } },
+                                ]}))
+                            },
+                            ("POST", Some(issue)) => {
+                                issue["fields"]["status"]["name"] = json!("Done");
+                                (204, Value::Null)
+                            },
+                            ("GET", Some(issue)) => (200, issue.clone()),
+                            ("PUT", Some(issue)) => {
+                                let body: Value = serde_json::from_str(&body)
+                                    .unwrap();
+                                let fields = &mut issue["fields"];
+                                fields["summary"] = body["fields"]["summary"].clone();
+                                fields["description"] =
+                                    body["fields"]["description"].clone();
+                                let updated = fields["updated"].as_str()
+                                    .unwrap().parse::<u32>().unwrap() + 1;
+                                fields["updated"] = json!(updated.to_string());
+                                (204, Value::Null)
+                            },
+                            _ => (404, json!({ "errorMessages": ["Not found"] })),
+                        }
+                    },
+                };
+                let text = if answer.is_null() {
+                    String::new()
+                } else {
+                    answer.to_string()
+                };
+                let response = tiny_http::Response::from_string(text)
+                    .with_status_code(status);
+                request.respond(response).unwrap();
+            }
+        });
+        (url, state)
+    }
+
+    fn jira(url: String) -> super::Jira {
+        super::Jira {
+            url,
+            project: String::from("PROJ"),
+            user: Some(String::from("me@example.com")),
+            token: Some(String::from("secret")),
+            issue_type: String::from("Task"),
+        }
+    }
+
+    #[test]
+    fn encode_credentials() {
+        assert_eq!(super::base64(b"user:pass"), "dXNlcjpwYXNz");
+        assert_eq!(super::base64(b"ab"), "YWI=");
+        assert_eq!(super::base64(b"a"), "YQ==");
+    }
+
+    #[test]
+    fn call_the_rest_api() {
+        let (url, state) = mock_jira(json!([
+            { "key": "PROJ-1", "fields": {
+                "summary": "First", "description": null,
+                "status": { "name": "To Do" }, "updated": "1" } },
+        ]));
+        let jira = jira(url);
+        let issues = jira.list().unwrap();
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].status, "To Do");
+        assert_eq!(issues[0].description, "");
+
+        jira.update("PROJ-1", "Renamed", "Body").unwrap();
+        assert_eq!(jira.get("PROJ-1").unwrap().updated, "2");
+        jira.transition("PROJ-1", "done").unwrap();
+        assert_eq!(jira.get("PROJ-1").unwrap().status, "Done");
+        assert!(jira.transition("PROJ-1", "never").is_err());
+        assert_eq!(jira.create("Second", "").unwrap(), "PROJ-2");
+        assert_eq!(state.lock().unwrap().as_array().unwrap().len(), 2);
+        assert!(jira.get("PROJ-9").unwrap_err().starts_with(
+            "The remote answered 404"));
+    }
+
+    #[test]
+    fn sync_with_the_remote() {
+        super::super::super::purge().unwrap_or(());
+        super::super::super::initialize_root().unwrap_or(());
+        let (url, state) = mock_jira(json!([
+            { "key": "PROJ-1", "fields": {
+                "summary": "First", "description": "Body",
+                "status": { "name": "To Do" }, "updated": "1" } },
+        ]));
+        let remote = super::super::Remote {
+            name: String::from("work"),
+            kind: String::from("jira"),
+            url: url.clone(),
+            project: String::from("PROJ"),
+            user: None,
+            categories: vec![String::from("to-do")],
+        };
+        let jira = jira(url);
+        let sync = || super::super::sync(&remote, &jira, None, false).unwrap();
+
+        assert_eq!(sync(), vec!["PULLED PROJ-1 => to-do/PROJ-1"]);
+        assert!(sync().is_empty());
+
+        // a local change is pushed, and a new ticket created
+        let path_root = super::super::super::get_path_root();
+        std::fs::write(path_root.join("to-do/PROJ-1"), "First\n\nEdited")
+            .unwrap();
+        std::fs::write(path_root.join("to-do/ID-1"), "Local one").unwrap();
+        assert_eq!(sync(), vec!["PUSHED to-do/PROJ-1 => PROJ-1",
+                                "PUSHED to-do/ID-1 => PROJ-2"]);
+        assert_eq!(state.lock().unwrap()[0]["fields"]["description"], "Edited");
+        assert!(sync().is_empty());
+
+        // a remote change is pulled, moving the ticket
+        state.lock().unwrap()[0]["fields"]["status"]["name"] = json!("Done");
+        state.lock().unwrap()[0]["fields"]["updated"] = json!("9");
+        assert_eq!(sync(), vec!["PULLED PROJ-1 => done/PROJ-1"]);
+        assert!(path_root.join("done/PROJ-1").exists());
+        assert!(!path_root.join("to-do/PROJ-1").exists());
+
+        // both changed
+        state.lock().unwrap()[0]["fields"]["summary"] = json!("Remote");
+        state.lock().unwrap()[0]["fields"]["updated"] = json!("10");
+        std::fs::write(path_root.join("done/PROJ-1"), "Local").unwrap();
+        assert_eq!(sync(), vec!["CONFLICT done/PROJ-1 <=> PROJ-1, \
+                                 changed on both sides"]);
+        assert_eq!(super::super::sync(&remote,
+                                      &jira,
+                                      Some(super::super::Prefer::Remote),
+                                      false).unwrap(),
+                   vec!["PULLED PROJ-1 => done/PROJ-1"]);
+        assert_eq!(std::fs::read_to_string(path_root.join("done/PROJ-1"))
+                   .unwrap(),
+                   "Remote\n\nEdited");
+    }
+}
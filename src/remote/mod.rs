@@ -0,0 +1,458 @@
+use std::collections::BTreeMap;
+use std::io;
+use std::str::FromStr;
+
+use super::config::{self, Scope};
+use super::ticket::Ticket;
+
+pub mod jira;
+
+/// The metadata linking a ticket to its issue on a remote.
+const META_REMOTE: &str = "remote";
+const META_KEY: &str = "remote_key";
+/// When the issue was updated on the remote at the last sync.
+const META_UPDATED: &str = "remote_updated";
+/// The digest of the ticket at the last sync, to tell a local change.
+const META_DIGEST: &str = "remote_digest";
+
+/// An issue on a remote tracker, as much as a ticket has.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Issue {
+    pub key: String,
+    pub title: String,
+    pub description: String,
+    /// The status, which is the category of the ticket.
+    pub status: String,
+    pub updated: String,
+}
+
+/// The REST API of an issue tracker.
+pub trait Adapter {
+    /// All issues of the project.
+    fn list(&self) -> Result<Vec<Issue>, String>;
+    fn get(&self, key: &str) -> Result<Issue, String>;
+    /// Create an issue, giving its key.
+    fn create(&self, title: &str, description: &str) -> Result<String, String>;
+    fn update(&self, key: &str, title: &str, description: &str)
+              -> Result<(), String>;
+    /// Bring the issue into the status of the category.
+    fn transition(&self, key: &str, category: &str) -> Result<(), String>;
+}
+
+/// The category of a status, like `in-progress` for In Progress.
+pub fn category_of(status: &str) -> String {
+    let mut category = String::new();
+    for ch in status.trim().chars() {
+        if ch.is_alphanumeric() {
+            category.extend(ch.to_lowercase());
+        }
+        else if !category.ends_with('-') {
+            category.push('-');
+        }
+    }
+    category.trim_end_matches('-').to_string()
+}
+
+/// FNV-1a, which stays the same across builds unlike the hasher of std.
+fn digest(category: &str, title: &str, description: &str) -> String {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in format!("{}\n{}\n{}", category, title.trim(), description.trim())
+        .bytes()
+    {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    format!("{:016x}", hash)
+}
+
+/// A remote from the options `remote.<name>.*`, which are added
+/// to the local scope as each store has its own.
+#[derive(Debug, PartialEq)]
+pub struct Remote {
+    pub name: String,
+    pub kind: String,
+    pub url: String,
+    pub project: String,
+    pub user: Option<String>,
+    /// The categories whose new tickets are created on the remote.
+    pub categories: Vec<String>,
+}
+
+impl Remote {
+    fn options(name: &str) -> io::Result<BTreeMap<String, String>> {
+        let prefix = format!("remote.{}.", name);
+        Ok(config::list()?
+           .into_iter()
+           .filter_map(|(key, value)| {
+               key.strip_prefix(&prefix).map(|key_| (key_.to_string(), value))
+           })
+           .collect())
+    }
+
+    pub fn load(name: &str) -> io::Result<Remote> {
+        let mut options = Remote::options(name)?;
+        let not_found = || io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("The remote {} does not exist.", name));
+        let url = options.remove("url").ok_or_else(not_found)?;
+        let project = options.remove("project").ok_or_else(not_found)?;
+        Ok(Remote {
+            name: name.to_string(),
+            kind: options.remove("kind").unwrap_or_else(|| String::from("jira")),
+            url,
+            project,
+            user: options.remove("user"),
+            categories: options
+                .remove("categories")
+                .map(|categories| categories
+                     .split_whitespace()
+                     .map(|category| category.trim_end_matches('/').to_string())
+                     .collect())
+                .unwrap_or_default(),
+        })
+    }
+
+    /// The names of the remotes.
+    pub fn names() -> io::Result<Vec<String>> {
+        let names: Vec<String> = config::list()?
+            .keys()
+            .filter_map(|key| {
+                let rest = key.strip_prefix("remote.")?;
+                let name = rest.strip_suffix(".url")?;
+                Some(name.to_string())
+            })
+            .collect();
+        Ok(names)
+    }
+
+    pub fn add(&self) -> io::Result<()> {
+        if self.name.is_empty() || self.name.contains('.') {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Invalid remote name {}", self.name)));
+        }
+        if Remote::load(&self.name).is_ok() {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("The remote {} already exists.", self.name)));
+        }
+        let key = |option: &str| format!("remote.{}.{}", self.name, option);
+        config::set(Scope::Local, &key("kind"), &self.kind)?;
+        config::set(Scope::Local, &key("url"), &self.url)?;
+        config::set(Scope::Local, &key("project"), &self.project)?;
+        if let Some(user) = &self.user {
+            config::set(Scope::Local, &key("user"), user)?;
+        }
+        if !self.categories.is_empty() {
+            config::set(Scope::Local,
+                        &key("categories"),
+                        &self.categories.join(" "))?;
+        }
+        Ok(())
+    }
+
+    pub fn remove(name: &str) -> io::Result<()> {
+        Remote::load(name)?;
+        for key in config::list_scope(Scope::Local)?.keys() {
+            if key.starts_with(&format!("remote.{}.", name)) {
+                config::unset(Scope::Local, key)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// The token from the environment variable TICKETS_REMOTE_TOKEN,
+    /// or the option `remote.<name>.token` not to be shared.
+    fn token(&self) -> Option<String> {
+        std::env::var("TICKETS_REMOTE_TOKEN")
+            .ok()
+            .filter(|token| !token.is_empty())
+            .or_else(|| config::get(&format!("remote.{}.token", self.name)))
+    }
+
+    pub fn adapter(&self) -> Result<Box<dyn Adapter>, String> {
+        match &self.kind[..] {
+            "jira" => Ok(Box::new(jira::Jira {
+                url: self.url.trim_end_matches('/').to_string(),
+                project: self.project.clone(),
+                user: self.user.clone(),
+                token: self.token(),
+                issue_type: config::get(&format!("remote.{}.issue_type",
+                                                 self.name))
+                    .unwrap_or_else(|| String::from("Task")),
+            })),
+            kind => Err(format!("Unknown kind of remote {}", kind)),
+        }
+    }
+}
+
+/// Which side wins when a ticket and its issue have both changed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Prefer {
+    Local,
+    Remote,
+}
+
+impl FromStr for Prefer {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Prefer, String> {
+        match s {
+            "local" => Ok(Prefer::Local),
+            "remote" => Ok(Prefer::Remote),
+            _ => Err(format!("Invalid side {}", s)),
+        }
+    }
+}
+
+fn io_error(error: String) -> io::Error {
+    io::Error::other(error)
+}
+
+/// A ticket linked to an issue of the remote.
+struct Linked {
+    ticket: Ticket,
+    meta: BTreeMap<String, String>,
+}
+
+impl Linked {
+    fn title(&self) -> &str {
+        self.ticket.title.as_ref().map(|title| &title[..]).unwrap_or("")
+    }
+
+    fn description(&self) -> &str {
+        self.ticket.message.as_ref().map(|message| &message[..]).unwrap_or("")
+    }
+
+    fn digest(&self) -> String {
+        digest(&self.ticket.category, self.title(), self.description())
+    }
+
+    fn id(&self) -> String {
+        format!("{}/{}",
+                self.ticket.category,
+                self.ticket.id.clone().unwrap_or_default())
+    }
+}
+
+/// Record the state of the ticket as synced with the issue.
+fn link(ticket: &Ticket, remote: &str, issue: &Issue) -> io::Result<()> {
+    ticket.set_meta(META_REMOTE, Some(remote))?;
+    ticket.set_meta(META_KEY, Some(&issue.key))?;
+    ticket.set_meta(META_UPDATED, Some(&issue.updated))?;
+    ticket.set_meta(META_DIGEST, Some(&digest(&category_of(&issue.status),
+                                              &issue.title,
+                                              &issue.description)))
+}
+
+fn content(issue: &Issue) -> String {
+    if issue.description.trim().is_empty() {
+        issue.title.clone()
+    }
+    else {
+        format!("{}\n\n{}", issue.title, issue.description.trim())
+    }
+}
+
+/// Write the issue into the ticket, moving it into the category
+/// of the status, and give the new one.
+fn pull(linked: Option<&Linked>, remote: &str, issue: &Issue)
+        -> io::Result<Ticket> {
+    let category = category_of(&issue.status);
+    let category_ticket = Ticket::from(format!("{}/", category), None)
+        .map_err(io_error)?;
+    if !category_ticket.path.exists() {
+        category_ticket.new()?;
+    }
+    let ticket = match linked {
+        Some(linked) => {
+            if linked.ticket.category != category {
+                linked.ticket.move_(&category_ticket)?;
+            }
+            let id = format!("{}/{}",
+                             category,
+                             linked.ticket.id.clone().unwrap_or_default());
+            let ticket = Ticket::from(id, Some(content(issue)))
+                .map_err(io_error)?;
+            ticket.write()?;
+            ticket
+        },
+        None => {
+            let ticket = Ticket::from(format!("{}/{}", category, issue.key),
+                                      Some(content(issue)))
+                .map_err(io_error)?;
+            ticket.new()?;
+            ticket
+        },
+    };
+    link(&ticket, remote, issue)?;
+    Ok(ticket)
+}
+
+/// Write the ticket into the issue, bringing it into the status
+/// of the category.
+fn push(adapter: &dyn Adapter, linked: &Linked, remote: &str, key: &str)
+        -> Result<(), String> {
+    adapter.update(key, linked.title(), linked.description())?;
+    let issue = adapter.get(key)?;
+    if category_of(&issue.status) != linked.ticket.category {
+        adapter.transition(key, &linked.ticket.category)?;
+    }
+    let issue = adapter.get(key)?;
+    link(&linked.ticket, remote, &issue).map_err(|error| error.to_string())
+}
+
+/// The tickets of the store, with their metadata.
+fn collect_tickets() -> io::Result<Vec<Linked>> {
+    let mut tickets = Vec::new();
+    for ticket in super::collect_all()? {
+        let meta = ticket.meta()?;
+        tickets.push(Linked { ticket, meta });
+    }
+    Ok(tickets)
+}
+
+/// Pull the issues changed on the remote, push the tickets changed
+/// locally, and report the conflicts of the ones changed on both sides
+/// unless a side is preferred. Nothing is written on a dry run.
+pub fn sync(remote: &Remote,
+            adapter: &dyn Adapter,
+            prefer: Option<Prefer>,
+            dry_run: bool) -> io::Result<Vec<String>> {
+    let mut report = Vec::new();
+    let issues = adapter.list().map_err(io_error)?;
+    let tickets = collect_tickets()?;
+    let mut linked: BTreeMap<String, &Linked> = BTreeMap::new();
+    for ticket in &tickets {
+        if ticket.meta.get(META_REMOTE) == Some(&remote.name) {
+            if let Some(key) = ticket.meta.get(META_KEY) {
+                linked.insert(key.clone(), ticket);
+            }
+        }
+    }
+
+    for issue in &issues {
+        let ticket = match linked.get(&issue.key) {
+            Some(ticket) => *ticket,
+            None => {
+                let id = format!("{}/{}", category_of(&issue.status), issue.key);
+                let exists = Ticket::from(id.clone(), None)
+                    .map(|ticket| ticket.path.exists())
+                    .map_err(io_error)?;
+                if exists {
+                    report.push(format!("CONFLICT {} <=> {}, which is not \
+                                         linked to the issue",
+                                        id, issue.key));
+                    continue;
+                }
+                if !dry_run {
+                    pull(None, &remote.name, issue)?;
+                }
+                report.push(format!("PULLED {} => {}", issue.key, id));
+                continue;
+            },
+        };
+
+        let changed_remote = ticket.meta.get(META_UPDATED) != Some(&issue.updated);
+        let changed_local = ticket.meta.get(META_DIGEST) != Some(&ticket.digest());
+        let direction = match (changed_local, changed_remote, prefer) {
+            (false, false, _) => continue,
+            (true, true, None) => {
+                report.push(format!("CONFLICT {} <=> {}, changed on both sides",
+                                    ticket.id(), issue.key));
+                continue;
+            },
+            (false, true, _) | (true, true, Some(Prefer::Remote)) => {
+                Prefer::Remote
+            },
+            (true, false, _) | (true, true, Some(Prefer::Local)) => {
+                Prefer::Local
+            },
+        };
+        match direction {
+            Prefer::Remote => {
+                let id = format!("{}/{}",
+                                 category_of(&issue.status),
+                                 ticket.ticket.id.clone().unwrap_or_default());
+                if !dry_run {
+                    pull(Some(ticket), &remote.name, issue)?;
+                }
+                report.push(format!("PULLED {} => {}", issue.key, id));
+            },
+            Prefer::Local => {
+                let result = if dry_run {
+                    Ok(())
+                } else {
+                    push(adapter, ticket, &remote.name, &issue.key)
+                };
+                report.push(match result {
+                    Ok(()) => format!("PUSHED {} => {}", ticket.id(), issue.key),
+                    Err(error) => format!("FAILED {} => {}: {}",
+                                          ticket.id(), issue.key, error),
+                });
+            },
+        }
+    }
+
+    // the new tickets of the categories shared with the remote
+    for ticket in &tickets {
+        if ticket.meta.contains_key(META_KEY)
+            || !remote.categories.contains(&ticket.ticket.category)
+        {
+            continue;
+        }
+        if dry_run {
+            report.push(format!("PUSHED {} => NEW", ticket.id()));
+            continue;
+        }
+        let result = adapter
+            .create(ticket.title(), ticket.description())
+            .and_then(|key| {
+                push(adapter, ticket, &remote.name, &key).map(|()| key)
+            });
+        report.push(match result {
+            Ok(key) => format!("PUSHED {} => {}", ticket.id(), key),
+            Err(error) => format!("FAILED {}: {}", ticket.id(), error),
+        });
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn categories_of_statuses() {
+        assert_eq!(super::category_of("To Do"), "to-do");
+        assert_eq!(super::category_of("In Progress"), "in-progress");
+        assert_eq!(super::category_of(" Done! "), "done");
+    }
+
+    #[test]
+    fn digests() {
+        assert_eq!(super::digest("open", "Title", "Body\n"),
+                   super::digest("open", "Title", "Body"));
+        assert_ne!(super::digest("open", "Title", "Body"),
+                   super::digest("done", "Title", "Body"));
+    }
+
+    #[test]
+    fn add_and_remove_remotes() {
+        super::super::purge().unwrap_or(());
+        super::super::initialize_root().unwrap_or(());
+        let remote = super::Remote {
+            name: String::from("work"),
+            kind: String::from("jira"),
+            url: String::from("https://jira.example.com"),
+            project: String::from("PROJ"),
+            user: None,
+            categories: vec![String::from("open")],
+        };
+        remote.add().unwrap();
+        assert!(remote.add().is_err());
+        assert_eq!(super::Remote::names().unwrap(), vec!["work"]);
+        assert_eq!(super::Remote::load("work").unwrap(), remote);
+        super::Remote::remove("work").unwrap();
+        assert!(super::Remote::load("work").is_err());
+        assert!(super::Remote::remove("work").is_err());
+    }
+}
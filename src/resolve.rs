@@ -0,0 +1,288 @@
+use std::io;
+
+use super::ticket::Ticket;
+
+/// How many suggestions to list at most.
+const MAX_SUGGESTIONS: usize = 5;
+
+/// The edit distance between two strings, in characters.
+fn distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ch_a) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, ch_b) in b.iter().enumerate() {
+            let substitution = if ch_a == *ch_b { diagonal } else { diagonal + 1 };
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(row[j + 1] + 1);
+        }
+    }
+    row[b.len()]
+}
+
+/// Whether the characters of the query appear in order in the text.
+fn is_subsequence(query: &str, text: &str) -> bool {
+    let mut chars = text.chars();
+    query.chars().all(|ch| chars.any(|ch_text| ch_text == ch))
+}
+
+/// The candidates looking like the query, the most similar first.
+fn fuzzy<'a>(query: &str, candidates: &[(&str, &'a str)]) -> Vec<&'a str> {
+    let query = query.to_lowercase();
+    let threshold = (query.chars().count() / 3).max(1);
+    let mut scored: Vec<(usize, &'a str)> = candidates
+        .iter()
+        .filter_map(|(key, id)| {
+            let key = key.to_lowercase();
+            let score = distance(&query, &key);
+            if score <= threshold
+                || (query.len() > 1 && is_subsequence(&query, &key)) {
+                Some((score, *id))
+            }
+            else {
+                None
+            }
+        })
+        .collect();
+    scored.sort();
+    scored.dedup_by_key(|(_, id)| *id);
+    scored
+        .into_iter()
+        .take(MAX_SUGGESTIONS)
+        .map(|(_, id)| id)
+        .collect()
+}
+
+fn suggest(message: String, ids: &[&str]) -> String {
+    let lines: Vec<String> = ids
+        .iter()
+        .map(|id| format!("    {}", id))
+        .collect();
+    format!("{} DID YOU MEAN\n\n{}", message, lines.join("\n"))
+}
+
+/// Pick the only match, or fail with all of them.
+fn pick(id: &str, matches: &[&str]) -> Option<Result<String, String>> {
+    match matches.len() {
+        0 => None,
+        1 => Some(Ok(matches[0].to_string())),
+        _ => Some(Err(suggest(format!("AMBIGUOUS IDENTIFIER {}.", id),
+                              &matches[..matches.len().min(MAX_SUGGESTIONS)]))),
+    }
+}
+
+/// The full identifiers of all categories and tickets.
+fn collect_ids() -> io::Result<(Vec<String>, Vec<String>)> {
+    let mut ids_category = Vec::new();
+    let mut ids_ticket = Vec::new();
+    for category in super::collect_categories()? {
+        ids_category.push(format!("{}/", category.category));
+        for ticket in category.collect()? {
+            if let Some(id) = &ticket.id {
+                ids_ticket.push(format!("{}/{}", category.category, id));
+            }
+        }
+    }
+    ids_category.sort();
+    ids_ticket.sort();
+    Ok((ids_category, ids_ticket))
+}
+
+/// The name of the ticket in its full identifier.
+fn name_of(id: &str) -> &str {
+    id.split('/').nth(1).unwrap_or("")
+}
+
+/// Resolve an abbreviated identifier of an existing category or ticket
+/// into the full one.
+///
+/// A bare ticket name is resolved across the categories, and both
+/// the category and the ticket name may be unique prefixes. Failing that,
+/// similar identifiers are suggested. An identifier resolved to nothing
+/// similar is given back as is, for the operation to fail on its own.
+pub fn resolve(id: &str) -> Result<String, String> {
+    if let Ok(ticket) = Ticket::from(id.to_string(), None) {
+        if ticket.path.exists() {
+            return Ok(id.to_string());
+        }
+    }
+    let (ids_category, ids_ticket) = match collect_ids() {
+        Ok(ids) => ids,
+        Err(_) => return Ok(id.to_string()),
+    };
+
+    let resolved = match id.matches('/').count() {
+        0 => {
+            let exact: Vec<&str> = ids_ticket
+                .iter()
+                .map(|id_| &id_[..])
+                .filter(|id_| name_of(id_) == id)
+                .collect();
+            let prefixed: Vec<&str> = ids_ticket
+                .iter()
+                .map(|id_| &id_[..])
+                .filter(|id_| name_of(id_).starts_with(id))
+                .collect();
+            pick(id, &exact).or_else(|| pick(id, &prefixed))
+        },
+        1 => {
+            let (category, name) = id.split_at(id.find('/').unwrap());
+            let name = &name[1..];
+            let exact_category = ids_category
+                .iter()
+                .any(|id_| id_[..id_.len() - 1] == *category);
+            let in_category = |id_: &&str| {
+                let category_ = &id_[..id_.find('/').unwrap()];
+                if exact_category {
+                    category_ == category
+                }
+                else {
+                    category_.starts_with(category)
+                }
+            };
+            if name.is_empty() {
+                let prefixed: Vec<&str> = ids_category
+                    .iter()
+                    .map(|id_| &id_[..])
+                    .filter(in_category)
+                    .collect();
+                pick(id, &prefixed)
+            }
+            else {
+                let prefixed: Vec<&str> = ids_ticket
+                    .iter()
+                    .map(|id_| &id_[..])
+                    .filter(in_category)
+                    .filter(|id_| name_of(id_).starts_with(name))
+                    .collect();
+                pick(id, &prefixed)
+            }
+        },
+        _ => return Ok(id.to_string()),
+    };
+    if let Some(resolved) = resolved {
+        return resolved;
+    }
+
+    // nothing starts with it, so it may be a typo
+    let candidates: Vec<(&str, &str)> = if id.contains('/') {
+        ids_category
+            .iter()
+            .chain(ids_ticket.iter())
+            .map(|id_| (&id_[..], &id_[..]))
+            .collect()
+    }
+    else {
+        ids_ticket
+            .iter()
+            .map(|id_| (name_of(id_), &id_[..]))
+            .collect()
+    };
+    let similar = fuzzy(id, &candidates);
+    if similar.is_empty() {
+        Ok(id.to_string())
+    }
+    else {
+        Err(suggest(String::from("NOT FOUND."), &similar))
+    }
+}
+
+/// Resolve the category of a destination like `op/` or `op/new-name`
+/// by its unique prefix, keeping the name as is, for it may be a new one.
+/// A destination like nothing is given back as is, without suggestions,
+/// for the operation to fail on its own.
+pub fn resolve_destination(id: &str) -> Result<String, String> {
+    let index = match id.find('/') {
+        Some(index) if id.matches('/').count() == 1 => index,
+        _ => return Ok(id.to_string()),
+    };
+    let (category, name) = (&id[..=index], &id[index + 1..]);
+    let ids_category = match collect_ids() {
+        Ok((ids_category, _)) => ids_category,
+        Err(_) => return Ok(id.to_string()),
+    };
+    if ids_category.iter().any(|id_| id_ == category) {
+        return Ok(id.to_string());
+    }
+    let prefixed: Vec<&str> = ids_category
+        .iter()
+        .map(|id_| &id_[..])
+        .filter(|id_| id_.starts_with(&category[..index]))
+        .collect();
+    match pick(category, &prefixed) {
+        Some(resolved) => Ok(format!("{}{}", resolved?, name)),
+        None => Ok(id.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    fn prepare() {
+        super::super::purge().unwrap_or(());
+        super::super::initialize_root().unwrap_or(());
+        let path_root = super::super::get_path_root();
+        std::fs::create_dir(path_root.join("open/")).unwrap();
+        std::fs::create_dir(path_root.join("in-progress/")).unwrap();
+        std::fs::write(path_root.join("open/ticket-1234"), "One").unwrap();
+        std::fs::write(path_root.join("open/ticket-1235"), "Two").unwrap();
+        std::fs::write(path_root.join("open/hotfix-86"), "Hot").unwrap();
+        std::fs::write(path_root.join("in-progress/ticket-1240"), "Three")
+            .unwrap();
+    }
+
+    #[test]
+    fn distance() {
+        assert_eq!(super::distance("ticket", "ticket"), 0);
+        assert_eq!(super::distance("tikcet", "ticket"), 2);
+        assert_eq!(super::distance("", "abc"), 3);
+        assert_eq!(super::distance("hotfix-68", "hotfix-86"), 2);
+    }
+
+    #[test]
+    fn resolve_exact_and_unique_prefixes() {
+        prepare();
+        assert_eq!(super::resolve("open/ticket-1234"),
+                   Ok(String::from("open/ticket-1234")));
+        assert_eq!(super::resolve("ticket-1240"),
+                   Ok(String::from("in-progress/ticket-1240")));
+        assert_eq!(super::resolve("hot"), Ok(String::from("open/hotfix-86")));
+        assert_eq!(super::resolve("open/hot"),
+                   Ok(String::from("open/hotfix-86")));
+        assert_eq!(super::resolve("in/ticket"),
+                   Ok(String::from("in-progress/ticket-1240")));
+        assert_eq!(super::resolve("in/"), Ok(String::from("in-progress/")));
+    }
+
+    #[test]
+    fn suggest_on_ambiguity_and_typo() {
+        prepare();
+        assert_eq!(super::resolve("open/ticket"),
+                   Err(String::from("AMBIGUOUS IDENTIFIER open/ticket. \
+                                    DID YOU MEAN\n\n    open/ticket-1234\n    \
+                                    open/ticket-1235")));
+        assert_eq!(super::resolve("hotfix-68"),
+                   Err(String::from("NOT FOUND. DID YOU MEAN\n\n    \
+                                    open/hotfix-86")));
+        // nothing similar at all
+        assert_eq!(super::resolve("open/zzz"), Ok(String::from("open/zzz")));
+        assert_eq!(super::resolve("in/valid/id"),
+                   Ok(String::from("in/valid/id")));
+    }
+
+    #[test]
+    fn resolve_the_category_of_destinations() {
+        prepare();
+        assert_eq!(super::resolve_destination("in/"),
+                   Ok(String::from("in-progress/")));
+        assert_eq!(super::resolve_destination("in/ticket-1234"),
+                   Ok(String::from("in-progress/ticket-1234")));
+        assert_eq!(super::resolve_destination("open/ticket-1240"),
+                   Ok(String::from("open/ticket-1240")));
+        assert_eq!(super::resolve_destination("never/new"),
+                   Ok(String::from("never/new")));
+        assert_eq!(super::resolve_destination("opeen/"),
+                   Ok(String::from("opeen/")));
+        assert!(super::resolve_destination("/").is_err());
+    }
+}
@@ -0,0 +1,307 @@
+use std::io;
+
+use serde_json::{json, Value};
+
+use super::ticket::Ticket;
+
+/// The JSON representation of a ticket, which must have been read.
+pub fn ticket_json(ticket: &Ticket) -> io::Result<Value> {
+    let modified_at = ticket.modified_at.map(|modified| {
+        chrono::DateTime::<chrono::Local>::from(modified).to_rfc3339()
+    });
+    Ok(json!({
+        "category": ticket.category,
+        "id": ticket.id,
+        "title": ticket.title,
+        "message": ticket.message,
+        "modified_at": modified_at,
+        "meta": ticket.meta()?,
+    }))
+}
+
+fn category_json(category: &Ticket) -> io::Result<Value> {
+    Ok(json!({
+        "category": category.category,
+        "tickets": category.collect()?.len(),
+    }))
+}
+
+/// The HTTP status for a failed operation on the store.
+fn status_of(error: &io::Error) -> u16 {
+    match error.kind() {
+        io::ErrorKind::NotFound => 404,
+        io::ErrorKind::AlreadyExists => 409,
+        io::ErrorKind::InvalidInput | io::ErrorKind::InvalidData => 400,
+        _ => 500,
+    }
+}
+
+fn error_json(status: u16, message: &str) -> (u16, Value) {
+    (status, json!({ "error": message }))
+}
+
+fn from_id(id: String) -> Result<Ticket, (u16, Value)> {
+    Ticket::from(id, None).map_err(|error| error_json(400, &error))
+}
+
+/// The content of a ticket file from a JSON body
+/// like `{"title": "...", "message": "..."}`.
+fn content_of(body: &str) -> Result<String, (u16, Value)> {
+    let value: Value = serde_json::from_str(body)
+        .map_err(|error| error_json(400, &error.to_string()))?;
+    let title = match value["title"].as_str() {
+        Some(title) if !title.trim().is_empty() => title.trim(),
+        _ => return Err(error_json(400, "The title is required.")),
+    };
+    match value["message"].as_str() {
+        Some(message) if !message.trim().is_empty() => {
+            Ok(format!("{}\n\n{}", title, message.trim()))
+        },
+        _ => Ok(title.to_string()),
+    }
+}
+
+fn read_ticket(id: String) -> Result<(u16, Value), (u16, Value)> {
+    let mut ticket = from_id(id)?;
+    if !ticket.path.is_file() {
+        return Err(error_json(404, "NOT FOUND."));
+    }
+    ticket.read().map_err(|error| error_json(status_of(&error),
+                                             &error.to_string()))?;
+    ticket_json(&ticket)
+        .map(|value| (200, value))
+        .map_err(|error| error_json(500, &error.to_string()))
+}
+
+/// Route a request to the operations on the store, and give the status
+/// with the JSON to respond.
+pub fn handle(method: &str, url: &str, body: &str) -> (u16, Value) {
+    match route(method, url, body) {
+        Ok(response) => response,
+        Err(response) => response,
+    }
+}
+
+/// The segment of a path with the percent escapes decoded.
+fn decode(segment: &str) -> Option<String> {
+    let bytes = segment.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        if bytes[index] == b'%' {
+            let hex = segment.get(index + 1..index + 3)?;
+            decoded.push(u8::from_str_radix(hex, 16).ok()?);
+            index += 3;
+        }
+        else {
+            decoded.push(bytes[index]);
+            index += 1;
+        }
+    }
+    String::from_utf8(decoded).ok()
+}
+
+/// The decoded segments of the path, none of which may leave
+/// its place in the store.
+fn segments_of(path: &str) -> Result<Vec<String>, (u16, Value)> {
+    let mut segments = Vec::new();
+    let path = path.trim_matches('/');
+    if path.is_empty() {
+        return Ok(segments);
+    }
+    for segment in path.split('/') {
+        match decode(segment) {
+            Some(ref decoded) if !decoded.is_empty()
+                && !decoded.starts_with('.')
+                && !decoded.contains('/') => {
+                segments.push(decoded.clone());
+            },
+            _ => {
+                return Err(error_json(400, &format!("Invalid segment {}",
+                                                    segment)));
+            },
+        }
+    }
+    Ok(segments)
+}
+
+fn route(method: &str, url: &str, body: &str)
+    -> Result<(u16, Value), (u16, Value)> {
+    let path = url.split('?').next().unwrap_or("");
+    let segments = segments_of(path)?;
+    let segments: Vec<&str> = segments
+        .iter()
+        .map(|segment| &segment[..])
+        .collect();
+    let io_error = |error: io::Error| error_json(status_of(&error),
+                                                 &error.to_string());
+
+    match (method, &segments[..]) {
+        ("GET", ["categories"]) => {
+            let categories = super::collect_categories().map_err(io_error)?;
+            let mut values = Vec::new();
+            for category in &categories {
+                values.push(category_json(category).map_err(io_error)?);
+            }
+            Ok((200, Value::Array(values)))
+        },
+        ("GET", ["categories", category]) => {
+            let category = from_id(format!("{}/", category))?;
+            if !category.path.is_dir() {
+                return Err(error_json(404, "NOT FOUND."));
+            }
+            let mut values = Vec::new();
+            for ticket in category.collect().map_err(io_error)? {
+                values.push(ticket_json(&ticket).map_err(io_error)?);
+            }
+            Ok((200, Value::Array(values)))
+        },
+        ("PUT", ["categories", category]) => {
+            let category = from_id(format!("{}/", category))?;
+            category.new().map_err(io_error)?;
+            Ok((201, category_json(&category).map_err(io_error)?))
+        },
+        ("DELETE", ["categories", category]) => {
+            let category = from_id(format!("{}/", category))?;
+            category.remove().map_err(io_error)?;
+            Ok((200, json!({})))
+        },
+        ("GET", ["tickets", category, id]) => {
+            read_ticket(format!("{}/{}", category, id))
+        },
+        ("PUT", ["tickets", category, id]) => {
+            let id = format!("{}/{}", category, id);
+            let content = content_of(body)?;
+            let ticket = Ticket::from(id.clone(), Some(content))
+                .map_err(|error| error_json(400, &error))?;
+            let status = if ticket.path.exists() {
+                ticket.edit().map_err(io_error)?;
+                200
+            } else {
+                ticket.new().map_err(io_error)?;
+                201
+            };
+            read_ticket(id).map(|(_, value)| (status, value))
+        },
+        ("DELETE", ["tickets", category, id]) => {
+            let ticket = from_id(format!("{}/{}", category, id))?;
+            if !ticket.path.is_file() {
+                return Err(error_json(404, "NOT FOUND."));
+            }
+            ticket.remove().map_err(io_error)?;
+            Ok((200, json!({})))
+        },
+        ("POST", ["tickets", category, id, "move"]) => {
+            let ticket = from_id(format!("{}/{}", category, id))?;
+            let value: Value = serde_json::from_str(body)
+                .map_err(|error| error_json(400, &error.to_string()))?;
+            let dest_id = match value["destination"].as_str() {
+                Some(dest_id) => dest_id.to_string(),
+                None => {
+                    return Err(error_json(400, "The destination is required."));
+                },
+            };
+            let dest_ticket = from_id(dest_id)?;
+            ticket.move_(&dest_ticket).map_err(io_error)?;
+            let moved_id = match &dest_ticket.id {
+                Some(dest_id) => format!("{}/{}", dest_ticket.category, dest_id),
+                None => format!("{}/{}", dest_ticket.category, id),
+            };
+            read_ticket(moved_id)
+        },
+        (_, ["categories"]) | (_, ["categories", _])
+            | (_, ["tickets", _, _]) | (_, ["tickets", _, _, "move"]) => {
+            Err(error_json(405, "METHOD NOT ALLOWED."))
+        },
+        _ => Err(error_json(404, "NOT FOUND.")),
+    }
+}
+
+pub fn bind(address: &str) -> io::Result<tiny_http::Server> {
+    tiny_http::Server::http(address)
+        .map_err(|error| io::Error::new(io::ErrorKind::AddrNotAvailable,
+                                        error.to_string()))
+}
+
+/// Serve the REST API until the process is killed.
+pub fn serve(server: tiny_http::Server) -> io::Result<()> {
+    let header_json = tiny_http::Header::from_bytes(
+        &b"Content-Type"[..],
+        &b"application/json"[..]).unwrap();
+    for mut request in server.incoming_requests() {
+        let mut body = String::new();
+        let (status, value) = match request.as_reader().read_to_string(&mut body) {
+            Ok(_) => handle(request.method().as_str(), request.url(), &body),
+            Err(error) => error_json(400, &error.to_string()),
+        };
+        let response = tiny_http::Response::from_string(value.to_string())
+            .with_status_code(status)
+            .with_header(header_json.clone());
+        request.respond(response).unwrap_or(());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    fn prepare() {
+        super::super::purge().unwrap_or(());
+        super::super::initialize_root().unwrap_or(());
+        let path_root = super::super::get_path_root();
+        std::fs::create_dir(path_root.join("open/")).unwrap();
+        std::fs::write(path_root.join("open/ID-1"), "Fix it\n\nQuickly")
+            .unwrap();
+    }
+
+    #[test]
+    fn get_a_ticket() {
+        prepare();
+        let (status, value) = super::handle("GET", "/tickets/open/ID-1", "");
+        assert_eq!(status, 200);
+        assert_eq!(value["category"], "open");
+        assert_eq!(value["id"], "ID-1");
+        assert_eq!(value["title"], "Fix it");
+        assert_eq!(value["message"], "Quickly");
+    }
+
+    #[test]
+    fn map_errors_to_statuses() {
+        prepare();
+        assert_eq!(super::handle("GET", "/tickets/open/ID-2", "").0, 404);
+        assert_eq!(super::handle("GET", "/tickets/open", "").0, 404);
+        assert_eq!(super::handle("PATCH", "/tickets/open/ID-1", "").0, 405);
+        assert_eq!(super::handle("PUT", "/tickets/open/ID-1", "{}").0, 400);
+        assert_eq!(super::handle("PUT", "/tickets/open/.x", "").0, 400);
+        assert_eq!(super::handle("PUT", "/categories/open", "").0, 409);
+        assert_eq!(
+            super::handle("PUT", "/tickets/never/ID-1", r#"{"title": "x"}"#).0,
+            404);
+    }
+
+    #[test]
+    fn decode_the_segments() {
+        prepare();
+        let (status, value) = super::handle("GET", "/tickets/open/ID%2D1", "");
+        assert_eq!(status, 200);
+        assert_eq!(value["id"], "ID-1");
+        assert_eq!(super::handle("GET", "/tickets/open/ID%2", "").0, 400);
+        assert_eq!(super::handle("GET", "/tickets/open/%FF", "").0, 400);
+    }
+
+    #[test]
+    fn reject_the_segments_out_of_the_store() {
+        prepare();
+        let path_root = super::super::get_path_root();
+        let body = r#"{"title": "x"}"#;
+        for url in &["/categories/..", "/categories/.", "/categories/%2E%2E",
+                     "/categories/.hidden", "/categories/open%2F..",
+                     "/tickets/../escaped", "/tickets/%2e%2e/escaped",
+                     "/tickets//escaped", "/tickets/open/..%2Fescaped"] {
+            assert_eq!(super::handle("DELETE", url, "").0, 400, "{}", url);
+            assert_eq!(super::handle("PUT", url, body).0, 400, "{}", url);
+        }
+        assert!(path_root.join("open/ID-1").exists());
+        assert!(!path_root.join("../escaped").exists());
+        assert!(!path_root.join("escaped").exists());
+    }
+}
@@ -0,0 +1,255 @@
+use std::ffi::OsString;
+use std::io::{self, IsTerminal};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+const RESET: &str = "\x1b[0m";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Choice {
+    Auto,
+    Always,
+    Never,
+}
+
+impl FromStr for Choice {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Choice, String> {
+        match value {
+            "auto" => Ok(Choice::Auto),
+            "always" => Ok(Choice::Always),
+            "never" => Ok(Choice::Never),
+            _ => Err(format!("Invalid color choice: {}", value)),
+        }
+    }
+}
+
+static CHOICE: AtomicU8 = AtomicU8::new(0);
+
+/// Set the color choice of the whole process, from `--color`.
+pub fn set_choice(choice: Choice) {
+    let value = match choice {
+        Choice::Auto => 0,
+        Choice::Always => 1,
+        Choice::Never => 2,
+    };
+    CHOICE.store(value, Ordering::Relaxed);
+}
+
+pub fn choice() -> Choice {
+    match CHOICE.load(Ordering::Relaxed) {
+        1 => Choice::Always,
+        2 => Choice::Never,
+        _ => Choice::Auto,
+    }
+}
+
+/// Whether to color the output to a stream, which may be a terminal.
+///
+/// With `--color auto`, a non-empty NO_COLOR turns the colors off
+/// and CLICOLOR_FORCE other than 0 turns them on even when piped.
+pub fn enabled_for(is_terminal: bool) -> bool {
+    decide(choice(),
+           is_terminal,
+           std::env::var_os("NO_COLOR"),
+           std::env::var_os("CLICOLOR_FORCE"))
+}
+
+fn decide(choice: Choice,
+          is_terminal: bool,
+          no_color: Option<OsString>,
+          clicolor_force: Option<OsString>) -> bool {
+    match choice {
+        Choice::Always => true,
+        Choice::Never => false,
+        Choice::Auto => {
+            if no_color.is_some_and(|value| !value.is_empty()) {
+                return false;
+            }
+            if clicolor_force
+                .is_some_and(|value| !value.is_empty() && value != "0") {
+                return true;
+            }
+            is_terminal
+        },
+    }
+}
+
+/// Whether to color the standard output.
+pub fn enabled() -> bool {
+    enabled_for(io::stdout().is_terminal())
+}
+
+/// Whether to color the standard error.
+pub fn enabled_stderr() -> bool {
+    enabled_for(io::stderr().is_terminal())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Style {
+    Header,
+    Category,
+    Id,
+    Title,
+    Status,
+    Priority,
+    Badge,
+    Total,
+    Succeeded,
+    Error,
+}
+
+impl Style {
+    fn code(self) -> &'static str {
+        match self {
+            Style::Header => "\x1b[1m",
+            Style::Category => "\x1b[1;34m",
+            Style::Id => "\x1b[33m",
+            Style::Title => "\x1b[1m",
+            Style::Status => "\x1b[35m",
+            Style::Priority => "\x1b[1;31m",
+            Style::Badge => "\x1b[36m",
+            Style::Total => "\x1b[2m",
+            Style::Succeeded => "\x1b[1;32m",
+            Style::Error => "\x1b[1;31m",
+        }
+    }
+}
+
+/// Style the text when coloring is enabled.
+pub fn paint_if(enabled: bool, style: Style, text: &str) -> String {
+    if enabled && !text.is_empty() {
+        format!("{}{}{}", style.code(), text, RESET)
+    }
+    else {
+        text.to_string()
+    }
+}
+
+/// Style the text for the standard output.
+pub fn paint(style: Style, text: &str) -> String {
+    paint_if(enabled(), style, text)
+}
+
+/// Style the header and the result line of a message of the program,
+/// like `tickets :: init` followed by `SUCCEEDED.` or `ERROR: ...`.
+/// Only the result where the program puts it is styled: an error right
+/// after the header, or alone like `ERROR: NOT INITIALIZED`, and a success
+/// last after the header and any report, so that no ticket is.
+pub fn paint_message(enabled: bool, is_error: bool, message: &str) -> String {
+    if !enabled {
+        return message.to_string();
+    }
+    let (header, body) = match message.split_once("\n\n") {
+        Some((header, body)) if header.starts_with("tickets ::") => {
+            (Some(header), body)
+        },
+        _ if message.starts_with("tickets ::") => (Some(message), ""),
+        _ => (None, message),
+    };
+    let body = if is_error {
+        match body.strip_prefix("ERROR:") {
+            Some(rest) => format!("{}{}",
+                                  paint_if(enabled, Style::Error, "ERROR:"),
+                                  rest),
+            None => body.to_string(),
+        }
+    }
+    else if header.is_some() && (body == "SUCCEEDED."
+                                 || body.ends_with("\n\nSUCCEEDED.")) {
+        format!("{}{}",
+                &body[..body.len() - "SUCCEEDED.".len()],
+                paint_if(enabled, Style::Succeeded, "SUCCEEDED."))
+    }
+    else {
+        body.to_string()
+    };
+    match header {
+        Some(header_) if header_.len() == message.len() => {
+            paint_if(enabled, Style::Header, header_)
+        },
+        Some(header_) => format!("{}\n\n{}",
+                                 paint_if(enabled, Style::Header, header_),
+                                 body),
+        None => body,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Choice, Style};
+
+    #[test]
+    fn parse_choice() {
+        assert_eq!("auto".parse::<Choice>(), Ok(Choice::Auto));
+        assert_eq!("always".parse::<Choice>(), Ok(Choice::Always));
+        assert_eq!("never".parse::<Choice>(), Ok(Choice::Never));
+        assert!("sometimes".parse::<Choice>().is_err());
+    }
+
+    #[test]
+    fn paint_only_when_enabled() {
+        assert_eq!(super::paint_if(true, Style::Id, "[ID-1]"),
+                   "\x1b[33m[ID-1]\x1b[0m");
+        assert_eq!(super::paint_if(false, Style::Id, "[ID-1]"), "[ID-1]");
+        assert_eq!(super::paint_if(true, Style::Title, ""), "");
+    }
+
+    #[test]
+    fn paint_results_of_messages() {
+        assert_eq!(
+            super::paint_message(true, false, "tickets :: init\n\nSUCCEEDED."),
+            "\x1b[1mtickets :: init\x1b[0m\n\n\x1b[1;32mSUCCEEDED.\x1b[0m");
+        assert_eq!(
+            super::paint_message(true, true,
+                                 "tickets :: show\n\nERROR: NOT FOUND."),
+            "\x1b[1mtickets :: show\x1b[0m\n\n\x1b[1;31mERROR:\x1b[0m NOT FOUND.");
+        assert_eq!(
+            super::paint_message(true, false, "tickets :: stop :: open/ID-1\n\n\
+                                 DISCARDED.\n\nSUCCEEDED."),
+            "\x1b[1mtickets :: stop :: open/ID-1\x1b[0m\n\nDISCARDED.\n\n\
+            \x1b[1;32mSUCCEEDED.\x1b[0m");
+        assert_eq!(
+            super::paint_message(true, true,
+                                 "ERROR: NOT INITIALIZED, PLEASE init."),
+            "\x1b[1;31mERROR:\x1b[0m NOT INITIALIZED, PLEASE init.");
+        assert_eq!(super::paint_message(true, false, "Title\n\nDone."),
+                   "Title\n\nDone.");
+        assert_eq!(
+            super::paint_message(false, false, "tickets :: init\n\nSUCCEEDED."),
+            "tickets :: init\n\nSUCCEEDED.");
+    }
+
+    #[test]
+    fn leave_the_tickets_unpainted() {
+        // a ticket shown is no result, whatever its lines
+        assert_eq!(
+            super::paint_message(true, false, "tickets :: show :: open/ID-1\n\n\
+                                 ERROR: in the log\nSUCCEEDED.\nDone."),
+            "\x1b[1mtickets :: show :: open/ID-1\x1b[0m\n\n\
+            ERROR: in the log\nSUCCEEDED.\nDone.");
+        // nor a listing without a header
+        assert_eq!(super::paint_message(true, false, "open/\n\nSUCCEEDED."),
+                   "open/\n\nSUCCEEDED.");
+        // and the error is the one right after the header
+        assert_eq!(
+            super::paint_message(true, true, "tickets :: show\n\n\
+                                 ERROR: The hook failed:\nERROR: denied"),
+            "\x1b[1mtickets :: show\x1b[0m\n\n\
+            \x1b[1;31mERROR:\x1b[0m The hook failed:\nERROR: denied");
+    }
+
+    #[test]
+    fn decide_by_environment() {
+        let set = |value: &str| Some(std::ffi::OsString::from(value));
+        assert!(super::decide(Choice::Auto, true, None, None));
+        assert!(!super::decide(Choice::Auto, false, None, None));
+        assert!(super::decide(Choice::Auto, false, None, set("1")));
+        assert!(!super::decide(Choice::Auto, false, None, set("0")));
+        assert!(!super::decide(Choice::Auto, true, set("1"), set("1")));
+        assert!(super::decide(Choice::Auto, true, set(""), None));
+        assert!(super::decide(Choice::Always, false, set("1"), None));
+        assert!(!super::decide(Choice::Never, true, None, set("1")));
+    }
+}
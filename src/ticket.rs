@@ -1,17 +1,31 @@
+use std::collections::BTreeMap;
 use std::fmt;
 use std::fs;
-use std::io::{self, BufRead};
+use std::io::{self, BufRead, Write};
 use std::path;
 use std::time;
 
+use super::comment::{self, Comment};
+use super::estimate;
+use super::style;
+use super::timelog;
+
 fn identify_id(id: &str) -> Result<(String, Option<String>), String> {
     let count = id.matches("/").count();
     match count {
         1 => {
             let split: Vec<&str> = id.split("/").collect();
             let category = split[0];
+            // the directory of the hooks is not a category, and neither
+            // is the root, its parent nor a hidden directory
+            if category == super::hooks::DIR_HOOKS || category.is_empty()
+                || category.starts_with('.') {
+                return Err(format!("Invalid identifier {}", id));
+            }
             let ticket_id = if split[1].is_empty() {
                 None
+            } else if split[1].starts_with('.') {
+                return Err(format!("Invalid identifier {}", id));
             } else {
                 Some(split[1].to_string())
             };
@@ -23,6 +37,18 @@ fn identify_id(id: &str) -> Result<(String, Option<String>), String> {
     }
 }
 
+/// Sort the tickets by the option `core.sort`, which is either
+/// `modified` (default), `id` or `title`.
+pub fn sort(tickets: &mut [Ticket]) {
+    match super::config::get("core.sort").as_ref().map(|sort_| &sort_[..]) {
+        Some("id") => tickets.sort_by(|a, b| (&a.category, &a.id)
+                                      .cmp(&(&b.category, &b.id))),
+        Some("title") => tickets.sort_by(|a, b| (&a.title, &a.category)
+                                         .cmp(&(&b.title, &b.category))),
+        _ => tickets.sort_by_key(|tic| tic.modified_at),
+    }
+}
+
 #[derive(Debug)]
 pub struct Ticket {
     pub path: path::PathBuf,
@@ -37,7 +63,11 @@ pub struct Ticket {
 impl fmt::Display for Ticket {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         if self.is_dir {
-            let vec_tickets = self.collect().unwrap();
+            // the callers report it before, but the formatting never fails
+            let vec_tickets = match self.collect() {
+                Ok(vec_tickets) => vec_tickets,
+                Err(error) => return write!(f, "ERROR: {}", error),
+            };
             if vec_tickets.is_empty() {
                 write!(f, "NO TICKETS.")
             }
@@ -47,19 +77,52 @@ impl fmt::Display for Ticket {
                     .map(|tic| format!("{}", tic))
                     .collect();
                 let str_tickets = vec_id.join("\n");
-                write!(f, "{}", str_tickets)
+                write!(f, "{}", str_tickets)?;
+                let unit = estimate::Unit::current();
+                match estimate::rollup(&vec_tickets, &unit) {
+                    Ok(Some(rollup)) => write!(f, "\n{}",
+                                               style::paint(style::Style::Total,
+                                                            &rollup)),
+                    Ok(None) => Ok(()),
+                    // the tickets are still worth showing without the total,
+                    // which is warned about by the caller
+                    Err(_) => Ok(()),
+                }
             }
         }
         else {
             let id = match &self.id {
-                Some(id_) => format!("[{}]", id_),
+                Some(id_) => style::paint(style::Style::Id,
+                                          &format!("[{}]", id_)),
                 None => String::new(),
             };
             let title = match &self.title {
-                Some(title_) => title_,
-                None => "",
+                Some(title_) => style::paint(style::Style::Title, title_),
+                None => String::new(),
             };
-            write!(f, "{}{}", id, title)
+            let meta = self.meta().unwrap_or_default();
+            let mut badges = String::new();
+            if let Some(status) = meta.get("status") {
+                badges.push_str(&format!(
+                    " {}",
+                    style::paint(style::Style::Status,
+                                 &format!("({})", status))));
+            }
+            if let Some(priority) = meta.get("priority") {
+                badges.push_str(&format!(
+                    " {}",
+                    style::paint(style::Style::Priority,
+                                 &format!("(priority {})", priority))));
+            }
+            if let Some(estimate_) = estimate::of(self).unwrap_or(None) {
+                badges.push_str(&format!(
+                    " {}",
+                    style::paint(
+                        style::Style::Badge,
+                        &format!("({})",
+                                 estimate::Unit::current().format(estimate_)))));
+            }
+            write!(f, "{}{}{}", id, title, badges)
         }
     }
 }
@@ -70,7 +133,7 @@ impl Ticket {
         let (category, id) = identify_id(&str_path)?;
         let path = super::get_path_root()
             .join(str_path);
-        let is_dir = id == None;
+        let is_dir = id.is_none();
         Ok(
             Ticket {
                 path,
@@ -84,6 +147,7 @@ impl Ticket {
         )
     }
 
+    #[allow(clippy::new_ret_no_self, clippy::wrong_self_convention)]
     pub fn new(&self) -> io::Result<()> {
         if self.path.exists() {
             return Err(io::Error::new(io::ErrorKind::AlreadyExists,
@@ -174,6 +238,9 @@ impl Ticket {
         let iter_dir = fs::read_dir(&self.path)?;
         for entry in iter_dir {
             let entry = entry?;
+            if is_sidecar(&entry.file_name()) {
+                continue;
+            }
             let path_entry = entry.path();
             let id_ticket = path_entry.strip_prefix(&path_root).unwrap();
             let mut ticket = Ticket::from(
@@ -183,12 +250,17 @@ impl Ticket {
             ticket.read()?;
             tickets.push(ticket);
         }
-        tickets.sort_by_key(|tic| tic.modified_at);
+        sort(&mut tickets);
         // println!("{:?}", tickets);
         Ok(tickets)
     }
 
     pub fn move_(&self, dest_ticket: &Ticket) -> io::Result<()> {
+        if !self.path.is_file() {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                "the source path is not an existing regular file"));
+        }
         let self_id = match &self.id {
             Some(id) => id,
             None => "",
@@ -197,9 +269,20 @@ impl Ticket {
         if dest_ticket.is_dir {
             dest_path.push(self_id);
         }
-        fs::copy(&self.path, dest_path)?;
-        fs::remove_file(&self.path)?;
-        Ok(())
+        let dest_id = dest_path.file_name().unwrap().to_string_lossy();
+        let dest_sidecar = dest_path.with_file_name(format!(".{}", dest_id));
+        let sidecar = self.sidecar_path();
+        // a ticket moved onto another replaces it, and so does its sidecar,
+        // not to adopt the comments and time of the ticket overwritten
+        if dest_path != self.path && dest_sidecar.exists() {
+            fs::remove_dir_all(&dest_sidecar)?;
+        }
+        fs::rename(&self.path, &dest_path)?;
+        if sidecar.exists() {
+            fs::rename(sidecar, dest_sidecar)?;
+        }
+        timelog::Timer::follow(&format!("{}/{}", self.category, self_id),
+                               &format!("{}/{}", dest_ticket.category, dest_id))
     }
 
     pub fn move_all(&self, dest_dir: &Ticket) -> io::Result<()> {
@@ -223,13 +306,16 @@ impl Ticket {
         let iter_dir = fs::read_dir(&self.path)?;
         for entry in iter_dir {
             let entry = entry?;
+            if is_sidecar(&entry.file_name()) {
+                continue;
+            }
             let path_entry = entry.path();
             let id_ticket = path_entry.strip_prefix(&path_root).unwrap();
             let ticket = Ticket::from(
                 id_ticket.to_string_lossy().into_owned(),
                 None)
                 .unwrap();
-            ticket.move_(&dest_dir)?;
+            ticket.move_(dest_dir)?;
         }
         Ok(())
     }
@@ -240,9 +326,216 @@ impl Ticket {
         }
         else {
             fs::remove_file(&self.path)?;
+            let sidecar = self.sidecar_path();
+            if sidecar.exists() {
+                fs::remove_dir_all(sidecar)?;
+            }
+        }
+        // the time of a ticket gone has nowhere to be logged
+        timelog::Timer::forget(&format!("{}/{}",
+                                        self.category,
+                                        self.id.clone().unwrap_or_default()))
+    }
+
+    /// The hidden directory next to the ticket file, which keeps
+    /// everything belonging to the ticket other than its message.
+    pub fn sidecar_path(&self) -> path::PathBuf {
+        let id = match &self.id {
+            Some(id_) => id_,
+            None => "",
+        };
+        self.path.with_file_name(format!(".{}", id))
+    }
+
+    pub fn comment(&self, body: Option<String>) -> io::Result<()> {
+        if !self.path.exists() {
+            return Err(io::Error::new(io::ErrorKind::NotFound,
+                                      "The path does not exist."));
+        }
+        if self.is_dir {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                                      "The category cannot be commented."));
+        }
+        let body = match body {
+            Some(body_) => body_,
+            None => {
+                let sidecar = self.sidecar_path();
+                fs::create_dir_all(&sidecar)?;
+                let path_draft = sidecar.join("COMMENT_EDITMSG");
+                super::open_editor(&path_draft)?;
+                let body_ = fs::read_to_string(&path_draft).unwrap_or_default();
+                fs::remove_file(&path_draft).unwrap_or(());
+                body_
+            },
+        };
+        if body.trim().is_empty() {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                                      "The comment is empty."));
+        }
+        let comment = Comment::new(comment::current_author(), body);
+        comment.write(&self.sidecar_path().join("comments"))
+    }
+
+    pub fn attach(&self, files: &[path::PathBuf]) -> io::Result<()> {
+        if !self.path.exists() {
+            return Err(io::Error::new(io::ErrorKind::NotFound,
+                                      "The path does not exist."));
+        }
+        if self.is_dir {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                                      "The category cannot have attachments."));
+        }
+        let path_attachments = self.sidecar_path().join("attachments");
+        let mut pairs: Vec<(&path::PathBuf, path::PathBuf)> = Vec::new();
+        for file in files {
+            if !file.is_file() {
+                return Err(io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("The file {} does not exist.", file.display())));
+            }
+            let name = file.file_name().unwrap();
+            let dest = path_attachments.join(name);
+            if dest.exists() || pairs.iter().any(|(_, dest_)| dest_ == &dest) {
+                return Err(io::Error::new(
+                    io::ErrorKind::AlreadyExists,
+                    format!("The attachment {} already exists.",
+                            name.to_string_lossy())));
+            }
+            pairs.push((file, dest));
+        }
+        fs::create_dir_all(&path_attachments)?;
+        for (file, dest) in pairs {
+            fs::copy(file, dest)?;
         }
         Ok(())
     }
+
+    pub fn attachments(&self) -> io::Result<Vec<String>> {
+        if !self.path.exists() {
+            return Err(io::Error::new(io::ErrorKind::NotFound,
+                                      "The path does not exist."));
+        }
+        let path_attachments = self.sidecar_path().join("attachments");
+        if self.is_dir || !path_attachments.exists() {
+            return Ok(Vec::new());
+        }
+        let mut names: Vec<String> = Vec::new();
+        for entry in fs::read_dir(&path_attachments)? {
+            names.push(entry?.file_name().to_string_lossy().into_owned());
+        }
+        names.sort();
+        Ok(names)
+    }
+
+    pub fn detach(&self, name: &str) -> io::Result<()> {
+        let path_attachment = self.sidecar_path()
+            .join("attachments")
+            .join(name);
+        if self.is_dir || name.contains('/') || !path_attachment.is_file() {
+            return Err(io::Error::new(io::ErrorKind::NotFound,
+                                      "The attachment does not exist."));
+        }
+        fs::remove_file(path_attachment)
+    }
+
+    /// The metadata of the ticket, kept as `key: value` lines.
+    pub fn meta(&self) -> io::Result<BTreeMap<String, String>> {
+        let mut meta = BTreeMap::new();
+        let path_meta = self.sidecar_path().join("meta");
+        if self.is_dir || !path_meta.exists() {
+            return Ok(meta);
+        }
+        for line in fs::read_to_string(path_meta)?.lines() {
+            if let Some(index) = line.find(": ") {
+                meta.insert(line[..index].to_string(),
+                            line[index + 2..].to_string());
+            }
+        }
+        Ok(meta)
+    }
+
+    /// Set or, given None, unset a metadata of the ticket.
+    pub fn set_meta(&self, key: &str, value: Option<&str>) -> io::Result<()> {
+        if !self.path.exists() {
+            return Err(io::Error::new(io::ErrorKind::NotFound,
+                                      "The path does not exist."));
+        }
+        if self.is_dir {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                                      "The category cannot have metadata."));
+        }
+        let mut meta = self.meta()?;
+        match value {
+            Some(value_) => {
+                let value_ = value_.replace('\n', " ");
+                meta.insert(key.to_string(), value_.trim().to_string());
+            },
+            None => {
+                meta.remove(key);
+            },
+        };
+        let sidecar = self.sidecar_path();
+        fs::create_dir_all(&sidecar)?;
+        let contents: String = meta
+            .iter()
+            .map(|(key_, value_)| format!("{}: {}\n", key_, value_))
+            .collect();
+        fs::write(sidecar.join("meta"), contents)
+    }
+
+    pub fn log_time(&self, entry: &timelog::Entry) -> io::Result<()> {
+        if !self.path.exists() {
+            return Err(io::Error::new(io::ErrorKind::NotFound,
+                                      "The path does not exist."));
+        }
+        if self.is_dir {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                                      "The category cannot be tracked."));
+        }
+        // the total of the ticket must stay countable
+        let logged = self.time_entries()?
+            .iter()
+            .map(|entry_| entry_.seconds)
+            .chain(vec![entry.seconds])
+            .collect::<Vec<i64>>();
+        timelog::sum(logged).map_err(|_| io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "The logged time of the ticket would be too long."))?;
+        let sidecar = self.sidecar_path();
+        fs::create_dir_all(&sidecar)?;
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(sidecar.join("timelog"))?;
+        file.write_all(entry.to_line().as_bytes())
+    }
+
+    pub fn time_entries(&self) -> io::Result<Vec<timelog::Entry>> {
+        let path_timelog = self.sidecar_path().join("timelog");
+        if self.is_dir || !path_timelog.exists() {
+            return Ok(Vec::new());
+        }
+        let contents = fs::read_to_string(path_timelog)?;
+        Ok(contents.lines().filter_map(timelog::Entry::parse).collect())
+    }
+
+    pub fn read_comments(&self) -> io::Result<Vec<Comment>> {
+        let path_comments = self.sidecar_path().join("comments");
+        if self.is_dir || !path_comments.exists() {
+            return Ok(Vec::new());
+        }
+        let mut paths: Vec<path::PathBuf> = Vec::new();
+        for entry in fs::read_dir(&path_comments)? {
+            paths.push(entry?.path());
+        }
+        paths.sort();
+        paths.iter().map(|path_| Comment::read(path_)).collect()
+    }
+}
+
+/// Whether a directory entry is a sidecar rather than a ticket.
+pub fn is_sidecar(file_name: &std::ffi::OsStr) -> bool {
+    file_name.to_string_lossy().starts_with('.')
 }
 
 #[cfg(test)]
@@ -279,6 +572,20 @@ mod tests {
                    Err(String::from("Invalid identifier really/bad/id")));
     }
 
+    #[test]
+    fn fail_to_identify_a_hidden_id() {
+        assert_eq!(super::identify_id("where/.hidden"),
+                   Err(String::from("Invalid identifier where/.hidden")));
+    }
+
+    #[test]
+    fn fail_to_identify_a_category_out_of_the_store() {
+        for id in &["../", "./", "../escaped", ".hidden/ID-1", "/ID-1"] {
+            assert_eq!(super::identify_id(id),
+                       Err(format!("Invalid identifier {}", id)));
+        }
+    }
+
     #[test]
     fn identify_directory() {
         assert_eq!(super::identify_id("where/"),
@@ -291,7 +598,7 @@ mod tests {
         let ticket = super::Ticket::from("valid/ticket".to_string(), None);
         assert!(ticket.is_ok());
         let ticket = ticket.unwrap();
-        assert_eq!(ticket.is_dir, false);
+        assert!(!ticket.is_dir);
     }
 
     #[test]
@@ -299,7 +606,7 @@ mod tests {
         let ticket = super::Ticket::from("valid/".to_string(), None);
         assert!(ticket.is_ok());
         let ticket = ticket.unwrap();
-        assert_eq!(ticket.is_dir, true);
+        assert!(ticket.is_dir);
     }
 
     #[test]
@@ -609,6 +916,34 @@ mod tests {
         assert_eq!(dest_ticket.message, Some("and some content".to_string()));
     }
 
+    #[test]
+    fn ticket_move_onto_an_existing_ticket() {
+        super::super::purge().unwrap_or(());
+        super::super::initialize_root().unwrap_or(());
+        let path_root = super::super::get_path_root();
+        super::fs::create_dir(path_root.join("hello/")).unwrap();
+        super::fs::create_dir(path_root.join("world/")).unwrap();
+        super::fs::write(path_root.join("hello/ticket"), "ticket title")
+            .unwrap();
+        super::fs::write(path_root.join("world/ticket"), "overwritten")
+            .unwrap();
+        let overwritten = super::Ticket::from("world/ticket".to_string(),
+                                              None).unwrap();
+        overwritten.comment(Some("stale".to_string())).unwrap();
+
+        let start_ticket = super::Ticket::from("hello/ticket".to_string(),
+                                               None).unwrap();
+        let dest_ticket = super::Ticket::from("world/".to_string(), None)
+            .unwrap();
+        start_ticket.move_(&dest_ticket).unwrap();
+        let moved = super::Ticket::from("world/ticket".to_string(), None)
+            .unwrap();
+        assert_eq!(super::fs::read_to_string(&moved.path).unwrap(),
+                   "ticket title");
+        assert!(!moved.sidecar_path().exists());
+        assert!(!start_ticket.path.exists());
+    }
+
     // ========== ticket.move_all ==========
     #[test]
     fn ticket_move_all() {
@@ -695,4 +1030,225 @@ mod tests {
         assert!(result_remove.is_ok());
         assert!(!ticket.path.exists());
     }
+
+    // ========== ticket.comment ==========
+    #[test]
+    fn ticket_comment_and_read_comments() {
+        super::super::purge().unwrap_or(());
+        super::super::initialize_root().unwrap_or(());
+        let path_root = super::super::get_path_root();
+        let path_dir = path_root.join("hello/");
+        super::fs::create_dir(&path_dir).unwrap();
+        super::fs::write(path_dir.join("ticket"), "ticket title").unwrap();
+
+        let ticket = super::Ticket::from("hello/ticket".to_string(), None)
+            .unwrap();
+        ticket.comment(Some("first\n".to_string())).unwrap();
+        thread::sleep(Duration::from_millis(10));
+        ticket.comment(Some("second".to_string())).unwrap();
+
+        let comments = ticket.read_comments().unwrap();
+        assert_eq!(comments.len(), 2);
+        assert_eq!(comments[0].body, "first");
+        assert_eq!(comments[1].body, "second");
+        assert!(ticket.sidecar_path().ends_with("hello/.ticket"));
+
+        let ticket_dir = super::Ticket::from("hello/".to_string(), None)
+            .unwrap();
+        assert_eq!(ticket_dir.collect().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn fail_to_ticket_comment_with_an_empty_message() {
+        super::super::purge().unwrap_or(());
+        super::super::initialize_root().unwrap_or(());
+        let path_root = super::super::get_path_root();
+        let path_dir = path_root.join("hello/");
+        super::fs::create_dir(&path_dir).unwrap();
+        super::fs::write(path_dir.join("ticket"), "ticket title").unwrap();
+
+        let ticket = super::Ticket::from("hello/ticket".to_string(), None)
+            .unwrap();
+        let result_comment = ticket.comment(Some("  \n".to_string()));
+        assert!(result_comment.is_err());
+        assert!(ticket.read_comments().unwrap().is_empty());
+    }
+
+    #[test]
+    fn ticket_remove_one_with_its_sidecar() {
+        super::super::purge().unwrap_or(());
+        super::super::initialize_root().unwrap_or(());
+        let path_root = super::super::get_path_root();
+        let path_dir = path_root.join("hello/");
+        super::fs::create_dir(&path_dir).unwrap();
+        super::fs::write(path_dir.join("ticket"), "ticket title").unwrap();
+
+        let ticket = super::Ticket::from("hello/ticket".to_string(), None)
+            .unwrap();
+        ticket.comment(Some("going away".to_string())).unwrap();
+        assert!(ticket.sidecar_path().exists());
+        ticket.remove().unwrap();
+        assert!(!ticket.sidecar_path().exists());
+    }
+
+    // ========== ticket.attach ==========
+    #[test]
+    fn ticket_attach_and_detach() {
+        super::super::purge().unwrap_or(());
+        super::super::initialize_root().unwrap_or(());
+        let path_root = super::super::get_path_root();
+        let path_dir = path_root.join("hello/");
+        super::fs::create_dir(&path_dir).unwrap();
+        super::fs::write(path_dir.join("ticket"), "ticket title").unwrap();
+        let path_log = path_root.join("error.log");
+        super::fs::write(&path_log, "oops").unwrap();
+
+        let ticket = super::Ticket::from("hello/ticket".to_string(), None)
+            .unwrap();
+        assert!(ticket.attachments().unwrap().is_empty());
+        ticket.attach(std::slice::from_ref(&path_log)).unwrap();
+        assert_eq!(ticket.attachments().unwrap(), vec!["error.log"]);
+
+        let result_attach = ticket.attach(&[path_log]);
+        match result_attach {
+            Ok(()) => {
+                panic!("This should never happen.");
+            },
+            Err(error) => {
+                assert_eq!(error.kind(), std::io::ErrorKind::AlreadyExists);
+                assert_eq!(error.to_string(),
+                           "The attachment error.log already exists.");
+            },
+        };
+
+        ticket.detach("error.log").unwrap();
+        assert!(ticket.attachments().unwrap().is_empty());
+        assert!(ticket.detach("error.log").is_err());
+    }
+
+    #[test]
+    fn ticket_move_all_carries_attachments() {
+        super::super::purge().unwrap_or(());
+        super::super::initialize_root().unwrap_or(());
+        let path_root = super::super::get_path_root();
+        super::fs::create_dir(path_root.join("hello/")).unwrap();
+        super::fs::create_dir(path_root.join("world/")).unwrap();
+        super::fs::write(path_root.join("hello/ticket"), "ticket title")
+            .unwrap();
+        let path_patch = path_root.join("fix.patch");
+        super::fs::write(&path_patch, "+fixed").unwrap();
+        let ticket = super::Ticket::from("hello/ticket".to_string(), None)
+            .unwrap();
+        ticket.attach(&[path_patch]).unwrap();
+
+        let start_dir = super::Ticket::from("hello/".to_string(), None)
+            .unwrap();
+        let dest_dir = super::Ticket::from("world/".to_string(), None)
+            .unwrap();
+        start_dir.move_all(&dest_dir).unwrap();
+
+        let moved = super::Ticket::from("world/ticket".to_string(), None)
+            .unwrap();
+        assert_eq!(moved.attachments().unwrap(), vec!["fix.patch"]);
+        assert!(!ticket.sidecar_path().exists());
+        assert_eq!(dest_dir.collect().unwrap().len(), 1);
+    }
+
+    // ========== ticket.log_time ==========
+    #[test]
+    fn ticket_log_time_and_read_entries() {
+        super::super::purge().unwrap_or(());
+        super::super::initialize_root().unwrap_or(());
+        let path_root = super::super::get_path_root();
+        super::fs::create_dir(path_root.join("hello/")).unwrap();
+        super::fs::write(path_root.join("hello/ticket"), "ticket title")
+            .unwrap();
+
+        let ticket = super::Ticket::from("hello/ticket".to_string(), None)
+            .unwrap();
+        assert!(ticket.time_entries().unwrap().is_empty());
+        for seconds in &[5400, 600] {
+            let entry = super::timelog::Entry {
+                started_at: chrono::Local::now(),
+                seconds: *seconds,
+            };
+            ticket.log_time(&entry).unwrap();
+        }
+        let entries = ticket.time_entries().unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].seconds, 5400);
+        assert_eq!(entries[1].seconds, 600);
+
+        let entry = super::timelog::Entry {
+            started_at: chrono::Local::now(),
+            seconds: i64::MAX - 1,
+        };
+        assert_eq!(ticket.log_time(&entry).unwrap_err().to_string(),
+                   "The logged time of the ticket would be too long.");
+        assert_eq!(ticket.time_entries().unwrap().len(), 2);
+    }
+
+    // ========== ticket.meta ==========
+    #[test]
+    fn ticket_set_and_unset_meta() {
+        super::super::purge().unwrap_or(());
+        super::super::initialize_root().unwrap_or(());
+        let path_root = super::super::get_path_root();
+        super::fs::create_dir(path_root.join("hello/")).unwrap();
+        super::fs::write(path_root.join("hello/ticket"), "ticket title")
+            .unwrap();
+
+        let ticket = super::Ticket::from("hello/ticket".to_string(), None)
+            .unwrap();
+        assert!(ticket.meta().unwrap().is_empty());
+        ticket.set_meta("estimate", Some("3")).unwrap();
+        ticket.set_meta("labels", Some("bug, ui")).unwrap();
+        let meta = ticket.meta().unwrap();
+        assert_eq!(meta.get("estimate"), Some(&String::from("3")));
+        assert_eq!(meta.get("labels"), Some(&String::from("bug, ui")));
+        assert_eq!(format!("{}", ticket), "[ticket] (3 points)");
+        ticket.set_meta("status", Some("waiting")).unwrap();
+        ticket.set_meta("priority", Some("A")).unwrap();
+        assert_eq!(format!("{}", ticket),
+                   "[ticket] (waiting) (priority A) (3 points)");
+        ticket.set_meta("status", None).unwrap();
+        ticket.set_meta("priority", None).unwrap();
+
+        ticket.set_meta("estimate", None).unwrap();
+        let meta = ticket.meta().unwrap();
+        assert_eq!(meta.get("estimate"), None);
+        assert_eq!(meta.len(), 1);
+    }
+
+    // ========== ticket display ==========
+    #[test]
+    fn display_an_unreadable_category() {
+        super::super::purge().unwrap_or(());
+        super::super::initialize_root().unwrap_or(());
+        let path_root = super::super::get_path_root();
+        super::fs::create_dir(path_root.join("hello/")).unwrap();
+        super::fs::write(path_root.join("hello/ticket"), b"\xff\xfe")
+            .unwrap();
+
+        let category = super::Ticket::from("hello/".to_string(), None)
+            .unwrap();
+        assert!(format!("{}", category).starts_with("ERROR: "));
+    }
+
+    #[test]
+    fn display_a_category_with_an_unreadable_timelog() {
+        super::super::purge().unwrap_or(());
+        super::super::initialize_root().unwrap_or(());
+        let path_root = super::super::get_path_root();
+        super::fs::create_dir(path_root.join("hello/")).unwrap();
+        super::fs::write(path_root.join("hello/ticket"), "ticket title")
+            .unwrap();
+        // a directory cannot be read as the timelog
+        super::fs::create_dir_all(path_root.join("hello/.ticket/timelog"))
+            .unwrap();
+
+        let category = super::Ticket::from("hello/".to_string(), None)
+            .unwrap();
+        assert_eq!(format!("{}", category), "[ticket]ticket title");
+    }
 }
@@ -0,0 +1,415 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path;
+use std::str::FromStr;
+
+use chrono::{DateTime, Datelike, Duration, Local, TimeZone, Weekday};
+
+use super::formats::csv;
+use super::ticket::Ticket;
+
+/// A piece of work logged against a ticket.
+#[derive(Debug)]
+pub struct Entry {
+    pub started_at: DateTime<Local>,
+    pub seconds: i64,
+}
+
+impl Entry {
+    pub fn to_line(&self) -> String {
+        format!("{} {}\n", self.started_at.to_rfc3339(), self.seconds)
+    }
+
+    pub fn parse(line: &str) -> Option<Entry> {
+        let mut fields = line.split_whitespace();
+        let started_at = DateTime::parse_from_rfc3339(fields.next()?).ok()?;
+        let seconds = fields.next()?.parse().ok()?;
+        Some(
+            Entry {
+                started_at: started_at.with_timezone(&Local),
+                seconds,
+            }
+        )
+    }
+}
+
+/// The sum of the logged seconds, which fails rather than overflows
+/// on the entries written by hand or logged before it was checked.
+pub fn sum(seconds: impl IntoIterator<Item = i64>) -> io::Result<i64> {
+    seconds
+        .into_iter()
+        .try_fold(0_i64, |total, seconds_| total.checked_add(seconds_))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData,
+                                      "The logged time is too long \
+                                      to be summed up."))
+}
+
+/// The only work session in progress, kept under the root
+/// so that it survives the terminal.
+#[derive(Debug)]
+pub struct Timer {
+    pub id: String,
+    pub started_at: DateTime<Local>,
+}
+
+impl Timer {
+    fn path() -> path::PathBuf {
+        super::get_path_root().join(".timer")
+    }
+
+    pub fn read() -> io::Result<Option<Timer>> {
+        let path = Timer::path();
+        if !path.exists() {
+            return Ok(None);
+        }
+        let contents = fs::read_to_string(&path)?;
+        let mut lines = contents.lines();
+        let id = lines.next();
+        let started_at = lines
+            .next()
+            .and_then(|line| DateTime::parse_from_rfc3339(line).ok());
+        match (id, started_at) {
+            (Some(id), Some(started_at)) => Ok(Some(
+                Timer {
+                    id: id.to_string(),
+                    started_at: started_at.with_timezone(&Local),
+                }
+            )),
+            _ => Err(io::Error::new(io::ErrorKind::InvalidData,
+                                    "The active timer is broken.")),
+        }
+    }
+
+    pub fn start(ticket: &Ticket) -> io::Result<Timer> {
+        if !ticket.path.exists() {
+            return Err(io::Error::new(io::ErrorKind::NotFound,
+                                      "The path does not exist."));
+        }
+        if ticket.is_dir {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                                      "The category cannot be tracked."));
+        }
+        if let Some(timer) = Timer::read()? {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("The ticket {} is already being tracked.", timer.id)));
+        }
+        let timer = Timer {
+            id: format!("{}/{}", ticket.category, ticket.id.as_ref().unwrap()),
+            started_at: Local::now(),
+        };
+        fs::write(Timer::path(),
+                  format!("{}\n{}\n", timer.id, timer.started_at.to_rfc3339()))?;
+        Ok(timer)
+    }
+
+    /// Stop the active timer and log its session against the ticket.
+    pub fn stop() -> io::Result<(Ticket, Entry)> {
+        let timer = match Timer::read()? {
+            Some(timer) => timer,
+            None => {
+                return Err(io::Error::new(io::ErrorKind::NotFound,
+                                          "No ticket is being tracked."));
+            },
+        };
+        let ticket = Ticket::from(timer.id.clone(), None)
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+        if !ticket.path.exists() {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("The tracked ticket {} does not exist anymore.",
+                        timer.id)));
+        }
+        let elapsed = Local::now().signed_duration_since(timer.started_at);
+        let entry = Entry {
+            started_at: timer.started_at,
+            seconds: elapsed.num_seconds().max(0),
+        };
+        ticket.log_time(&entry)?;
+        fs::remove_file(Timer::path())?;
+        Ok((ticket, entry))
+    }
+
+    /// Stop the active timer without logging its session.
+    pub fn discard() -> io::Result<Timer> {
+        let timer = match Timer::read() {
+            Ok(Some(timer)) => timer,
+            Ok(None) => {
+                return Err(io::Error::new(io::ErrorKind::NotFound,
+                                          "No ticket is being tracked."));
+            },
+            // a broken timer is discarded as well
+            Err(_) => Timer {
+                id: String::new(),
+                started_at: Local::now(),
+            },
+        };
+        fs::remove_file(Timer::path())?;
+        Ok(timer)
+    }
+
+    /// Follow the tracked ticket to the identifier it is moved to.
+    pub fn follow(id: &str, dest_id: &str) -> io::Result<()> {
+        match Timer::read() {
+            Ok(Some(timer)) if timer.id == id => {
+                fs::write(Timer::path(),
+                          format!("{}\n{}\n",
+                                  dest_id,
+                                  timer.started_at.to_rfc3339()))
+            },
+            _ => Ok(()),
+        }
+    }
+
+    /// Discard the timer of the ticket, or of any ticket in the category,
+    /// which is removed.
+    pub fn forget(id: &str) -> io::Result<()> {
+        match Timer::read() {
+            Ok(Some(timer)) if timer.id == id
+                || (id.ends_with('/') && timer.id.starts_with(id)) => {
+                fs::remove_file(Timer::path())
+            },
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Parse a duration like `1h30m`, `45m` or `90s` into seconds.
+pub fn parse_duration(str_duration: &str) -> Result<i64, String> {
+    let error = || format!("Invalid duration {}", str_duration);
+    let mut seconds = 0;
+    let mut digits = String::new();
+    for ch in str_duration.chars() {
+        if ch.is_ascii_digit() {
+            digits.push(ch);
+            continue;
+        }
+        let unit = match ch {
+            'h' => 3600,
+            'm' => 60,
+            's' => 1,
+            _ => return Err(error()),
+        };
+        let value: i64 = digits.parse().map_err(|_| error())?;
+        seconds = value
+            .checked_mul(unit)
+            .and_then(|seconds_| seconds_.checked_add(seconds))
+            .ok_or_else(|| format!("Too long duration {}", str_duration))?;
+        digits.clear();
+    }
+    if !digits.is_empty() || seconds == 0 {
+        return Err(error());
+    }
+    Ok(seconds)
+}
+
+pub fn format_duration(seconds: i64) -> String {
+    let hours = seconds / 3600;
+    let minutes = seconds % 3600 / 60;
+    if hours == 0 {
+        format!("{}m", minutes)
+    }
+    else {
+        format!("{}h{:02}m", hours, minutes)
+    }
+}
+
+/// Parse the beginning of a timesheet, such as `today`, `yesterday`,
+/// a weekday for its latest occurrence, or a date like `2019-01-02`.
+pub fn parse_since(since: &str) -> Result<DateTime<Local>, String> {
+    let today = Local::now().date_naive();
+    let date = match &since.to_lowercase()[..] {
+        "today" => today,
+        "yesterday" => today - Duration::days(1),
+        str_since => match Weekday::from_str(str_since) {
+            Ok(weekday) => {
+                let days_back = (7 + today.weekday().num_days_from_monday()
+                                 - weekday.num_days_from_monday()) % 7;
+                today - Duration::days(i64::from(days_back))
+            },
+            Err(_) => chrono::NaiveDate::parse_from_str(str_since, "%Y-%m-%d")
+                .map_err(|_| format!("Invalid date {}", since))?,
+        },
+    };
+    Local
+        .from_local_datetime(&date.and_hms_opt(0, 0, 0).unwrap())
+        .earliest()
+        .ok_or_else(|| format!("Invalid date {}", since))
+}
+
+#[derive(Debug, PartialEq)]
+pub enum GroupBy {
+    Ticket,
+    Category,
+    Day,
+}
+
+impl FromStr for GroupBy {
+    type Err = String;
+
+    fn from_str(by: &str) -> Result<GroupBy, String> {
+        match by {
+            "ticket" => Ok(GroupBy::Ticket),
+            "category" => Ok(GroupBy::Category),
+            "day" => Ok(GroupBy::Day),
+            _ => Err(format!("Invalid grouping {}", by)),
+        }
+    }
+}
+
+/// Sum up the logged time of the tickets since the given moment.
+pub fn timesheet(tickets: &[Ticket],
+                 since: Option<DateTime<Local>>,
+                 by: &GroupBy) -> io::Result<Vec<(String, i64)>> {
+    let mut totals: BTreeMap<String, i64> = BTreeMap::new();
+    for ticket in tickets {
+        for entry in ticket.time_entries()? {
+            if let Some(since_) = since {
+                if entry.started_at < since_ {
+                    continue;
+                }
+            }
+            let key = match by {
+                GroupBy::Ticket => format!("{}/{}",
+                                           ticket.category,
+                                           ticket.id.as_ref().unwrap()),
+                GroupBy::Category => format!("{}/", ticket.category),
+                GroupBy::Day => entry.started_at.format("%Y-%m-%d").to_string(),
+            };
+            let total = totals.entry(key).or_insert(0);
+            *total = sum(vec![*total, entry.seconds])?;
+        }
+    }
+    // the grand total of format_text must fit too
+    sum(totals.values().cloned())?;
+    Ok(totals.into_iter().collect())
+}
+
+pub fn format_text(totals: &[(String, i64)]) -> String {
+    if totals.is_empty() {
+        return String::from("NO TIME LOGGED.");
+    }
+    let width = totals
+        .iter()
+        .map(|(key, _)| key.chars().count())
+        .max()
+        .unwrap()
+        .max("TOTAL".len());
+    let mut lines: Vec<String> = totals
+        .iter()
+        .map(|(key, seconds)| format!("{:width$}  {:>7}",
+                                      key,
+                                      format_duration(*seconds),
+                                      width = width))
+        .collect();
+    // checked by timesheet, so the saturation is only for the others
+    let total = totals
+        .iter()
+        .fold(0_i64, |total, (_, seconds)| total.saturating_add(*seconds));
+    lines.push(String::new());
+    lines.push(format!("{:width$}  {:>7}",
+                       "TOTAL",
+                       format_duration(total),
+                       width = width));
+    lines.join("\n")
+}
+
+pub fn format_csv(totals: &[(String, i64)], by: &GroupBy) -> String {
+    let column = match by {
+        GroupBy::Ticket => "ticket",
+        GroupBy::Category => "category",
+        GroupBy::Day => "day",
+    };
+    let mut lines = vec![format!("{},hours", column)];
+    for (key, seconds) in totals {
+        lines.push(format!("{},{:.2}",
+                           csv::field(key),
+                           *seconds as f64 / 3600.0));
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Datelike, Weekday};
+
+    #[test]
+    fn parse_durations() {
+        assert_eq!(super::parse_duration("1h30m"), Ok(5400));
+        assert_eq!(super::parse_duration("45m"), Ok(2700));
+        assert_eq!(super::parse_duration("2h"), Ok(7200));
+        assert_eq!(super::parse_duration("90s"), Ok(90));
+    }
+
+    #[test]
+    fn fail_to_parse_bad_durations() {
+        assert_eq!(super::parse_duration("90"),
+                   Err(String::from("Invalid duration 90")));
+        assert!(super::parse_duration("1d").is_err());
+        assert!(super::parse_duration("h").is_err());
+        assert!(super::parse_duration("0m").is_err());
+        assert_eq!(super::parse_duration("99999999999999999h"),
+                   Err(String::from("Too long duration 99999999999999999h")));
+        assert!(super::parse_duration("9223372036854775807s1s").is_err());
+    }
+
+    #[test]
+    fn format_durations() {
+        assert_eq!(super::format_duration(5400), "1h30m");
+        assert_eq!(super::format_duration(2700), "45m");
+        assert_eq!(super::format_duration(7200 + 60 * 5 + 59), "2h05m");
+    }
+
+    #[test]
+    fn parse_since_a_weekday() {
+        let since = super::parse_since("monday").unwrap();
+        assert_eq!(since.weekday(), Weekday::Mon);
+        assert!(since <= chrono::Local::now());
+        assert!(chrono::Local::now() - since < chrono::Duration::days(7));
+    }
+
+    #[test]
+    fn parse_since_a_date() {
+        let since = super::parse_since("2019-01-02").unwrap();
+        assert_eq!(since.format("%F %T").to_string(), "2019-01-02 00:00:00");
+        assert!(super::parse_since("someday").is_err());
+    }
+
+    #[test]
+    fn fail_to_sum_up_too_long_time() {
+        super::super::purge().unwrap_or(());
+        super::super::initialize_root().unwrap_or(());
+        let path_root = super::super::get_path_root();
+        std::fs::create_dir_all(path_root.join("open/.a")).unwrap();
+        std::fs::create_dir_all(path_root.join("open/.b")).unwrap();
+        std::fs::write(path_root.join("open/a"), "A").unwrap();
+        std::fs::write(path_root.join("open/b"), "B").unwrap();
+        let line = format!("2019-01-02T03:04:05+00:00 {}\n", i64::MAX - 1);
+        std::fs::write(path_root.join("open/.a/timelog"), &line).unwrap();
+        std::fs::write(path_root.join("open/.b/timelog"), &line).unwrap();
+
+        let category = super::Ticket::from(String::from("open/"), None)
+            .unwrap();
+        let tickets = category.collect().unwrap();
+        assert_eq!(super::timesheet(&tickets, None, &super::GroupBy::Ticket)
+                   .unwrap_err()
+                   .to_string(),
+                   "The logged time is too long to be summed up.");
+        assert!(super::timesheet(&tickets, None, &super::GroupBy::Day)
+                .is_err());
+        assert!(super::super::estimate::rollup(
+            &tickets, &super::super::estimate::Unit::Points).is_err());
+        // the category is still shown, without the total
+        assert!(!format!("{}", category).contains("TOTAL"));
+        assert_eq!(super::sum(vec![i64::MAX - 1, 1]).unwrap(), i64::MAX);
+    }
+
+    #[test]
+    fn format_timesheet_as_csv() {
+        let totals = vec![(String::from("open/a,b"), 5400),
+                          (String::from("open/c"), 900)];
+        assert_eq!(super::format_csv(&totals, &super::GroupBy::Ticket),
+                   "ticket,hours\n\"open/a,b\",1.50\nopen/c,0.25");
+    }
+}
@@ -0,0 +1,597 @@
+use std::fs;
+use std::io::{self, IsTerminal, Write};
+use std::path;
+use std::time;
+
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind,
+                       KeyModifiers};
+use crossterm::{cursor, execute, queue, style, terminal};
+
+use super::board::fit;
+use super::ticket::{self, Ticket};
+
+const GAP: &str = " | ";
+const HELP: &str = "q:quit  tab:switch  j/k:move  /:search  n:new  \
+                    e:edit  m:move  d:remove  r:reload";
+
+#[derive(Debug, PartialEq)]
+enum Pane {
+    Categories,
+    Tickets,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum Prompt {
+    New,
+    Move,
+}
+
+#[derive(Debug, PartialEq)]
+enum Mode {
+    Normal,
+    Search,
+    Prompt(Prompt, String),
+    Confirm,
+}
+
+/// What a key asks beyond moving around, which may touch the store.
+#[derive(Debug, PartialEq)]
+enum Command {
+    Nothing,
+    Quit,
+    New(String),
+    Edit,
+    Move(String),
+    Remove,
+}
+
+/// A piece of a line on the screen, highlighted or not.
+type Segment = (String, bool);
+
+pub struct App {
+    categories: Vec<Ticket>,
+    tickets: Vec<Ticket>,
+    pane: Pane,
+    index_category: usize,
+    index_ticket: usize,
+    search: String,
+    mode: Mode,
+    status: String,
+    fingerprint: Vec<(path::PathBuf, Option<time::SystemTime>)>,
+}
+
+/// The modification times of everything under the root,
+/// to tell whether the store changed on disk.
+fn fingerprint() -> io::Result<Vec<(path::PathBuf, Option<time::SystemTime>)>> {
+    let mut stamps = Vec::new();
+    for category in super::collect_categories()? {
+        stamps.push((category.path.clone(), category.modified_at));
+        for entry in fs::read_dir(&category.path)? {
+            let entry = entry?;
+            if ticket::is_sidecar(&entry.file_name()) {
+                continue;
+            }
+            let modified = entry.metadata()?.modified().ok();
+            stamps.push((entry.path(), modified));
+        }
+    }
+    Ok(stamps)
+}
+
+impl App {
+    pub fn load() -> io::Result<App> {
+        let mut app = App {
+            categories: Vec::new(),
+            tickets: Vec::new(),
+            pane: Pane::Categories,
+            index_category: 0,
+            index_ticket: 0,
+            search: String::new(),
+            mode: Mode::Normal,
+            status: String::new(),
+            fingerprint: Vec::new(),
+        };
+        app.reload()?;
+        Ok(app)
+    }
+
+    /// Read the store again, keeping the selections where possible.
+    fn reload(&mut self) -> io::Result<()> {
+        self.categories = super::collect_categories()?;
+        self.fingerprint = fingerprint()?;
+        if self.index_category >= self.categories.len() {
+            self.index_category = self.categories.len().saturating_sub(1);
+        }
+        self.load_tickets()
+    }
+
+    fn load_tickets(&mut self) -> io::Result<()> {
+        self.tickets = match self.categories.get(self.index_category) {
+            Some(category) => category.collect()?,
+            None => Vec::new(),
+        };
+        if !self.search.is_empty() {
+            let search = self.search.to_lowercase();
+            self.tickets.retain(|ticket| {
+                format!("{}", ticket).to_lowercase().contains(&search)
+            });
+        }
+        if self.index_ticket >= self.tickets.len() {
+            self.index_ticket = self.tickets.len().saturating_sub(1);
+        }
+        Ok(())
+    }
+
+    fn changed_on_disk(&self) -> io::Result<bool> {
+        Ok(fingerprint()? != self.fingerprint)
+    }
+
+    fn selected_category(&self) -> Option<&Ticket> {
+        self.categories.get(self.index_category)
+    }
+
+    fn selected_ticket(&self) -> Option<&Ticket> {
+        self.tickets.get(self.index_ticket)
+    }
+
+    /// The ticket or category to remove, depending on the pane.
+    fn selected(&self) -> Option<&Ticket> {
+        match self.pane {
+            Pane::Categories => self.selected_category(),
+            Pane::Tickets => self.selected_ticket(),
+        }
+    }
+
+    fn move_selection(&mut self, down: bool) -> io::Result<()> {
+        let (index, len) = match self.pane {
+            Pane::Categories => (&mut self.index_category, self.categories.len()),
+            Pane::Tickets => (&mut self.index_ticket, self.tickets.len()),
+        };
+        if down && *index + 1 < len {
+            *index += 1;
+        }
+        else if !down && *index > 0 {
+            *index -= 1;
+        }
+        else {
+            return Ok(());
+        }
+        if self.pane == Pane::Categories {
+            self.index_ticket = 0;
+            self.load_tickets()?;
+        }
+        Ok(())
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) -> io::Result<Command> {
+        if key.code == KeyCode::Char('c')
+            && key.modifiers.contains(KeyModifiers::CONTROL) {
+            return Ok(Command::Quit);
+        }
+        self.status.clear();
+        match &mut self.mode {
+            Mode::Search => {
+                match key.code {
+                    KeyCode::Char(ch) => self.search.push(ch),
+                    KeyCode::Backspace => {
+                        self.search.pop();
+                    },
+                    KeyCode::Enter => self.mode = Mode::Normal,
+                    KeyCode::Esc => {
+                        self.search.clear();
+                        self.mode = Mode::Normal;
+                    },
+                    _ => (),
+                };
+                self.index_ticket = 0;
+                self.load_tickets()?;
+                Ok(Command::Nothing)
+            },
+            Mode::Prompt(prompt, input) => {
+                match key.code {
+                    KeyCode::Char(ch) => input.push(ch),
+                    KeyCode::Backspace => {
+                        input.pop();
+                    },
+                    KeyCode::Enter => {
+                        let command = match prompt {
+                            Prompt::New => Command::New(input.clone()),
+                            Prompt::Move => Command::Move(input.clone()),
+                        };
+                        self.mode = Mode::Normal;
+                        return Ok(command);
+                    },
+                    KeyCode::Esc => self.mode = Mode::Normal,
+                    _ => (),
+                };
+                Ok(Command::Nothing)
+            },
+            Mode::Confirm => {
+                self.mode = Mode::Normal;
+                match key.code {
+                    KeyCode::Char('y') => Ok(Command::Remove),
+                    _ => Ok(Command::Nothing),
+                }
+            },
+            Mode::Normal => {
+                match key.code {
+                    KeyCode::Char('q') => return Ok(Command::Quit),
+                    KeyCode::Esc if !self.search.is_empty() => {
+                        self.search.clear();
+                        self.load_tickets()?;
+                    },
+                    KeyCode::Esc => return Ok(Command::Quit),
+                    KeyCode::Tab | KeyCode::Char('h') | KeyCode::Char('l')
+                        | KeyCode::Left | KeyCode::Right => {
+                        self.pane = match self.pane {
+                            Pane::Categories => Pane::Tickets,
+                            Pane::Tickets => Pane::Categories,
+                        };
+                    },
+                    KeyCode::Char('j') | KeyCode::Down => {
+                        self.move_selection(true)?;
+                    },
+                    KeyCode::Char('k') | KeyCode::Up => {
+                        self.move_selection(false)?;
+                    },
+                    KeyCode::Char('/') => {
+                        self.pane = Pane::Tickets;
+                        self.mode = Mode::Search;
+                    },
+                    KeyCode::Char('n') => {
+                        self.mode = Mode::Prompt(Prompt::New, String::new());
+                    },
+                    KeyCode::Char('e') | KeyCode::Enter
+                        if self.selected_ticket().is_some() => {
+                        return Ok(Command::Edit);
+                    },
+                    KeyCode::Char('m') if self.selected_ticket().is_some() => {
+                        self.mode = Mode::Prompt(Prompt::Move, String::new());
+                    },
+                    KeyCode::Char('d') if self.selected().is_some() => {
+                        self.mode = Mode::Confirm;
+                    },
+                    KeyCode::Char('r') => self.reload()?,
+                    _ => (),
+                };
+                Ok(Command::Nothing)
+            },
+        }
+    }
+
+    /// Run the command on the store, reporting how it went in the status.
+    fn execute(&mut self, command: &Command) -> io::Result<()> {
+        let result = match command {
+            Command::New(name) => {
+                // a name with the trailing slash makes a category
+                let id = if name.ends_with('/') {
+                    name.clone()
+                } else {
+                    match self.selected_category() {
+                        Some(category) => format!("{}/{}",
+                                                  category.category,
+                                                  name),
+                        None => name.clone(),
+                    }
+                };
+                ticket::Ticket::from(id, None)
+                    .map_err(|error| io::Error::new(io::ErrorKind::InvalidInput,
+                                                    error))
+                    .and_then(|ticket| ticket.new())
+            },
+            Command::Edit => match self.selected_ticket() {
+                Some(selected) => {
+                    let id = format!("{}/{}",
+                                     selected.category,
+                                     selected.id.as_ref().unwrap());
+                    Ticket::from(id, None).unwrap().edit()
+                },
+                None => Ok(()),
+            },
+            Command::Move(dest_id) => match self.selected_ticket() {
+                Some(selected) => ticket::Ticket::from(dest_id.clone(), None)
+                    .map_err(|error| io::Error::new(io::ErrorKind::InvalidInput,
+                                                    error))
+                    .and_then(|dest_ticket| selected.move_(&dest_ticket)),
+                None => Ok(()),
+            },
+            Command::Remove => match self.selected() {
+                Some(selected) => selected.remove(),
+                None => Ok(()),
+            },
+            Command::Nothing | Command::Quit => return Ok(()),
+        };
+        self.status = match result {
+            Ok(()) => String::from("SUCCEEDED."),
+            Err(error) => format!("ERROR: {}", error),
+        };
+        self.reload()
+    }
+
+    fn render_categories(&self) -> Vec<Segment> {
+        self.categories
+            .iter()
+            .enumerate()
+            .map(|(index, category)| {
+                (format!("{}/", category.category),
+                 index == self.index_category && self.pane == Pane::Categories)
+            })
+            .collect()
+    }
+
+    fn render_tickets(&self) -> Vec<Segment> {
+        if self.tickets.is_empty() {
+            return vec![(String::from("NO TICKETS."), false)];
+        }
+        self.tickets
+            .iter()
+            .enumerate()
+            .map(|(index, ticket)| {
+                (format!("{}", ticket),
+                 index == self.index_ticket && self.pane == Pane::Tickets)
+            })
+            .collect()
+    }
+
+    fn render_preview(&self) -> Vec<Segment> {
+        let ticket = match self.selected_ticket() {
+            Some(ticket) => ticket,
+            None => return Vec::new(),
+        };
+        let mut lines = vec![
+            (ticket.title.clone().unwrap_or_default(), false),
+            (String::new(), false),
+        ];
+        if let Some(message) = &ticket.message {
+            for line in message.lines() {
+                lines.push((line.to_string(), false));
+            }
+        }
+        lines
+    }
+
+    /// Lay out the whole screen in lines of segments.
+    fn render(&self, width: usize, height: usize) -> Vec<Vec<Segment>> {
+        let width_categories = (width / 5).max(12);
+        let width_tickets = (width * 2 / 5).max(20);
+        let width_preview = width
+            .saturating_sub(width_categories + width_tickets + GAP.len() * 2)
+            .max(1);
+        let height_body = height.saturating_sub(2);
+
+        let title = if self.search.is_empty() {
+            String::from("tickets :: tui")
+        } else {
+            format!("tickets :: tui :: /{}", self.search)
+        };
+        let mut screen = vec![vec![(fit(&title, width), false)]];
+
+        let panes = [
+            (self.render_categories(), width_categories, self.index_category),
+            (self.render_tickets(), width_tickets, self.index_ticket),
+            (self.render_preview(), width_preview, 0),
+        ];
+        for row in 0..height_body {
+            let mut line: Vec<Segment> = Vec::new();
+            for (index_pane, (segments, width_pane, selected)) in
+                panes.iter().enumerate() {
+                if index_pane > 0 {
+                    line.push((String::from(GAP), false));
+                }
+                // scroll to keep the selection on the screen
+                let offset = (selected + 1).saturating_sub(height_body);
+                match segments.get(row + offset) {
+                    Some((text, highlighted)) => {
+                        line.push((fit(text, *width_pane), *highlighted));
+                    },
+                    None => line.push((" ".repeat(*width_pane), false)),
+                };
+            }
+            screen.push(line);
+        }
+
+        let bottom = match &self.mode {
+            Mode::Search => format!("/{}", self.search),
+            Mode::Prompt(Prompt::New, input) => {
+                format!("New ticket (or category/) name: {}", input)
+            },
+            Mode::Prompt(Prompt::Move, input) => format!("Move to: {}", input),
+            Mode::Confirm => match self.selected() {
+                Some(selected) => format!(
+                    "Remove {}/{}? (y/n)",
+                    selected.category,
+                    selected.id.as_ref().map(|id| &id[..]).unwrap_or("")),
+                None => String::new(),
+            },
+            Mode::Normal if !self.status.is_empty() => self.status.clone(),
+            Mode::Normal => String::from(HELP),
+        };
+        screen.push(vec![(fit(&bottom, width), false)]);
+        screen
+    }
+
+    fn draw(&self, out: &mut impl Write) -> io::Result<()> {
+        let (width, height) = terminal::size()?;
+        queue!(out, terminal::Clear(terminal::ClearType::All))?;
+        let screen = self.render(width as usize, height as usize);
+        for (row, line) in screen.iter().enumerate() {
+            queue!(out, cursor::MoveTo(0, row as u16))?;
+            for (text, highlighted) in line {
+                if *highlighted {
+                    queue!(out,
+                           style::SetAttribute(style::Attribute::Reverse),
+                           style::Print(text),
+                           style::SetAttribute(style::Attribute::Reset))?;
+                }
+                else {
+                    queue!(out, style::Print(text))?;
+                }
+            }
+        }
+        out.flush()
+    }
+}
+
+fn enter(out: &mut impl Write) -> io::Result<()> {
+    terminal::enable_raw_mode()?;
+    execute!(out, terminal::EnterAlternateScreen, cursor::Hide)
+}
+
+fn leave(out: &mut impl Write) -> io::Result<()> {
+    execute!(out, cursor::Show, terminal::LeaveAlternateScreen)?;
+    terminal::disable_raw_mode()
+}
+
+/// Run the interactive interface until quitting.
+pub fn run() -> io::Result<()> {
+    if !io::stdout().is_terminal() {
+        return Err(io::Error::other("The terminal is not interactive."));
+    }
+    // the interface draws its own styles over the plain texts
+    super::style::set_choice(super::style::Choice::Never);
+    let mut app = App::load()?;
+    let mut out = io::stdout();
+    enter(&mut out)?;
+    let result = event_loop(&mut app, &mut out);
+    leave(&mut out)?;
+    result
+}
+
+fn event_loop(app: &mut App, out: &mut io::Stdout) -> io::Result<()> {
+    loop {
+        app.draw(out)?;
+        if !event::poll(time::Duration::from_millis(500))? {
+            if app.changed_on_disk()? {
+                app.reload()?;
+            }
+            continue;
+        }
+        let key = match event::read()? {
+            Event::Key(key) if key.kind == KeyEventKind::Press => key,
+            _ => continue,
+        };
+        let command = app.handle_key(key)?;
+        match command {
+            Command::Quit => return Ok(()),
+            Command::Nothing => (),
+            // the editor needs the terminal back
+            Command::New(_) | Command::Edit => {
+                leave(out)?;
+                let result = app.execute(&command);
+                enter(out)?;
+                result?;
+            },
+            _ => app.execute(&command)?,
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    fn text(line: &[super::Segment]) -> String {
+        line.iter().map(|(text, _)| &text[..]).collect::<String>()
+    }
+
+    fn prepare() {
+        super::super::purge().unwrap_or(());
+        super::super::initialize_root().unwrap_or(());
+        let path_root = super::super::get_path_root();
+        super::fs::create_dir(path_root.join("open/")).unwrap();
+        super::fs::create_dir(path_root.join("done/")).unwrap();
+        super::fs::write(path_root.join("open/ID-1"), "Fix the bug\n\nSoon")
+            .unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(10));
+        super::fs::write(path_root.join("open/ID-2"), "Write docs").unwrap();
+    }
+
+    #[test]
+    fn navigate_and_render() {
+        prepare();
+        let mut app = super::App::load().unwrap();
+        let index_open = app.categories
+            .iter()
+            .position(|category| category.category == "open")
+            .unwrap();
+        while app.index_category < index_open {
+            app.handle_key(key(KeyCode::Char('j'))).unwrap();
+        }
+        app.handle_key(key(KeyCode::Tab)).unwrap();
+        assert_eq!(app.pane, super::Pane::Tickets);
+        assert_eq!(app.tickets.len(), 2);
+
+        let screen = app.render(80, 6);
+        assert_eq!(screen.len(), 6);
+        assert!(text(&screen[1]).contains("[ID-1]Fix the bug"));
+        assert_eq!(screen[1][4].0.trim_end(), "Fix the bug");
+        assert!(screen[1][2].1);
+        assert_eq!(text(&screen[5]), super::fit(super::HELP, 80));
+
+        app.handle_key(key(KeyCode::Down)).unwrap();
+        assert_eq!(app.selected_ticket().unwrap().id,
+                   Some(String::from("ID-2")));
+    }
+
+    #[test]
+    fn search_incrementally() {
+        prepare();
+        let mut app = super::App::load().unwrap();
+        while app.selected_category().unwrap().category != "open" {
+            app.handle_key(key(KeyCode::Char('j'))).unwrap();
+        }
+        app.handle_key(key(KeyCode::Char('/'))).unwrap();
+        for ch in "docs".chars() {
+            app.handle_key(key(KeyCode::Char(ch))).unwrap();
+        }
+        assert_eq!(app.tickets.len(), 1);
+        assert_eq!(app.tickets[0].id, Some(String::from("ID-2")));
+        app.handle_key(key(KeyCode::Esc)).unwrap();
+        assert_eq!(app.tickets.len(), 2);
+    }
+
+    #[test]
+    fn move_and_remove_with_confirmation() {
+        prepare();
+        let mut app = super::App::load().unwrap();
+        while app.selected_category().unwrap().category != "open" {
+            app.handle_key(key(KeyCode::Char('j'))).unwrap();
+        }
+        app.handle_key(key(KeyCode::Tab)).unwrap();
+
+        app.handle_key(key(KeyCode::Char('m'))).unwrap();
+        let mut command = super::Command::Nothing;
+        for ch in "done/".chars() {
+            command = app.handle_key(key(KeyCode::Char(ch))).unwrap();
+        }
+        assert_eq!(command, super::Command::Nothing);
+        command = app.handle_key(key(KeyCode::Enter)).unwrap();
+        assert_eq!(command, super::Command::Move(String::from("done/")));
+        app.execute(&command).unwrap();
+        assert_eq!(app.status, "SUCCEEDED.");
+        assert_eq!(app.tickets.len(), 1);
+
+        app.handle_key(key(KeyCode::Char('d'))).unwrap();
+        let command = app.handle_key(key(KeyCode::Char('n'))).unwrap();
+        assert_eq!(command, super::Command::Nothing);
+        app.handle_key(key(KeyCode::Char('d'))).unwrap();
+        let command = app.handle_key(key(KeyCode::Char('y'))).unwrap();
+        assert_eq!(command, super::Command::Remove);
+        app.execute(&command).unwrap();
+        assert!(app.tickets.is_empty());
+    }
+
+    #[test]
+    fn notice_changes_on_disk() {
+        prepare();
+        let app = super::App::load().unwrap();
+        assert!(!app.changed_on_disk().unwrap());
+        let path_root = super::super::get_path_root();
+        super::fs::write(path_root.join("done/ID-3"), "Appeared").unwrap();
+        assert!(app.changed_on_disk().unwrap());
+    }
+}
@@ -0,0 +1,298 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io;
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+use chrono::{DateTime, Local};
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use serde_json::{json, Value};
+
+/// How long the notifications are gathered, for an editor or a `move`
+/// to finish writing before the store is read.
+const DEBOUNCE: Duration = Duration::from_millis(100);
+/// How many times the store is read again when a file vanishes
+/// while being read.
+const RETRIES: usize = 5;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change {
+    Created,
+    Edited,
+    /// Moved from the ticket of the identifier.
+    Moved(String),
+    Removed,
+}
+
+#[derive(Debug, Clone)]
+pub struct Event {
+    pub change: Change,
+    pub id: String,
+    pub title: String,
+    pub at: DateTime<Local>,
+}
+
+impl Event {
+    /// The object of a line of NDJSON.
+    pub fn to_json(&self) -> Value {
+        let (name, from) = match &self.change {
+            Change::Created => ("created", None),
+            Change::Edited => ("edited", None),
+            Change::Moved(from) => ("moved", Some(from)),
+            Change::Removed => ("removed", None),
+        };
+        let mut value = json!({
+            "event": name,
+            "id": self.id,
+            "title": self.title,
+            "at": self.at.to_rfc3339(),
+        });
+        if let Some(from_) = from {
+            value["from"] = json!(from_);
+        }
+        value
+    }
+}
+
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.change {
+            Change::Created => write!(f, "CREATED {}", self.id)?,
+            Change::Edited => write!(f, "EDITED {}", self.id)?,
+            Change::Moved(from) => write!(f, "MOVED {} => {}", from, self.id)?,
+            Change::Removed => write!(f, "REMOVED {}", self.id)?,
+        };
+        write!(f, " :: {}", self.title)
+    }
+}
+
+/// What a ticket looks like at a moment.
+#[derive(Debug, Clone, PartialEq)]
+struct State {
+    title: String,
+    content: String,
+    modified_at: Option<SystemTime>,
+}
+
+type Snapshot = BTreeMap<String, State>;
+
+fn snapshot(id: &Option<String>) -> io::Result<Snapshot> {
+    let mut snapshot = Snapshot::new();
+    for category in super::resolve_categories(id.clone())? {
+        for ticket in category.collect()? {
+            let title = ticket.title.clone().unwrap_or_default();
+            let content = match &ticket.message {
+                Some(message) => format!("{}\n\n{}", title, message),
+                None => title.clone(),
+            };
+            snapshot.insert(format!("{}/{}",
+                                    ticket.category,
+                                    ticket.id.clone().unwrap_or_default()),
+                            State {
+                                title,
+                                content,
+                                modified_at: ticket.modified_at,
+                            });
+        }
+    }
+    Ok(snapshot)
+}
+
+fn name_of(id: &str) -> &str {
+    id.rsplit('/').next().unwrap_or(id)
+}
+
+/// The changes between the snapshots. A ticket gone with the same
+/// content as a new one is taken as moved, preferably under its name.
+fn diff(old: &Snapshot, new: &Snapshot, at: DateTime<Local>) -> Vec<Event> {
+    let mut gone: Vec<&String> = old.keys()
+        .filter(|id| !new.contains_key(*id))
+        .collect();
+    let mut events = Vec::new();
+    for (id, state) in new {
+        let change = match old.get(id) {
+            Some(state_) if state_ == state => continue,
+            Some(_) => Change::Edited,
+            None => {
+                let same_content = |id_: &&String| {
+                    old[*id_].content == state.content
+                };
+                let index = gone.iter()
+                    .position(|id_| same_content(id_)
+                              && name_of(id_) == name_of(id))
+                    .or_else(|| gone.iter().position(same_content));
+                match index {
+                    Some(index_) => Change::Moved(gone.remove(index_).clone()),
+                    None => Change::Created,
+                }
+            },
+        };
+        events.push(Event {
+            change,
+            id: id.clone(),
+            title: state.title.clone(),
+            at,
+        });
+    }
+    for id in gone {
+        events.push(Event {
+            change: Change::Removed,
+            id: id.clone(),
+            title: old[id].title.clone(),
+            at,
+        });
+    }
+    events
+}
+
+/// The store being watched, or a category of it.
+pub struct Watch {
+    id: Option<String>,
+    // notifies as long as it lives
+    _watcher: RecommendedWatcher,
+    receiver: mpsc::Receiver<notify::Result<notify::Event>>,
+    snapshot: Snapshot,
+}
+
+impl Watch {
+    pub fn new(id: Option<String>) -> io::Result<Watch> {
+        let snapshot = snapshot(&id)?;
+        let (sender, receiver) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(sender)
+            .map_err(io::Error::other)?;
+        watcher.watch(&super::get_path_root(), RecursiveMode::Recursive)
+            .map_err(io::Error::other)?;
+        Ok(Watch { id, _watcher: watcher, receiver, snapshot })
+    }
+
+    /// Read the store again after a file vanished while being read.
+    fn refresh(&mut self) -> io::Result<Snapshot> {
+        let mut result = snapshot(&self.id);
+        for _ in 0..RETRIES {
+            match result {
+                Err(ref error) if error.kind() == io::ErrorKind::NotFound => {
+                    thread::sleep(DEBOUNCE);
+                    result = snapshot(&self.id);
+                },
+                _ => break,
+            }
+        }
+        result
+    }
+
+    /// Pass the changes of the tickets to emit as they come, and the
+    /// errors to fail without stopping, until the notifications stop.
+    pub fn run(mut self,
+               mut emit: impl FnMut(&Event),
+               mut fail: impl FnMut(&io::Error)) {
+        while let Ok(notification) = self.receiver.recv() {
+            let notification = match notification {
+                Ok(notification_) => notification_,
+                Err(error) => {
+                    fail(&io::Error::other(error));
+                    continue;
+                },
+            };
+            if let EventKind::Access(_) = notification.kind {
+                continue;
+            }
+            while self.receiver.recv_timeout(DEBOUNCE).is_ok() {}
+            let result = self.refresh();
+            update(&mut self.snapshot, result, &mut emit, &mut fail);
+        }
+    }
+}
+
+/// Emit the changes into the snapshot read, or fail keeping the previous
+/// snapshot, for the changes to be caught up by the next one.
+fn update(snapshot: &mut Snapshot,
+          result: io::Result<Snapshot>,
+          emit: &mut impl FnMut(&Event),
+          fail: &mut impl FnMut(&io::Error)) {
+    match result {
+        Ok(snapshot_) => {
+            for event in diff(snapshot, &snapshot_, Local::now()) {
+                emit(&event);
+            }
+            *snapshot = snapshot_;
+        },
+        Err(error) => fail(&error),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    fn state(content: &str, modified_secs: u64) -> super::State {
+        super::State {
+            title: content.lines().next().unwrap_or("").to_string(),
+            content: content.to_string(),
+            modified_at: Some(std::time::UNIX_EPOCH
+                              + std::time::Duration::from_secs(modified_secs)),
+        }
+    }
+
+    #[test]
+    fn diff_snapshots() {
+        let mut old = super::Snapshot::new();
+        old.insert(String::from("open/1"), state("Fix it", 1));
+        old.insert(String::from("open/2"), state("Ship it", 1));
+        old.insert(String::from("open/3"), state("Drop it", 1));
+        old.insert(String::from("open/4"), state("Keep it", 1));
+        let mut new = super::Snapshot::new();
+        new.insert(String::from("open/1"), state("Fix it now", 2));
+        new.insert(String::from("done/2"), state("Ship it", 1));
+        new.insert(String::from("open/4"), state("Keep it", 1));
+        new.insert(String::from("open/5"), state("Test it", 2));
+
+        let lines: Vec<String> = super::diff(&old, &new, chrono::Local::now())
+            .iter()
+            .map(|event| event.to_string())
+            .collect();
+        assert_eq!(lines, vec!["MOVED open/2 => done/2 :: Ship it",
+                               "EDITED open/1 :: Fix it now",
+                               "CREATED open/5 :: Test it",
+                               "REMOVED open/3 :: Drop it"]);
+    }
+
+    #[test]
+    fn keep_the_snapshot_on_errors() {
+        let mut snapshot = super::Snapshot::new();
+        snapshot.insert(String::from("open/1"), state("Fix it", 1));
+        let mut lines: Vec<String> = Vec::new();
+        let mut errors: Vec<String> = Vec::new();
+        super::update(&mut snapshot,
+                      Err(std::io::Error::other("Permission denied")),
+                      &mut |event| lines.push(event.to_string()),
+                      &mut |error| errors.push(error.to_string()));
+        assert_eq!(errors, vec!["Permission denied"]);
+        assert!(snapshot.contains_key("open/1"));
+
+        let mut new = super::Snapshot::new();
+        new.insert(String::from("open/2"), state("Ship it", 2));
+        super::update(&mut snapshot,
+                      Ok(new),
+                      &mut |event| lines.push(event.to_string()),
+                      &mut |error| errors.push(error.to_string()));
+        assert_eq!(lines, vec!["CREATED open/2 :: Ship it",
+                               "REMOVED open/1 :: Fix it"]);
+        assert!(snapshot.contains_key("open/2"));
+    }
+
+    #[test]
+    fn events_as_json() {
+        let event = super::Event {
+            change: super::Change::Moved(String::from("open/2")),
+            id: String::from("done/2"),
+            title: String::from("Ship it"),
+            at: chrono::Local::now(),
+        };
+        let value = event.to_json();
+        assert_eq!(value["event"], "moved");
+        assert_eq!(value["from"], "open/2");
+        assert_eq!(value["id"], "done/2");
+        assert_eq!(value["title"], "Ship it");
+        let event = super::Event { change: super::Change::Removed, ..event };
+        assert!(event.to_json().get("from").is_none());
+    }
+}
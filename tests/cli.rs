@@ -4,14 +4,38 @@ use std::process::Command;
 use std::time;
 use std::thread;
 use assert_cmd::prelude::*;
+use predicates::prelude::*;
+
+/// The home of each test, named after the thread running it, with
+/// the store and the config in it, so that the tests never touch
+/// the ones of the user and run in parallel.
+fn home() -> std::path::PathBuf {
+    let thread = thread::current();
+    let name = thread.name().unwrap_or("main").replace("::", "-");
+    std::env::temp_dir().join(format!("tickets-cli-{}", name))
+}
+
+fn purge() -> std::io::Result<()> {
+    let home = home();
+    if home.exists() {
+        std::fs::remove_dir_all(&home)?;
+    }
+    std::fs::create_dir_all(home)
+}
 
-use tickets::purge;
+fn main_binary() -> Command {
+    let mut cmd = Command::main_binary().unwrap();
+    cmd
+        .env("HOME", home())
+        .env("TICKETS_ROOT", home().join(".tickets"));
+    cmd
+}
 
 // ================= INIT =================
 #[test]
 fn the_very_first_run_requires_init() {
     purge().unwrap_or(());
-    let mut cmd = Command::main_binary().unwrap();
+    let mut cmd = main_binary();
     cmd
         .assert()
         .failure()
@@ -21,7 +45,7 @@ fn the_very_first_run_requires_init() {
 #[test]
 fn init_successfully() {
     purge().unwrap_or(());
-    let mut cmd = Command::main_binary().unwrap();
+    let mut cmd = main_binary();
     cmd
         .arg("init")
         .assert()
@@ -35,7 +59,7 @@ SUCCEEDED.
 #[test]
 fn fail_to_re_init() {
     purge().unwrap_or(());
-    let mut cmd = Command::main_binary().unwrap();
+    let mut cmd = main_binary();
     cmd
         .arg("init")
         .assert()
@@ -44,23 +68,23 @@ fn fail_to_re_init() {
     cmd
         .assert()
         .failure()
-        .stderr("tickets :: init
+        .stderr(format!("tickets :: init
 
-ERROR: The root ~/.tickets already exists.
-");
+ERROR: The root {} already exists.
+", home().join(".tickets").display()));
 }
 
 // ================= NEW =================
 #[test]
 fn create_a_new_category() {
     purge().unwrap_or(());
-    let mut cmd = Command::main_binary().unwrap();
+    let mut cmd = main_binary();
     cmd
         .arg("init")
         .assert()
         .success();
 
-    let mut cmd = Command::main_binary().unwrap();
+    let mut cmd = main_binary();
     cmd
         .arg("new")
         .arg("test/")
@@ -75,19 +99,19 @@ SUCCEEDED.
 #[test]
 fn do_not_create_the_same_category_again() {
     purge().unwrap_or(());
-    let mut cmd = Command::main_binary().unwrap();
+    let mut cmd = main_binary();
     cmd
         .arg("init")
         .assert()
         .success();
-    let mut cmd = Command::main_binary().unwrap();
+    let mut cmd = main_binary();
     cmd
         .arg("new")
         .arg("test/")
         .assert()
         .success();
 
-    let mut cmd = Command::main_binary().unwrap();
+    let mut cmd = main_binary();
     cmd
         .arg("new")
         .arg("test/")
@@ -102,19 +126,19 @@ ERROR: The path already exists.
 #[test]
 fn create_a_new_ticket() {
     purge().unwrap_or(());
-    let mut cmd = Command::main_binary().unwrap();
+    let mut cmd = main_binary();
     cmd
         .arg("init")
         .assert()
         .success();
-    let mut cmd = Command::main_binary().unwrap();
+    let mut cmd = main_binary();
     cmd
         .arg("new")
         .arg("test/")
         .assert()
         .success();
 
-    let mut cmd = Command::main_binary().unwrap();
+    let mut cmd = main_binary();
     cmd
         .arg("new")
         .arg("test/ID-1234")
@@ -131,13 +155,13 @@ SUCCEEDED.
 #[test]
 fn fail_to_create_a_ticket_without_category() {
     purge().unwrap_or(());
-    let mut cmd = Command::main_binary().unwrap();
+    let mut cmd = main_binary();
     cmd
         .arg("init")
         .assert()
         .success();
 
-    let mut cmd = Command::main_binary().unwrap();
+    let mut cmd = main_binary();
     cmd
         .arg("new")
         .arg("without-you")
@@ -154,13 +178,13 @@ ERROR: Invalid identifier without-you
 #[test]
 fn fail_to_create_a_ticket_with_non_existing_category() {
     purge().unwrap_or(());
-    let mut cmd = Command::main_binary().unwrap();
+    let mut cmd = main_binary();
     cmd
         .arg("init")
         .assert()
         .success();
 
-    let mut cmd = Command::main_binary().unwrap();
+    let mut cmd = main_binary();
     cmd
         .arg("new")
         .arg("never-exists/ID-1234")
@@ -178,19 +202,19 @@ ERROR: The category does not exist.
 #[test]
 fn show_a_valid_ticket() {
     purge().unwrap_or(());
-    let mut cmd = Command::main_binary().unwrap();
+    let mut cmd = main_binary();
     cmd
         .arg("init")
         .assert()
         .success();
-    let mut cmd = Command::main_binary().unwrap();
+    let mut cmd = main_binary();
     cmd
         .arg("new")
         .arg("test/")
         .assert()
         .success();
 
-    let mut cmd = Command::main_binary().unwrap();
+    let mut cmd = main_binary();
     cmd
         .arg("new")
         .arg("test/ID-1234")
@@ -199,7 +223,7 @@ fn show_a_valid_ticket() {
         .assert()
         .success();
 
-    let mut cmd = Command::main_binary().unwrap();
+    let mut cmd = main_binary();
     cmd
         .arg("show")
         .arg("test/ID-1234")
@@ -213,7 +237,7 @@ A fresh new ticket.
 
 #[test]
 fn show_a_invalid_ticket_id() {
-    let mut cmd = Command::main_binary().unwrap();
+    let mut cmd = main_binary();
     cmd
         .arg("show")
         .arg("invalid-ticket")
@@ -228,7 +252,7 @@ ERROR: Invalid identifier invalid-ticket
 #[test]
 fn fail_to_show_a_non_existing_ticket() {
     purge().unwrap_or(());
-    let mut cmd = Command::main_binary().unwrap();
+    let mut cmd = main_binary();
     cmd
         .arg("show")
         .arg("never/exists")
@@ -243,19 +267,19 @@ ERROR: NOT FOUND.
 #[test]
 fn show_an_empty_category() {
     purge().unwrap_or(());
-    let mut cmd = Command::main_binary().unwrap();
+    let mut cmd = main_binary();
     cmd
         .arg("init")
         .assert()
         .success();
-    let mut cmd = Command::main_binary().unwrap();
+    let mut cmd = main_binary();
     cmd
         .arg("new")
         .arg("test/")
         .assert()
         .success();
 
-    let mut cmd = Command::main_binary().unwrap();
+    let mut cmd = main_binary();
     cmd
         .arg("show")
         .arg("test/")
@@ -271,13 +295,13 @@ NO TICKETS.
 #[test]
 fn failed_to_show_a_non_existing_category() {
     purge().unwrap_or(());
-    let mut cmd = Command::main_binary().unwrap();
+    let mut cmd = main_binary();
     cmd
         .arg("init")
         .assert()
         .success();
 
-    let mut cmd = Command::main_binary().unwrap();
+    let mut cmd = main_binary();
     cmd
         .arg("show")
         .arg("never-exists/")
@@ -292,13 +316,13 @@ ERROR: NOT FOUND.
 #[test]
 fn show_a_category_with_multiple_tickets() {
     purge().unwrap_or(());
-    let mut cmd = Command::main_binary().unwrap();
+    let mut cmd = main_binary();
     cmd
         .arg("init")
         .assert()
         .success();
 
-    let mut cmd = Command::main_binary().unwrap();
+    let mut cmd = main_binary();
     cmd
         .arg("new")
         .arg("test/")
@@ -308,7 +332,7 @@ fn show_a_category_with_multiple_tickets() {
     let millis100 = time::Duration::from_millis(100);
 
     // create 3 tickets
-    let mut cmd = Command::main_binary().unwrap();
+    let mut cmd = main_binary();
     cmd
         .arg("new")
         .arg("test/ID-1")
@@ -318,7 +342,7 @@ fn show_a_category_with_multiple_tickets() {
         .success();
 
     thread::sleep(millis100);
-    let mut cmd = Command::main_binary().unwrap();
+    let mut cmd = main_binary();
     cmd
         .arg("new")
         .arg("test/ID-2")
@@ -328,7 +352,7 @@ fn show_a_category_with_multiple_tickets() {
         .success();
 
     thread::sleep(millis100);
-    let mut cmd = Command::main_binary().unwrap();
+    let mut cmd = main_binary();
     cmd
         .arg("new")
         .arg("test/ID-3")
@@ -339,7 +363,7 @@ fn show_a_category_with_multiple_tickets() {
 
     // the main test begin
     thread::sleep(millis100);
-    let mut cmd = Command::main_binary().unwrap();
+    let mut cmd = main_binary();
     cmd
         .arg("show")
         .arg("test/")
@@ -357,18 +381,18 @@ fn show_a_category_with_multiple_tickets() {
 #[test]
 fn edit_a_ticket() {
     purge().unwrap_or(());
-    let mut cmd = Command::main_binary().unwrap();
+    let mut cmd = main_binary();
     cmd
         .arg("init")
         .assert()
         .success();
-    let mut cmd = Command::main_binary().unwrap();
+    let mut cmd = main_binary();
     cmd
         .arg("new")
         .arg("hello/")
         .assert()
         .success();
-    let mut cmd = Command::main_binary().unwrap();
+    let mut cmd = main_binary();
     cmd
         .arg("new")
         .arg("hello/ID-1234")
@@ -377,7 +401,7 @@ fn edit_a_ticket() {
         .assert()
         .success();
 
-    let mut cmd = Command::main_binary().unwrap();
+    let mut cmd = main_binary();
     cmd
         .arg("edit")
         .arg("hello/ID-1234")
@@ -390,7 +414,7 @@ fn edit_a_ticket() {
 SUCCEEDED.
 ");
 
-    let mut cmd = Command::main_binary().unwrap();
+    let mut cmd = main_binary();
     cmd
         .arg("show")
         .arg("hello/ID-1234")
@@ -405,19 +429,19 @@ Now edited!
 #[test]
 fn fail_to_edit_a_non_existing_ticket() {
     purge().unwrap_or(());
-    let mut cmd = Command::main_binary().unwrap();
+    let mut cmd = main_binary();
     cmd
         .arg("init")
         .assert()
         .success();
-    let mut cmd = Command::main_binary().unwrap();
+    let mut cmd = main_binary();
     cmd
         .arg("new")
         .arg("hello/")
         .assert()
         .success();
 
-    let mut cmd = Command::main_binary().unwrap();
+    let mut cmd = main_binary();
     cmd
         .arg("edit")
         .arg("hello/ID-1234")
@@ -434,19 +458,19 @@ ERROR: The path does not exist.
 #[test]
 fn fail_to_edit_a_category() {
     purge().unwrap_or(());
-    let mut cmd = Command::main_binary().unwrap();
+    let mut cmd = main_binary();
     cmd
         .arg("init")
         .assert()
         .success();
-    let mut cmd = Command::main_binary().unwrap();
+    let mut cmd = main_binary();
     cmd
         .arg("new")
         .arg("hello/")
         .assert()
         .success();
 
-    let mut cmd = Command::main_binary().unwrap();
+    let mut cmd = main_binary();
     cmd
         .arg("edit")
         .arg("hello/")
@@ -464,24 +488,24 @@ ERROR: The category cannot be edited.
 #[test]
 fn move_a_ticket() {
     purge().unwrap_or(());
-    let mut cmd = Command::main_binary().unwrap();
+    let mut cmd = main_binary();
     cmd
         .arg("init")
         .assert()
         .success();
-    let mut cmd = Command::main_binary().unwrap();
+    let mut cmd = main_binary();
     cmd
         .arg("new")
         .arg("test1/")
         .assert()
         .success();
-    let mut cmd = Command::main_binary().unwrap();
+    let mut cmd = main_binary();
     cmd
         .arg("new")
         .arg("test2/")
         .assert()
         .success();
-    let mut cmd = Command::main_binary().unwrap();
+    let mut cmd = main_binary();
     cmd
         .arg("new")
         .arg("test1/ID-1234")
@@ -490,7 +514,7 @@ fn move_a_ticket() {
         .assert()
         .success();
 
-    let mut cmd = Command::main_binary().unwrap();
+    let mut cmd = main_binary();
     cmd
         .arg("move")
         .arg("test1/ID-1234")
@@ -504,7 +528,7 @@ SUCCEEDED.
 
     // Move back again,
     // but the destination representation is directory.
-    let mut cmd = Command::main_binary().unwrap();
+    let mut cmd = main_binary();
     cmd
         .arg("move")
         .arg("test2/ID-1234")
@@ -520,13 +544,13 @@ SUCCEEDED.
 #[test]
 fn reject_to_move_from_invalid_id() {
     purge().unwrap_or(());
-    let mut cmd = Command::main_binary().unwrap();
+    let mut cmd = main_binary();
     cmd
         .arg("init")
         .assert()
         .success();
 
-    let mut cmd = Command::main_binary().unwrap();
+    let mut cmd = main_binary();
     cmd
         .arg("move")
         .arg("in/valid/ID-1234")
@@ -542,18 +566,18 @@ ERROR: Invalid identifier in/valid/ID-1234
 #[test]
 fn reject_to_move_from_invalid_destination_id() {
     purge().unwrap_or(());
-    let mut cmd = Command::main_binary().unwrap();
+    let mut cmd = main_binary();
     cmd
         .arg("init")
         .assert()
         .success();
-    let mut cmd = Command::main_binary().unwrap();
+    let mut cmd = main_binary();
     cmd
         .arg("new")
         .arg("test1/")
         .assert()
         .success();
-    let mut cmd = Command::main_binary().unwrap();
+    let mut cmd = main_binary();
     cmd
         .arg("new")
         .arg("test1/ID-1234")
@@ -562,7 +586,7 @@ fn reject_to_move_from_invalid_destination_id() {
         .assert()
         .success();
 
-    let mut cmd = Command::main_binary().unwrap();
+    let mut cmd = main_binary();
     cmd
         .arg("move")
         .arg("test1/ID-1234")
@@ -578,19 +602,19 @@ ERROR: Invalid identifier in/valid/id
 #[test]
 fn fail_to_move_a_non_existing_ticket() {
     purge().unwrap_or(());
-    let mut cmd = Command::main_binary().unwrap();
+    let mut cmd = main_binary();
     cmd
         .arg("init")
         .assert()
         .success();
-    let mut cmd = Command::main_binary().unwrap();
+    let mut cmd = main_binary();
     cmd
         .arg("new")
         .arg("test1/")
         .assert()
         .success();
 
-    let mut cmd = Command::main_binary().unwrap();
+    let mut cmd = main_binary();
     cmd
         .arg("move")
         .arg("test1/ID-1234")
@@ -606,18 +630,18 @@ ERROR: the source path is not an existing regular file
 #[test]
 fn fail_to_move_a_ticket_to_non_existing_category() {
     purge().unwrap_or(());
-    let mut cmd = Command::main_binary().unwrap();
+    let mut cmd = main_binary();
     cmd
         .arg("init")
         .assert()
         .success();
-    let mut cmd = Command::main_binary().unwrap();
+    let mut cmd = main_binary();
     cmd
         .arg("new")
         .arg("test1/")
         .assert()
         .success();
-    let mut cmd = Command::main_binary().unwrap();
+    let mut cmd = main_binary();
     cmd
         .arg("new")
         .arg("test1/ID-1234")
@@ -626,7 +650,7 @@ fn fail_to_move_a_ticket_to_non_existing_category() {
         .assert()
         .success();
 
-    let mut cmd = Command::main_binary().unwrap();
+    let mut cmd = main_binary();
     cmd
         .arg("move")
         .arg("test1/ID-1234")
@@ -642,24 +666,24 @@ ERROR: No such file or directory (os error 2)
 #[test]
 fn move_a_whole_category() {
     purge().unwrap_or(());
-    let mut cmd = Command::main_binary().unwrap();
+    let mut cmd = main_binary();
     cmd
         .arg("init")
         .assert()
         .success();
-    let mut cmd = Command::main_binary().unwrap();
+    let mut cmd = main_binary();
     cmd
         .arg("new")
         .arg("test1/")
         .assert()
         .success();
-    let mut cmd = Command::main_binary().unwrap();
+    let mut cmd = main_binary();
     cmd
         .arg("new")
         .arg("test2/")
         .assert()
         .success();
-    let mut cmd = Command::main_binary().unwrap();
+    let mut cmd = main_binary();
     cmd
         .arg("new")
         .arg("test1/ID-1233")
@@ -667,7 +691,7 @@ fn move_a_whole_category() {
         .arg("To be moved...")
         .assert()
         .success();
-    let mut cmd = Command::main_binary().unwrap();
+    let mut cmd = main_binary();
     cmd
         .arg("new")
         .arg("test1/ID-1234")
@@ -676,7 +700,7 @@ fn move_a_whole_category() {
         .assert()
         .success();
 
-    let mut cmd = Command::main_binary().unwrap();
+    let mut cmd = main_binary();
     cmd
         .arg("move")
         .arg("test1/")
@@ -689,7 +713,7 @@ SUCCEEDED.
 ");
 
     // additional check: show them!
-    let mut cmd = Command::main_binary().unwrap();
+    let mut cmd = main_binary();
     cmd
         .arg("show")
         .arg("test2/")
@@ -706,18 +730,18 @@ SUCCEEDED.
 #[test]
 fn remove_a_ticket() {
     purge().unwrap_or(());
-    let mut cmd = Command::main_binary().unwrap();
+    let mut cmd = main_binary();
     cmd
         .arg("init")
         .assert()
         .success();
-    let mut cmd = Command::main_binary().unwrap();
+    let mut cmd = main_binary();
     cmd
         .arg("new")
         .arg("hello/")
         .assert()
         .success();
-    let mut cmd = Command::main_binary().unwrap();
+    let mut cmd = main_binary();
     cmd
         .arg("new")
         .arg("hello/ID-1233")
@@ -726,7 +750,7 @@ fn remove_a_ticket() {
         .assert()
         .success();
 
-    let mut cmd = Command::main_binary().unwrap();
+    let mut cmd = main_binary();
     cmd
         .arg("remove")
         .arg("hello/ID-1233")
@@ -738,7 +762,7 @@ SUCCEEDED.
 ");
 
     // check the ticket is removed.
-    let mut cmd = Command::main_binary().unwrap();
+    let mut cmd = main_binary();
     cmd
         .arg("show")
         .arg("hello/ID-1233")
@@ -753,19 +777,19 @@ ERROR: NOT FOUND.
 #[test]
 fn fail_to_remove_non_existing_ticket() {
     purge().unwrap_or(());
-    let mut cmd = Command::main_binary().unwrap();
+    let mut cmd = main_binary();
     cmd
         .arg("init")
         .assert()
         .success();
-    let mut cmd = Command::main_binary().unwrap();
+    let mut cmd = main_binary();
     cmd
         .arg("new")
         .arg("hello/")
         .assert()
         .success();
 
-    let mut cmd = Command::main_binary().unwrap();
+    let mut cmd = main_binary();
     cmd
         .arg("remove")
         .arg("hello/ID-1233")
@@ -780,18 +804,18 @@ ERROR: No such file or directory (os error 2)
 #[test]
 fn remove_a_whole_category() {
     purge().unwrap_or(());
-    let mut cmd = Command::main_binary().unwrap();
+    let mut cmd = main_binary();
     cmd
         .arg("init")
         .assert()
         .success();
-    let mut cmd = Command::main_binary().unwrap();
+    let mut cmd = main_binary();
     cmd
         .arg("new")
         .arg("hello/")
         .assert()
         .success();
-    let mut cmd = Command::main_binary().unwrap();
+    let mut cmd = main_binary();
     cmd
         .arg("new")
         .arg("hello/ID-1233")
@@ -799,7 +823,7 @@ fn remove_a_whole_category() {
         .arg("To be removed...")
         .assert()
         .success();
-    let mut cmd = Command::main_binary().unwrap();
+    let mut cmd = main_binary();
     cmd
         .arg("new")
         .arg("hello/ID-1234")
@@ -808,7 +832,7 @@ fn remove_a_whole_category() {
         .assert()
         .success();
 
-    let mut cmd = Command::main_binary().unwrap();
+    let mut cmd = main_binary();
     cmd
         .arg("remove")
         .arg("hello/")
@@ -820,7 +844,7 @@ SUCCEEDED.
 ");
 
     // check the hello category is removed.
-    let mut cmd = Command::main_binary().unwrap();
+    let mut cmd = main_binary();
     cmd
         .arg("show")
         .arg("hello/")
@@ -836,18 +860,18 @@ ERROR: NOT FOUND.
 #[test]
 fn tickets () {
     purge().unwrap_or(());
-    let mut cmd = Command::main_binary().unwrap();
+    let mut cmd = main_binary();
     cmd
         .arg("init")
         .assert()
         .success();
-    let mut cmd = Command::main_binary().unwrap();
+    let mut cmd = main_binary();
     cmd
         .arg("new")
         .arg("hello/")
         .assert()
         .success();
-    let mut cmd = Command::main_binary().unwrap();
+    let mut cmd = main_binary();
     cmd
         .arg("new")
         .arg("hello/ID-1233")
@@ -855,7 +879,7 @@ fn tickets () {
         .arg("Final Tests")
         .assert()
         .success();
-    let mut cmd = Command::main_binary().unwrap();
+    let mut cmd = main_binary();
     cmd
         .arg("new")
         .arg("hello/ID-1234")
@@ -863,13 +887,13 @@ fn tickets () {
         .arg("Real Final Tests")
         .assert()
         .success();
-    let mut cmd = Command::main_binary().unwrap();
+    let mut cmd = main_binary();
     cmd
         .arg("new")
         .arg("world/")
         .assert()
         .success();
-    let mut cmd = Command::main_binary().unwrap();
+    let mut cmd = main_binary();
     cmd
         .arg("new")
         .arg("world/WTF-1")
@@ -877,7 +901,7 @@ fn tickets () {
         .arg("What The ...?")
         .assert()
         .success();
-    let mut cmd = Command::main_binary().unwrap();
+    let mut cmd = main_binary();
     cmd
         .arg("new")
         .arg("world/WTF-2")
@@ -886,7 +910,7 @@ fn tickets () {
         .assert()
         .success();
 
-    let mut cmd = Command::main_binary().unwrap();
+    let mut cmd = main_binary();
     cmd
         .assert()
         .success()
@@ -904,7 +928,7 @@ world/
 fn fail_to_ticket_without_init() {
     purge().unwrap_or(());
 
-    let mut cmd = Command::main_binary().unwrap();
+    let mut cmd = main_binary();
     cmd
         .assert()
         .failure()
@@ -914,13 +938,13 @@ fn fail_to_ticket_without_init() {
 #[test]
 fn ticket_with_no_categories() {
     purge().unwrap_or(());
-    let mut cmd = Command::main_binary().unwrap();
+    let mut cmd = main_binary();
     cmd
         .arg("init")
         .assert()
         .success();
 
-    let mut cmd = Command::main_binary().unwrap();
+    let mut cmd = main_binary();
     cmd
         .assert()
         .success()
@@ -930,25 +954,25 @@ fn ticket_with_no_categories() {
 #[test]
 fn ticket_with_categories_but_no_tickets() {
     purge().unwrap_or(());
-    let mut cmd = Command::main_binary().unwrap();
+    let mut cmd = main_binary();
     cmd
         .arg("init")
         .assert()
         .success();
-    let mut cmd = Command::main_binary().unwrap();
+    let mut cmd = main_binary();
     cmd
         .arg("new")
         .arg("hello/")
         .assert()
         .success();
-    let mut cmd = Command::main_binary().unwrap();
+    let mut cmd = main_binary();
     cmd
         .arg("new")
         .arg("world/")
         .assert()
         .success();
 
-    let mut cmd = Command::main_binary().unwrap();
+    let mut cmd = main_binary();
     cmd
         .assert()
         .success()
@@ -959,3 +983,2328 @@ world/
 NO TICKETS.
 ");
 }
+
+#[test]
+fn ticket_with_an_unreadable_category() {
+    purge().unwrap_or(());
+    let mut cmd = main_binary();
+    cmd
+        .arg("init")
+        .assert()
+        .success();
+    for id in &["hello/", "world/"] {
+        let mut cmd = main_binary();
+        cmd
+            .arg("new")
+            .arg(id)
+            .assert()
+            .success();
+    }
+    std::fs::write(home().join(".tickets/hello/binary"), b"\xff\xfe").unwrap();
+
+    let mut cmd = main_binary();
+    cmd
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("hello/\nERROR: "))
+        .stdout(predicate::str::contains("world/\nNO TICKETS."));
+}
+
+// ================= COMMENT =================
+#[test]
+fn comment_a_ticket() {
+    purge().unwrap_or(());
+    let mut cmd = main_binary();
+    cmd
+        .arg("init")
+        .assert()
+        .success();
+    let mut cmd = main_binary();
+    cmd
+        .arg("new")
+        .arg("test/")
+        .assert()
+        .success();
+    let mut cmd = main_binary();
+    cmd
+        .arg("new")
+        .arg("test/ID-1234")
+        .arg("--message")
+        .arg("A ticket to discuss.")
+        .assert()
+        .success();
+
+    let mut cmd = main_binary();
+    cmd
+        .arg("comment")
+        .arg("test/ID-1234")
+        .arg("--message")
+        .arg("The first opinion")
+        .env("USER", "alice")
+        .assert()
+        .success()
+        .stdout("tickets :: comment :: test/ID-1234
+
+SUCCEEDED.
+");
+    let mut cmd = main_binary();
+    cmd
+        .arg("comment")
+        .arg("test/ID-1234")
+        .arg("--message")
+        .arg("The second opinion")
+        .env("USER", "bob")
+        .assert()
+        .success();
+
+    let mut cmd = main_binary();
+    cmd
+        .arg("show")
+        .arg("test/ID-1234")
+        .assert()
+        .success()
+        .stdout(predicate::str::is_match(
+            "^tickets :: show :: test/ID-1234
+
+A ticket to discuss.
+
+\\[.+\\] alice
+The first opinion
+
+\\[.+\\] bob
+The second opinion
+$").unwrap());
+
+    let mut cmd = main_binary();
+    cmd
+        .arg("show")
+        .arg("test/ID-1234")
+        .arg("--no-comments")
+        .assert()
+        .success()
+        .stdout("tickets :: show :: test/ID-1234
+
+A ticket to discuss.
+");
+
+    // comments are not tickets
+    let mut cmd = main_binary();
+    cmd
+        .arg("show")
+        .arg("test/")
+        .assert()
+        .success()
+        .stdout("tickets :: show :: test/
+
+[ID-1234]A ticket to discuss.
+");
+}
+
+#[test]
+fn fail_to_comment_a_category() {
+    purge().unwrap_or(());
+    let mut cmd = main_binary();
+    cmd
+        .arg("init")
+        .assert()
+        .success();
+    let mut cmd = main_binary();
+    cmd
+        .arg("new")
+        .arg("test/")
+        .assert()
+        .success();
+
+    let mut cmd = main_binary();
+    cmd
+        .arg("comment")
+        .arg("test/")
+        .arg("--message")
+        .arg("Nowhere to go")
+        .assert()
+        .failure()
+        .stderr("tickets :: comment :: test/
+
+ERROR: The category cannot be commented.
+");
+}
+
+#[test]
+fn comments_follow_the_moved_ticket() {
+    purge().unwrap_or(());
+    let mut cmd = main_binary();
+    cmd
+        .arg("init")
+        .assert()
+        .success();
+    let mut cmd = main_binary();
+    cmd
+        .arg("new")
+        .arg("test1/")
+        .assert()
+        .success();
+    let mut cmd = main_binary();
+    cmd
+        .arg("new")
+        .arg("test2/")
+        .assert()
+        .success();
+    let mut cmd = main_binary();
+    cmd
+        .arg("new")
+        .arg("test1/ID-1234")
+        .arg("--message")
+        .arg("To be moved...")
+        .assert()
+        .success();
+    let mut cmd = main_binary();
+    cmd
+        .arg("comment")
+        .arg("test1/ID-1234")
+        .arg("--message")
+        .arg("Take me with you")
+        .assert()
+        .success();
+
+    let mut cmd = main_binary();
+    cmd
+        .arg("move")
+        .arg("test1/ID-1234")
+        .arg("test2/ID-4321")
+        .assert()
+        .success();
+
+    let mut cmd = main_binary();
+    cmd
+        .arg("show")
+        .arg("test2/ID-4321")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\nTake me with you\n"));
+}
+
+// ================= ATTACH =================
+#[test]
+fn attach_list_and_detach_files() {
+    purge().unwrap_or(());
+    let mut cmd = main_binary();
+    cmd
+        .arg("init")
+        .assert()
+        .success();
+    let mut cmd = main_binary();
+    cmd
+        .arg("new")
+        .arg("test/")
+        .assert()
+        .success();
+    let mut cmd = main_binary();
+    cmd
+        .arg("new")
+        .arg("test/ID-1234")
+        .arg("--message")
+        .arg("It crashes.")
+        .assert()
+        .success();
+    let path_log = std::env::temp_dir().join("tickets-crash.log");
+    std::fs::write(&path_log, "panicked at ...").unwrap();
+
+    let mut cmd = main_binary();
+    cmd
+        .arg("attachments")
+        .arg("test/ID-1234")
+        .assert()
+        .success()
+        .stdout("tickets :: attachments :: test/ID-1234
+
+NO ATTACHMENTS.
+");
+
+    let mut cmd = main_binary();
+    cmd
+        .arg("attach")
+        .arg("test/ID-1234")
+        .arg(&path_log)
+        .assert()
+        .success()
+        .stdout("tickets :: attach :: test/ID-1234
+
+SUCCEEDED.
+");
+
+    let mut cmd = main_binary();
+    cmd
+        .arg("attachments")
+        .arg("test/ID-1234")
+        .assert()
+        .success()
+        .stdout("tickets :: attachments :: test/ID-1234
+
+tickets-crash.log
+");
+
+    // attachments are not tickets
+    let mut cmd = main_binary();
+    cmd
+        .arg("show")
+        .arg("test/")
+        .assert()
+        .success()
+        .stdout("tickets :: show :: test/
+
+[ID-1234]It crashes.
+");
+
+    let mut cmd = main_binary();
+    cmd
+        .arg("detach")
+        .arg("test/ID-1234")
+        .arg("tickets-crash.log")
+        .assert()
+        .success()
+        .stdout("tickets :: detach :: test/ID-1234
+
+SUCCEEDED.
+");
+
+    let mut cmd = main_binary();
+    cmd
+        .arg("detach")
+        .arg("test/ID-1234")
+        .arg("tickets-crash.log")
+        .assert()
+        .failure()
+        .stderr("tickets :: detach :: test/ID-1234
+
+ERROR: The attachment does not exist.
+");
+}
+
+// ================= TIME TRACKING =================
+#[test]
+fn start_and_stop_a_ticket() {
+    purge().unwrap_or(());
+    let mut cmd = main_binary();
+    cmd
+        .arg("init")
+        .assert()
+        .success();
+    let mut cmd = main_binary();
+    cmd
+        .arg("new")
+        .arg("test/")
+        .assert()
+        .success();
+    let mut cmd = main_binary();
+    cmd
+        .arg("new")
+        .arg("test/ID-1234")
+        .arg("--message")
+        .arg("Billable work")
+        .assert()
+        .success();
+
+    let mut cmd = main_binary();
+    cmd
+        .arg("stop")
+        .assert()
+        .failure()
+        .stderr("tickets :: stop
+
+ERROR: No ticket is being tracked.
+");
+
+    let mut cmd = main_binary();
+    cmd
+        .arg("start")
+        .arg("test/ID-1234")
+        .assert()
+        .success()
+        .stdout("tickets :: start :: test/ID-1234
+
+SUCCEEDED.
+");
+
+    let mut cmd = main_binary();
+    cmd
+        .arg("start")
+        .arg("test/ID-1234")
+        .assert()
+        .failure()
+        .stderr("tickets :: start :: test/ID-1234
+
+ERROR: The ticket test/ID-1234 is already being tracked.
+");
+
+    let mut cmd = main_binary();
+    cmd
+        .arg("stop")
+        .assert()
+        .success()
+        .stdout("tickets :: stop :: test/ID-1234
+
+0m LOGGED.
+
+SUCCEEDED.
+");
+}
+
+#[test]
+fn follow_or_discard_the_tracked_ticket() {
+    purge().unwrap_or(());
+    let mut cmd = main_binary();
+    cmd
+        .arg("init")
+        .assert()
+        .success();
+    for id in &["test/", "done/"] {
+        let mut cmd = main_binary();
+        cmd
+            .arg("new")
+            .arg(id)
+            .assert()
+            .success();
+    }
+    let mut cmd = main_binary();
+    cmd
+        .arg("new")
+        .arg("test/ID-1234")
+        .arg("--message")
+        .arg("Billable work")
+        .assert()
+        .success();
+
+    let mut cmd = main_binary();
+    cmd
+        .arg("start")
+        .arg("test/ID-1234")
+        .assert()
+        .success();
+    let mut cmd = main_binary();
+    cmd
+        .arg("move")
+        .arg("test/ID-1234")
+        .arg("done/")
+        .assert()
+        .success();
+    let mut cmd = main_binary();
+    cmd
+        .arg("stop")
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("tickets :: stop :: done/ID-1234"));
+
+    let mut cmd = main_binary();
+    cmd
+        .arg("start")
+        .arg("done/ID-1234")
+        .assert()
+        .success();
+    let mut cmd = main_binary();
+    cmd
+        .arg("stop")
+        .arg("--discard")
+        .assert()
+        .success()
+        .stdout("tickets :: stop :: done/ID-1234
+
+DISCARDED.
+
+SUCCEEDED.
+");
+
+    let mut cmd = main_binary();
+    cmd
+        .arg("start")
+        .arg("done/ID-1234")
+        .assert()
+        .success();
+    let mut cmd = main_binary();
+    cmd
+        .arg("remove")
+        .arg("done/ID-1234")
+        .assert()
+        .success();
+    let mut cmd = main_binary();
+    cmd
+        .arg("stop")
+        .assert()
+        .failure()
+        .stderr("tickets :: stop
+
+ERROR: No ticket is being tracked.
+");
+}
+
+#[test]
+fn log_time_and_show_timesheet() {
+    purge().unwrap_or(());
+    let mut cmd = main_binary();
+    cmd
+        .arg("init")
+        .assert()
+        .success();
+    let mut cmd = main_binary();
+    cmd
+        .arg("new")
+        .arg("test/")
+        .assert()
+        .success();
+    let mut cmd = main_binary();
+    cmd
+        .arg("new")
+        .arg("test/ID-1233")
+        .arg("--message")
+        .arg("Billable work")
+        .assert()
+        .success();
+    let mut cmd = main_binary();
+    cmd
+        .arg("new")
+        .arg("test/ID-1234")
+        .arg("--message")
+        .arg("More billable work")
+        .assert()
+        .success();
+
+    let mut cmd = main_binary();
+    cmd
+        .arg("log-time")
+        .arg("test/ID-1233")
+        .arg("1h30m")
+        .assert()
+        .success()
+        .stdout("tickets :: log-time :: test/ID-1233
+
+SUCCEEDED.
+");
+    let mut cmd = main_binary();
+    cmd
+        .arg("log-time")
+        .arg("test/ID-1234")
+        .arg("45m")
+        .assert()
+        .success();
+    let mut cmd = main_binary();
+    cmd
+        .arg("log-time")
+        .arg("test/ID-1234")
+        .arg("forever")
+        .assert()
+        .failure()
+        .stderr("tickets :: log-time :: test/ID-1234
+
+ERROR: Invalid duration forever
+");
+
+    let mut cmd = main_binary();
+    cmd
+        .arg("timesheet")
+        .arg("--since")
+        .arg("today")
+        .assert()
+        .success()
+        .stdout("tickets :: timesheet
+
+test/ID-1233    1h30m
+test/ID-1234      45m
+
+TOTAL           2h15m
+");
+
+    let mut cmd = main_binary();
+    cmd
+        .arg("timesheet")
+        .arg("--by")
+        .arg("category")
+        .arg("--format")
+        .arg("csv")
+        .assert()
+        .success()
+        .stdout("category,hours
+test/,2.25
+");
+}
+
+// ================= ESTIMATE =================
+#[test]
+fn estimate_tickets_and_show_totals() {
+    purge().unwrap_or(());
+    let mut cmd = main_binary();
+    cmd
+        .arg("init")
+        .assert()
+        .success();
+    let mut cmd = main_binary();
+    cmd
+        .arg("new")
+        .arg("test/")
+        .assert()
+        .success();
+    let mut cmd = main_binary();
+    cmd
+        .arg("new")
+        .arg("test/ID-1233")
+        .arg("--message")
+        .arg("Small one")
+        .assert()
+        .success();
+    let mut cmd = main_binary();
+    cmd
+        .arg("new")
+        .arg("test/ID-1234")
+        .arg("--message")
+        .arg("Big one")
+        .assert()
+        .success();
+
+    let mut cmd = main_binary();
+    cmd
+        .arg("estimate")
+        .arg("test/ID-1233")
+        .arg("1")
+        .assert()
+        .success()
+        .stdout("tickets :: estimate :: test/ID-1233
+
+SUCCEEDED.
+");
+    let mut cmd = main_binary();
+    cmd
+        .arg("estimate")
+        .arg("test/ID-1234")
+        .arg("5")
+        .assert()
+        .success();
+    let mut cmd = main_binary();
+    cmd
+        .arg("estimate")
+        .arg("test/ID-1234")
+        .arg("a lot")
+        .assert()
+        .failure()
+        .stderr("tickets :: estimate :: test/ID-1234
+
+ERROR: Invalid estimate a lot
+");
+
+    let mut cmd = main_binary();
+    cmd
+        .arg("show")
+        .arg("test/")
+        .assert()
+        .success()
+        .stdout("tickets :: show :: test/
+
+[ID-1233]Small one (1 point)
+[ID-1234]Big one (5 points)
+TOTAL: 6 points estimated
+");
+
+    let mut cmd = main_binary();
+    cmd
+        .arg("log-time")
+        .arg("test/ID-1234")
+        .arg("2h")
+        .assert()
+        .success();
+    let mut cmd = main_binary();
+    cmd
+        .assert()
+        .success()
+        .stdout("test/
+[ID-1233]Small one (1 point)
+[ID-1234]Big one (5 points)
+TOTAL: 6 points estimated, 2h00m logged
+");
+}
+
+#[test]
+fn estimate_tickets_in_hours() {
+    purge().unwrap_or(());
+    let mut cmd = main_binary();
+    cmd
+        .arg("init")
+        .assert()
+        .success();
+    let mut cmd = main_binary();
+    cmd
+        .arg("new")
+        .arg("test/")
+        .assert()
+        .success();
+    let mut cmd = main_binary();
+    cmd
+        .arg("new")
+        .arg("test/ID-1234")
+        .arg("--message")
+        .arg("Hours matter")
+        .assert()
+        .success();
+    let mut cmd = main_binary();
+    cmd
+        .arg("estimate")
+        .arg("test/ID-1234")
+        .arg("3h")
+        .env("TICKETS_ESTIMATE_UNIT", "hours")
+        .assert()
+        .success();
+    let mut cmd = main_binary();
+    cmd
+        .arg("log-time")
+        .arg("test/ID-1234")
+        .arg("1h15m")
+        .assert()
+        .success();
+
+    let mut cmd = main_binary();
+    cmd
+        .arg("show")
+        .arg("test/")
+        .env("TICKETS_ESTIMATE_UNIT", "hours")
+        .assert()
+        .success()
+        .stdout("tickets :: show :: test/
+
+[ID-1234]Hours matter (3h00m)
+TOTAL: 3h00m estimated, 1h15m logged, 1h45m remaining
+");
+}
+
+// ================= BOARD =================
+#[test]
+fn show_a_board() {
+    purge().unwrap_or(());
+    let mut cmd = main_binary();
+    cmd
+        .arg("init")
+        .assert()
+        .success();
+    for id in &["todo/", "doing/", "done/"] {
+        let mut cmd = main_binary();
+        cmd
+            .arg("new")
+            .arg(id)
+            .assert()
+            .success();
+    }
+    let mut cmd = main_binary();
+    cmd
+        .arg("new")
+        .arg("todo/ID-1233")
+        .arg("--message")
+        .arg("A title much longer than the column")
+        .assert()
+        .success();
+    let mut cmd = main_binary();
+    cmd
+        .arg("new")
+        .arg("todo/ID-1234")
+        .arg("--message")
+        .arg("Short")
+        .assert()
+        .success();
+    let mut cmd = main_binary();
+    cmd
+        .arg("new")
+        .arg("doing/ID-1235")
+        .arg("--message")
+        .arg("Busy")
+        .assert()
+        .success();
+
+    let mut cmd = main_binary();
+    cmd
+        .arg("board")
+        .arg("todo/")
+        .arg("doing/")
+        .arg("done/")
+        .env("COLUMNS", "60")
+        .assert()
+        .success()
+        .stdout("tickets :: board
+
+todo/ (2)          | doing/ (1)         | done/ (0)
+------------------ | ------------------ | ------------------
+[ID-1233]A title … | [ID-1235]Busy      |
+[ID-1234]Short     |                    |
+");
+
+    // alphabetical without categories given
+    let mut cmd = main_binary();
+    cmd
+        .arg("board")
+        .env("COLUMNS", "40")
+        .assert()
+        .success()
+        .stdout("tickets :: board
+
+doing/ (1)  | done/ (0)   | todo/ (2)
+----------- | ----------- | -----------
+[ID-1235]B… |             | [ID-1233]A…
+            |             | [ID-1234]S…
+");
+
+    let mut cmd = main_binary();
+    cmd
+        .arg("board")
+        .arg("todo/ID-1234")
+        .assert()
+        .failure()
+        .stderr("tickets :: board
+
+ERROR: todo/ID-1234 is not an existing category.
+");
+}
+
+// ================= TUI =================
+#[test]
+fn fail_to_tui_without_terminal() {
+    purge().unwrap_or(());
+    let mut cmd = main_binary();
+    cmd
+        .arg("init")
+        .assert()
+        .success();
+
+    let mut cmd = main_binary();
+    cmd
+        .arg("tui")
+        .assert()
+        .failure()
+        .stderr("tickets :: tui
+
+ERROR: The terminal is not interactive.
+");
+}
+
+// ================= SERVE =================
+/// Send a request to the server and give the status with the body.
+fn http(port: u16, method: &str, path: &str, body: &str) -> (u16, String) {
+    use std::io::{Read, Write};
+
+    let mut stream = std::net::TcpStream::connect(("127.0.0.1", port))
+        .unwrap();
+    write!(stream,
+           "{} {} HTTP/1.1\r\nHost: localhost\r\nContent-Length: {}\r\n\
+           Connection: close\r\n\r\n{}",
+           method, path, body.len(), body).unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    let status = response[9..12].parse().unwrap();
+    let body = response.split_once("\r\n\r\n").unwrap().1.to_string();
+    (status, body)
+}
+
+#[test]
+fn serve_a_rest_api() {
+    use std::io::BufRead;
+
+    purge().unwrap_or(());
+    let mut cmd = main_binary();
+    cmd
+        .arg("init")
+        .assert()
+        .success();
+    let mut cmd = main_binary();
+    cmd
+        .arg("new")
+        .arg("open/")
+        .assert()
+        .success();
+
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let mut server = main_binary()
+        .arg("serve")
+        .arg("--bind")
+        .arg(format!("127.0.0.1:{}", port))
+        .stdout(std::process::Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut stdout = std::io::BufReader::new(server.stdout.take().unwrap());
+    let mut line = String::new();
+    while line.trim() != "LISTENING." {
+        line.clear();
+        stdout.read_line(&mut line).unwrap();
+    }
+
+    assert_eq!(http(port, "GET", "/categories", ""),
+               (200, String::from(r#"[{"category":"open","tickets":0}]"#)));
+
+    let (status, body) = http(port, "PUT", "/tickets/open/ID-1234",
+                              r#"{"title": "Served", "message": "Hot"}"#);
+    assert_eq!(status, 201);
+    assert!(body.contains(r#""title":"Served""#));
+    assert!(body.contains(r#""message":"Hot""#));
+
+    let (status, body) = http(port, "PUT", "/tickets/open/ID-1234",
+                              r#"{"title": "Served again"}"#);
+    assert_eq!(status, 200);
+    assert!(body.contains(r#""title":"Served again""#));
+
+    assert_eq!(http(port, "PUT", "/categories/done", "").0, 201);
+    let (status, body) = http(port, "POST", "/tickets/open/ID-1234/move",
+                              r#"{"destination": "done/"}"#);
+    assert_eq!(status, 200);
+    assert!(body.contains(r#""category":"done""#));
+    assert_eq!(http(port, "GET", "/tickets/open/ID-1234", "").0, 404);
+
+    let (status, body) = http(port, "GET", "/tickets/done/ID-1234", "");
+    assert_eq!(status, 200);
+    assert!(body.contains(r#""id":"ID-1234""#));
+
+    assert_eq!(http(port, "DELETE", "/tickets/done/ID-1234", "").0, 200);
+    assert_eq!(http(port, "DELETE", "/tickets/done/ID-1234", "").0, 404);
+
+    // the raw paths are sent as they are, like `curl --path-as-is`
+    assert_eq!(http(port, "DELETE", "/categories/..", "").0, 400);
+    assert_eq!(http(port, "PUT", "/tickets/../escaped",
+                    r#"{"title": "Escaped"}"#).0, 400);
+    assert_eq!(http(port, "PUT", "/tickets/%2e%2e/escaped",
+                    r#"{"title": "Escaped"}"#).0, 400);
+    assert!(home().join(".tickets/done").exists());
+    assert!(!home().join("escaped").exists());
+    assert_eq!(http(port, "GET", "/nowhere", "").0, 404);
+
+    server.kill().unwrap();
+    server.wait().unwrap();
+}
+
+// ================= EXPORT =================
+#[test]
+fn export_a_static_site() {
+    purge().unwrap_or(());
+    let mut cmd = main_binary();
+    cmd
+        .arg("init")
+        .assert()
+        .success();
+    let mut cmd = main_binary();
+    cmd
+        .arg("new")
+        .arg("open/")
+        .assert()
+        .success();
+    let mut cmd = main_binary();
+    cmd
+        .arg("new")
+        .arg("open/ID-1234")
+        .arg("--message")
+        .arg("Publish <it>\n\n- for *everyone*")
+        .assert()
+        .success();
+    let mut cmd = main_binary();
+    cmd
+        .arg("new")
+        .arg("open/index")
+        .arg("--message")
+        .arg("Not the index\n\n[click](javascript:alert(1))")
+        .assert()
+        .success();
+
+    let out_dir = std::env::temp_dir().join("tickets-site");
+    std::fs::remove_dir_all(&out_dir).unwrap_or(());
+    let mut cmd = main_binary();
+    cmd
+        .arg("export")
+        .arg("html")
+        .arg(&out_dir)
+        .assert()
+        .success()
+        .stdout("tickets :: export :: html
+
+SUCCEEDED.
+");
+
+    let index = std::fs::read_to_string(out_dir.join("index.html")).unwrap();
+    assert!(index.contains("<a href=\"open/index.html\">open/</a>"));
+    assert!(index.contains("<a href=\"open/t-ID-1234.html\">"));
+    let category = std::fs::read_to_string(out_dir.join("open/index.html"))
+        .unwrap();
+    assert!(category.contains("Publish &lt;it&gt;"));
+    assert!(category.contains("<a href=\"t-index.html\">"));
+    let ticket = std::fs::read_to_string(out_dir.join("open/t-ID-1234.html"))
+        .unwrap();
+    assert!(ticket.contains("<li>for <em>everyone</em></li>"));
+    let ticket = std::fs::read_to_string(out_dir.join("open/t-index.html"))
+        .unwrap();
+    assert!(ticket.contains("<a href=\"#\">click</a>"));
+    let search = std::fs::read_to_string(out_dir.join("search.js")).unwrap();
+    assert!(search.contains(r#""url":"open/t-ID-1234.html""#));
+}
+
+// ================= SHOW RAW =================
+#[test]
+fn show_raw() {
+    purge().unwrap_or(());
+    let mut cmd = main_binary();
+    cmd
+        .arg("init")
+        .assert()
+        .success();
+
+    let mut cmd = main_binary();
+    cmd
+        .arg("new")
+        .arg("open/")
+        .assert()
+        .success();
+    let mut cmd = main_binary();
+    cmd
+        .arg("new")
+        .arg("open/ID-1234")
+        .arg("--message")
+        .arg("Render it\n\n# Steps\n\n- *first*")
+        .assert()
+        .success();
+
+    let mut cmd = main_binary();
+    cmd
+        .arg("show")
+        .arg("open/ID-1234")
+        .arg("--raw")
+        .assert()
+        .success()
+        .stdout("Render it\n\n# Steps\n\n- *first*");
+
+    // piped output stays plain
+    let mut cmd = main_binary();
+    cmd
+        .arg("show")
+        .arg("open/ID-1234")
+        .assert()
+        .success()
+        .stdout("tickets :: show :: open/ID-1234
+
+Render it
+
+# Steps
+
+- *first*
+");
+}
+
+// ================= COLOR =================
+#[test]
+fn color_choices() {
+    purge().unwrap_or(());
+    let mut cmd = main_binary();
+    cmd
+        .arg("init")
+        .assert()
+        .success();
+    let mut cmd = main_binary();
+    cmd
+        .arg("new")
+        .arg("open/")
+        .assert()
+        .success();
+    let mut cmd = main_binary();
+    cmd
+        .arg("new")
+        .arg("open/ID-1234")
+        .arg("--message")
+        .arg("Color it")
+        .assert()
+        .success();
+
+    let mut cmd = main_binary();
+    cmd
+        .arg("show")
+        .arg("open/")
+        .arg("--color")
+        .arg("always")
+        .assert()
+        .success()
+        .stdout("\x1b[1mtickets :: show :: open/\x1b[0m
+
+\x1b[33m[ID-1234]\x1b[0m\x1b[1mColor it\x1b[0m
+");
+
+    let mut cmd = main_binary();
+    cmd
+        .env("CLICOLOR_FORCE", "1")
+        .arg("remove")
+        .arg("open/ID-9999")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("\x1b[1;31mERROR:\x1b[0m"));
+
+    let mut cmd = main_binary();
+    cmd
+        .env("CLICOLOR_FORCE", "1")
+        .env("NO_COLOR", "1")
+        .arg("show")
+        .arg("open/")
+        .assert()
+        .success()
+        .stdout("tickets :: show :: open/
+
+[ID-1234]Color it
+");
+
+    let mut cmd = main_binary();
+    cmd
+        .env("CLICOLOR_FORCE", "1")
+        .arg("--color")
+        .arg("never")
+        .arg("show")
+        .arg("open/")
+        .assert()
+        .success()
+        .stdout("tickets :: show :: open/
+
+[ID-1234]Color it
+");
+}
+
+// ================= COMPLETIONS =================
+#[test]
+fn completion_scripts() {
+    let mut cmd = main_binary();
+    cmd
+        .arg("completions")
+        .arg("bash")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("tickets__show)")
+                .and(predicate::str::contains("tickets __complete"))
+                .and(predicate::str::contains(
+                    "complete -F _tickets_ids -o bashdefault -o default tickets")));
+
+    let mut cmd = main_binary();
+    cmd
+        .arg("completions")
+        .arg("zsh")
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("#compdef tickets")
+                .and(predicate::str::contains(
+                    "':id -- Target identifier:_tickets_ids'")));
+
+    let mut cmd = main_binary();
+    cmd
+        .arg("completions")
+        .arg("fish")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "-f -a \"(tickets __complete (commandline -ct))\""));
+
+    let mut cmd = main_binary();
+    cmd
+        .arg("completions")
+        .arg("tcsh")
+        .assert()
+        .failure();
+}
+
+#[test]
+fn complete_ids() {
+    purge().unwrap_or(());
+    let mut cmd = main_binary();
+    cmd
+        .arg("init")
+        .assert()
+        .success();
+    for id in &["open/", "in-progress/", "open/ID-1234", "open/ID-1235"] {
+        let mut cmd = main_binary();
+        cmd
+            .arg("new")
+            .arg(id)
+            .arg("--message")
+            .arg("Complete it")
+            .assert()
+            .success();
+    }
+
+    let mut cmd = main_binary();
+    cmd
+        .arg("__complete")
+        .assert()
+        .success()
+        .stdout("in-progress/\nopen/\n");
+
+    let mut cmd = main_binary();
+    cmd
+        .arg("__complete")
+        .arg("open/ID")
+        .assert()
+        .success()
+        .stdout("open/ID-1234\nopen/ID-1235\n");
+
+    let mut cmd = main_binary();
+    cmd
+        .arg("__complete")
+        .arg("closed/")
+        .assert()
+        .success()
+        .stdout("");
+
+    // hidden from the help
+    let mut cmd = main_binary();
+    cmd
+        .arg("help")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("__complete").not());
+}
+
+// ================= ID RESOLUTION =================
+#[test]
+fn resolve_abbreviated_ids() {
+    purge().unwrap_or(());
+    let mut cmd = main_binary();
+    cmd
+        .arg("init")
+        .assert()
+        .success();
+    for id in &["open/", "closed/", "open/ticket-1234", "open/ticket-1235",
+                "open/hotfix-86"] {
+        let mut cmd = main_binary();
+        cmd
+            .arg("new")
+            .arg(id)
+            .arg("--message")
+            .arg("Resolve it")
+            .assert()
+            .success();
+    }
+
+    let mut cmd = main_binary();
+    cmd
+        .arg("show")
+        .arg("ticket-1234")
+        .assert()
+        .success()
+        .stdout("tickets :: show :: open/ticket-1234
+
+Resolve it
+");
+
+    let mut cmd = main_binary();
+    cmd
+        .arg("move")
+        .arg("open/hot")
+        .arg("clo/")
+        .assert()
+        .success()
+        .stdout("tickets :: move :: open/hotfix-86 => closed/
+
+SUCCEEDED.
+");
+
+    let mut cmd = main_binary();
+    cmd
+        .arg("remove")
+        .arg("open/tick")
+        .assert()
+        .failure()
+        .stderr("tickets :: remove
+
+ERROR: AMBIGUOUS IDENTIFIER open/tick. DID YOU MEAN
+
+    open/ticket-1234
+    open/ticket-1235
+");
+
+    let mut cmd = main_binary();
+    cmd
+        .arg("edit")
+        .arg("ticket-1243")
+        .arg("--message")
+        .arg("Typo")
+        .assert()
+        .failure()
+        .stderr("tickets :: edit
+
+ERROR: NOT FOUND. DID YOU MEAN
+
+    open/ticket-1234
+    open/ticket-1235
+");
+}
+
+// ================= CONFIG =================
+#[test]
+fn config_local_options() {
+    purge().unwrap_or(());
+    let mut cmd = main_binary();
+    cmd
+        .arg("init")
+        .assert()
+        .success();
+    for (id, message) in &[("open/", ""), ("open/ID-2", "Banana"),
+                           ("open/ID-1", "Cherry"), ("open/ID-3", "Apple")] {
+        let mut cmd = main_binary();
+        cmd.arg("new").arg(id);
+        if !message.is_empty() {
+            cmd.arg("--message").arg(message);
+        }
+        cmd.assert().success();
+    }
+
+    let mut cmd = main_binary();
+    cmd
+        .arg("config")
+        .arg("set")
+        .arg("--local")
+        .arg("core.sort")
+        .arg("title")
+        .assert()
+        .success()
+        .stdout("tickets :: config :: core.sort
+
+SUCCEEDED.
+");
+
+    let mut cmd = main_binary();
+    cmd
+        .arg("config")
+        .arg("get")
+        .arg("--local")
+        .arg("core.sort")
+        .assert()
+        .success()
+        .stdout("title\n");
+
+    let mut cmd = main_binary();
+    cmd
+        .arg("config")
+        .arg("list")
+        .arg("--local")
+        .assert()
+        .success()
+        .stdout("core.sort=title\n");
+
+    let mut cmd = main_binary();
+    cmd
+        .arg("show")
+        .arg("open/")
+        .assert()
+        .success()
+        .stdout("tickets :: show :: open/
+
+[ID-3]Apple
+[ID-2]Banana
+[ID-1]Cherry
+");
+
+    let mut cmd = main_binary();
+    cmd
+        .arg("config")
+        .arg("unset")
+        .arg("--local")
+        .arg("core.sort")
+        .assert()
+        .success();
+
+    let mut cmd = main_binary();
+    cmd
+        .arg("config")
+        .arg("get")
+        .arg("--local")
+        .arg("core.sort")
+        .assert()
+        .failure()
+        .stderr("tickets :: config :: core.sort
+
+ERROR: The key core.sort is not set.
+");
+
+    let mut cmd = main_binary();
+    cmd
+        .arg("config")
+        .arg("set")
+        .arg("--local")
+        .arg("sort")
+        .arg("title")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("ERROR: Invalid key sort"));
+
+    let mut cmd = main_binary();
+    cmd
+        .arg("config")
+        .arg("list")
+        .arg("--local")
+        .arg("--global")
+        .assert()
+        .failure();
+}
+
+// ================= ALIAS =================
+#[test]
+fn expand_aliases() {
+    purge().unwrap_or(());
+    let mut cmd = main_binary();
+    cmd
+        .arg("init")
+        .assert()
+        .success();
+    for id in &["open/", "done/", "open/ID-1234"] {
+        let mut cmd = main_binary();
+        cmd
+            .arg("new")
+            .arg(id)
+            .arg("--message")
+            .arg("Alias it")
+            .assert()
+            .success();
+    }
+    for (name, alias) in &[("alias.todo", "show open/"),
+                           ("alias.finish", "move {} done/"),
+                           ("alias.hello", "!echo hello"),
+                           ("alias.remove", "show open/")] {
+        let mut cmd = main_binary();
+        cmd
+            .arg("config")
+            .arg("set")
+            .arg("--local")
+            .arg(name)
+            .arg(alias)
+            .assert()
+            .success();
+    }
+
+    let mut cmd = main_binary();
+    cmd
+        .arg("todo")
+        .assert()
+        .success()
+        .stdout("tickets :: show :: open/
+
+[ID-1234]Alias it
+");
+
+    let mut cmd = main_binary();
+    cmd
+        .arg("finish")
+        .arg("ID-1234")
+        .assert()
+        .success()
+        .stdout("tickets :: move :: open/ID-1234 => done/
+
+SUCCEEDED.
+");
+
+    let mut cmd = main_binary();
+    cmd
+        .arg("hello")
+        .arg("world")
+        .assert()
+        .success()
+        .stdout("hello world\n");
+
+    let mut cmd = main_binary();
+    cmd
+        .arg("remove")
+        .arg("done/")
+        .assert()
+        .failure()
+        .stderr("tickets :: alias
+
+ERROR: The alias remove shadows the subcommand remove.
+");
+}
+
+// ================= HOOKS =================
+#[cfg(unix)]
+fn write_hook(name: &str, script: &str) {
+    use std::os::unix::fs::PermissionsExt;
+    let dir_hooks = home().join(".tickets/hooks");
+    std::fs::create_dir_all(&dir_hooks).unwrap();
+    let path_hook = dir_hooks.join(name);
+    std::fs::write(&path_hook, script).unwrap();
+    std::fs::set_permissions(&path_hook,
+                             std::fs::Permissions::from_mode(0o755)).unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn run_hooks_around_operations() {
+    purge().unwrap_or(());
+    let mut cmd = main_binary();
+    cmd
+        .arg("init")
+        .assert()
+        .success();
+    let mut cmd = main_binary();
+    cmd
+        .arg("new")
+        .arg("open/")
+        .assert()
+        .success();
+    write_hook("pre-new", "#!/bin/sh
+if grep -q WIP; then
+    echo 'No WIP tickets' >&2
+    exit 1
+fi
+");
+    write_hook("post-move", "#!/bin/sh
+echo \"$TICKETS_ID $TICKETS_DEST_CATEGORY\" > hooks/.moved
+");
+
+    let mut cmd = main_binary();
+    cmd
+        .arg("new")
+        .arg("open/ID-1234")
+        .arg("--message")
+        .arg("WIP: not yet")
+        .assert()
+        .failure()
+        .stderr("No WIP tickets
+tickets :: new :: open/ID-1234
+
+ERROR: The hook pre-new failed with exit status: 1.
+");
+    assert!(!home().join(".tickets/open/ID-1234").exists());
+
+    let mut cmd = main_binary();
+    cmd
+        .arg("new")
+        .arg("open/ID-1234")
+        .arg("--message")
+        .arg("Ready")
+        .assert()
+        .success();
+    let mut cmd = main_binary();
+    cmd
+        .arg("new")
+        .arg("done/")
+        .assert()
+        .success();
+    let mut cmd = main_binary();
+    cmd
+        .arg("move")
+        .arg("ID-1234")
+        .arg("done/")
+        .assert()
+        .success();
+    let moved = std::fs::read_to_string(
+        home().join(".tickets/hooks/.moved")).unwrap();
+    assert_eq!(moved, "open/ID-1234 done\n");
+
+    // the hooks are not a category
+    let mut cmd = main_binary();
+    cmd
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("hooks").not());
+
+    // nor are the tickets left in it silently
+    std::fs::write(home().join(".tickets/hooks/ID-1"), "Old ticket").unwrap();
+    let mut cmd = main_binary();
+    cmd
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("done/"))
+        .stderr(predicate::str::contains("reserved for the hooks"))
+        .stderr(predicate::str::contains("tickets ID-1,"));
+}
+
+// ================= PLUGINS =================
+#[cfg(unix)]
+#[test]
+fn run_plugins() {
+    use std::os::unix::fs::PermissionsExt;
+    let dir_plugins = std::env::temp_dir().join("tickets-plugins");
+    std::fs::create_dir_all(&dir_plugins).unwrap();
+    let path_plugin = dir_plugins.join("tickets-report");
+    std::fs::write(&path_plugin, "#!/bin/sh
+echo \"report $*\"
+test -n \"$TICKETS_ROOT\" && test -n \"$TICKETS_CONFIG_LOCAL\"
+").unwrap();
+    std::fs::set_permissions(&path_plugin,
+                             std::fs::Permissions::from_mode(0o755)).unwrap();
+    let path = format!("{}:{}",
+                       dir_plugins.display(),
+                       std::env::var("PATH").unwrap_or_default());
+
+    let mut cmd = main_binary();
+    cmd
+        .env("PATH", &path)
+        .arg("report")
+        .arg("--weekly")
+        .assert()
+        .success()
+        .stdout("report --weekly\n");
+
+    let mut cmd = main_binary();
+    cmd
+        .env("PATH", &path)
+        .arg("help")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("PLUGINS:\n    report\n"));
+
+    let mut cmd = main_binary();
+    cmd
+        .env("PATH", &path)
+        .arg("unknown")
+        .assert()
+        .failure();
+}
+
+// ================= CSV =================
+#[test]
+fn export_and_import_csv() {
+    purge().unwrap_or(());
+    let mut cmd = main_binary();
+    cmd
+        .arg("init")
+        .assert()
+        .success();
+    for (id, message) in &[("open/", "Open"),
+                           ("open/ID-1", "First, \"quoted\"\n\nSecond line")] {
+        let mut cmd = main_binary();
+        cmd
+            .arg("new")
+            .arg(id)
+            .arg("--message")
+            .arg(message)
+            .assert()
+            .success();
+    }
+    let mut cmd = main_binary();
+    cmd
+        .arg("estimate")
+        .arg("open/ID-1")
+        .arg("3")
+        .assert()
+        .success();
+
+    let mut cmd = main_binary();
+    cmd
+        .arg("export")
+        .arg("csv")
+        .arg("open/")
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with(
+            "category,id,title,message,created_at,modified_at,estimate
+open,ID-1,\"First, \"\"quoted\"\"\",Second line,"))
+        .stdout(predicate::str::ends_with(",3\n"));
+
+    let path_csv = std::env::temp_dir().join("tickets-export.csv");
+    let mut cmd = main_binary();
+    cmd
+        .arg("export")
+        .arg("csv")
+        .arg("--output")
+        .arg(&path_csv)
+        .assert()
+        .success()
+        .stdout("tickets :: export :: csv\n\nSUCCEEDED.\n");
+
+    purge().unwrap_or(());
+    let mut cmd = main_binary();
+    cmd
+        .arg("init")
+        .assert()
+        .success();
+    let header = format!("tickets :: import :: csv :: {}", path_csv.display());
+    let mut cmd = main_binary();
+    cmd
+        .arg("import")
+        .arg("csv")
+        .arg(&path_csv)
+        .arg("--dry-run")
+        .assert()
+        .success()
+        .stdout(format!("{}\n\nCREATED open/\nCREATED open/ID-1\n\nDRY RUN.\n",
+                        header));
+
+    let mut cmd = main_binary();
+    cmd
+        .arg("import")
+        .arg("csv")
+        .arg(&path_csv)
+        .assert()
+        .success()
+        .stdout(format!("{}\n\nCREATED open/\nCREATED open/ID-1\n\nSUCCEEDED.\n",
+                        header));
+
+    let mut cmd = main_binary();
+    cmd
+        .arg("show")
+        .arg("--raw")
+        .arg("open/ID-1")
+        .assert()
+        .success()
+        .stdout("First, \"quoted\"\n\nSecond line");
+
+    let mut cmd = main_binary();
+    cmd
+        .arg("import")
+        .arg("csv")
+        .arg(&path_csv)
+        .arg("--on-conflict")
+        .arg("rename")
+        .assert()
+        .success()
+        .stdout(format!("{}\n\nRENAMED open/ID-1 => open/ID-1-2\n\nSUCCEEDED.\n",
+                        header));
+
+    let path_mapped = std::env::temp_dir().join("tickets-mapped.csv");
+    std::fs::write(&path_mapped, "Key,Summary\nID-1,Mapped\n,No id\n")
+        .unwrap();
+    let mut cmd = main_binary();
+    cmd
+        .arg("import")
+        .arg("csv")
+        .arg(&path_mapped)
+        .arg("--map")
+        .arg("Key=id")
+        .arg("--map")
+        .arg("Summary=title")
+        .arg("--category")
+        .arg("open")
+        .assert()
+        .success()
+        .stdout(format!("tickets :: import :: csv :: {}
+
+SKIPPED open/ID-1, which already exists
+INVALID open/: The id is missing.
+
+SUCCEEDED.
+", path_mapped.display()));
+}
+
+// ================= GITHUB =================
+#[test]
+fn import_github_issues() {
+    purge().unwrap_or(());
+    let mut cmd = main_binary();
+    cmd
+        .arg("init")
+        .assert()
+        .success();
+
+    let path_json = std::env::temp_dir().join("tickets-issues.json");
+    std::fs::write(&path_json, r#"[
+  {"number": 12, "state": "OPEN", "title": "Crash on start",
+   "body": "It crashes.", "labels": [{"name": "bug"}],
+   "assignees": [{"login": "alice"}], "author": {"login": "bob"},
+   "comments": [{"author": {"login": "alice"}, "body": "Confirmed.",
+                 "createdAt": "2019-01-02T03:04:05Z"}]},
+  {"number": 10, "state": "CLOSED", "title": "Old one", "body": ""}
+]"#).unwrap();
+    let header = format!("tickets :: import :: github :: {}",
+                         path_json.display());
+
+    let mut cmd = main_binary();
+    cmd
+        .arg("import")
+        .arg("github")
+        .arg(&path_json)
+        .arg("--closed-category")
+        .arg("done")
+        .assert()
+        .success()
+        .stdout(format!("{}
+
+CREATED open/
+CREATED open/12
+CREATED done/
+CREATED done/10
+
+SUCCEEDED.
+", header));
+
+    let mut cmd = main_binary();
+    cmd
+        .arg("show")
+        .arg("open/12")
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("tickets :: show :: open/12
+
+Crash on start
+
+It crashes.
+"))
+        .stdout(predicate::str::contains("alice\nConfirmed."));
+
+    let mut cmd = main_binary();
+    cmd
+        .arg("import")
+        .arg("github")
+        .arg(&path_json)
+        .arg("--closed-category")
+        .arg("done")
+        .arg("--on-conflict")
+        .arg("overwrite")
+        .assert()
+        .success()
+        .stdout(format!("{}
+
+OVERWRITTEN open/12
+OVERWRITTEN done/10
+
+SUCCEEDED.
+", header));
+}
+
+// ================= SYNC =================
+/// A JIRA with a single issue, answering from a local port,
+/// which keeps the bodies of the updates.
+fn mock_jira() -> (String, std::sync::Arc<std::sync::Mutex<Vec<String>>>) {
+    let server = tiny_http::Server::http("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", server.server_addr());
+    let updates = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let updates_server = std::sync::Arc::clone(&updates);
+    thread::spawn(move || {
+        for mut request in server.incoming_requests() {
+            let mut body = String::new();
+            request.as_reader().read_to_string(&mut body).unwrap();
+            let mut updates = updates_server.lock().unwrap();
+            let issue = format!(
+                r#"{{"key": "PROJ-1", "fields": {{"summary": "Remote one",
+                "description": "From JIRA", "status": {{"name": "To Do"}},
+                "updated": "{}"}}}}"#,
+                updates.len());
+            let path = request.url().split('?').next().unwrap().to_string();
+            let text = match (request.method().as_str(), &path[..]) {
+                ("GET", "/rest/api/2/search") => {
+                    format!(r#"{{"total": 1, "issues": [{}]}}"#, issue)
+                },
+                ("GET", "/rest/api/2/issue/PROJ-1") => issue,
+                ("PUT", "/rest/api/2/issue/PROJ-1") => {
+                    updates.push(body);
+                    String::new()
+                },
+                _ => String::from("{}"),
+            };
+            request.respond(tiny_http::Response::from_string(text)).unwrap();
+        }
+    });
+    (url, updates)
+}
+
+#[test]
+fn sync_with_a_remote() {
+    purge().unwrap_or(());
+    let mut cmd = main_binary();
+    cmd
+        .arg("init")
+        .assert()
+        .success();
+    let (url, updates) = mock_jira();
+
+    let mut cmd = main_binary();
+    cmd
+        .arg("remote")
+        .arg("add")
+        .arg("work")
+        .arg(&url)
+        .arg("--project")
+        .arg("PROJ")
+        .assert()
+        .success()
+        .stdout("tickets :: remote :: add :: work\n\nSUCCEEDED.\n");
+
+    let mut cmd = main_binary();
+    cmd
+        .arg("remote")
+        .arg("list")
+        .assert()
+        .success()
+        .stdout(format!("work\t{}\n", url));
+
+    let mut cmd = main_binary();
+    cmd
+        .arg("sync")
+        .assert()
+        .success()
+        .stdout("tickets :: sync :: work
+
+PULLED PROJ-1 => to-do/PROJ-1
+
+SUCCEEDED.
+");
+
+    let mut cmd = main_binary();
+    cmd
+        .arg("sync")
+        .arg("work")
+        .assert()
+        .success()
+        .stdout("tickets :: sync :: work\n\nUP TO DATE.\n\nSUCCEEDED.\n");
+
+    let mut cmd = main_binary();
+    cmd
+        .arg("edit")
+        .arg("to-do/PROJ-1")
+        .arg("--message")
+        .arg("Local one\n\nEdited")
+        .assert()
+        .success();
+
+    let mut cmd = main_binary();
+    cmd
+        .arg("sync")
+        .arg("--dry-run")
+        .assert()
+        .success()
+        .stdout("tickets :: sync :: work
+
+PUSHED to-do/PROJ-1 => PROJ-1
+
+DRY RUN.
+");
+    assert!(updates.lock().unwrap().is_empty());
+
+    let mut cmd = main_binary();
+    cmd
+        .arg("sync")
+        .assert()
+        .success()
+        .stdout("tickets :: sync :: work
+
+PUSHED to-do/PROJ-1 => PROJ-1
+
+SUCCEEDED.
+");
+    assert!(updates.lock().unwrap()[0].contains("\"summary\":\"Local one\""));
+
+    let mut cmd = main_binary();
+    cmd
+        .arg("remote")
+        .arg("remove")
+        .arg("work")
+        .assert()
+        .success();
+
+    let mut cmd = main_binary();
+    cmd
+        .arg("sync")
+        .assert()
+        .failure()
+        .stderr("tickets :: sync\n\nERROR: No remote is added.\n");
+}
+
+// ================= TODOTXT =================
+#[test]
+fn import_and_export_todotxt() {
+    purge().unwrap_or(());
+    let mut cmd = main_binary();
+    cmd
+        .arg("init")
+        .assert()
+        .success();
+
+    let todo = "(A) 2019-01-01 Call mom +family @phone due:2019-01-10 id:CALL
+x 2019-01-03 2019-01-02 Pay rent +home pri:B id:RENT
+Water the plants";
+    let path_todo = std::env::temp_dir().join("tickets-todo.txt");
+    std::fs::write(&path_todo, todo).unwrap();
+    let mut cmd = main_binary();
+    cmd
+        .arg("import")
+        .arg("todotxt")
+        .arg(&path_todo)
+        .assert()
+        .success()
+        .stdout(format!("tickets :: import :: todotxt :: {}
+
+CREATED family/
+CREATED family/CALL
+CREATED home/
+CREATED home/RENT
+CREATED inbox/
+CREATED inbox/TODO-3
+
+SUCCEEDED.
+", path_todo.display()));
+
+    // the same lines come back
+    for (line, category) in todo.lines().zip(&["family/", "home/"]) {
+        let mut cmd = main_binary();
+        cmd
+            .arg("export")
+            .arg("todotxt")
+            .arg(category)
+            .assert()
+            .success()
+            .stdout(format!("{}\n", line));
+    }
+
+    let mut cmd = main_binary();
+    cmd
+        .arg("export")
+        .arg("todotxt")
+        .arg("inbox/")
+        .assert()
+        .success()
+        .stdout("Water the plants +inbox id:TODO-3\n");
+}
+
+// ================= TASKWARRIOR =================
+#[test]
+fn import_and_export_taskwarrior() {
+    purge().unwrap_or(());
+    let mut cmd = main_binary();
+    cmd
+        .arg("init")
+        .assert()
+        .success();
+
+    let path_json = std::env::temp_dir().join("tickets-tasks.json");
+    std::fs::write(&path_json, r#"[
+{"id":1,"description":"Buy milk","entry":"20190101T000000Z","project":"home",
+ "status":"pending","uuid":"0a1b2c3d-0000-4000-8000-000000000000",
+ "priority":"H","tags":["shop"],
+ "annotations":[{"entry":"20190102T030405Z","description":"Whole milk"}]}
+]"#).unwrap();
+    let mut cmd = main_binary();
+    cmd
+        .arg("import")
+        .arg("taskwarrior")
+        .arg(&path_json)
+        .assert()
+        .success()
+        .stdout(format!("tickets :: import :: taskwarrior :: {}
+
+CREATED home/
+CREATED home/0a1b2c3d
+
+SUCCEEDED.
+", path_json.display()));
+
+    let mut cmd = main_binary();
+    cmd
+        .arg("show")
+        .arg("home/0a1b2c3d")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Buy milk"))
+        .stdout(predicate::str::contains("Whole milk"));
+
+    let mut cmd = main_binary();
+    cmd
+        .arg("export")
+        .arg("taskwarrior")
+        .arg("home/")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            r#""uuid": "0a1b2c3d-0000-4000-8000-000000000000""#))
+        .stdout(predicate::str::contains(r#""project": "home""#))
+        .stdout(predicate::str::contains(r#""priority": "H""#))
+        .stdout(predicate::str::contains(r#""entry": "20190101T000000Z""#))
+        .stdout(predicate::str::contains(
+            r#""description": "Whole milk""#));
+}
+
+// ================= OUTLINE =================
+#[test]
+fn export_org_and_markdown() {
+    purge().unwrap_or(());
+    let mut cmd = main_binary();
+    cmd
+        .arg("init")
+        .assert()
+        .success();
+    for (id, message) in &[("open/", "Open"),
+                           ("open/ID-1", "Fix it\n\nQuickly"),
+                           ("open/ID-2", "Later")] {
+        let mut cmd = main_binary();
+        cmd
+            .arg("new")
+            .arg(id)
+            .arg("--message")
+            .arg(message)
+            .assert()
+            .success();
+    }
+    let mut cmd = main_binary();
+    cmd
+        .arg("estimate")
+        .arg("open/ID-1")
+        .arg("3")
+        .assert()
+        .success();
+
+    let mut cmd = main_binary();
+    cmd
+        .arg("export")
+        .arg("markdown")
+        .arg("open/")
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("# open/\n\n"))
+        .stdout(predicate::str::contains("- [ ] **Fix it** `open/ID-1`
+
+  Quickly
+
+  - estimate: 3
+"));
+
+    let mut cmd = main_binary();
+    cmd
+        .arg("export")
+        .arg("org")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("* open/\n"))
+        .stdout(predicate::str::contains("** TODO Later\n   :PROPERTIES:
+   :ID: open/ID-2
+   :END:
+"));
+
+    let mut cmd = main_binary();
+    cmd
+        .arg("export")
+        .arg("org")
+        .arg("--tag")
+        .arg("nothing")
+        .assert()
+        .success()
+        .stdout("");
+}
+
+// ================= ICS =================
+#[test]
+fn export_ics() {
+    purge().unwrap_or(());
+    let mut cmd = main_binary();
+    cmd
+        .arg("init")
+        .assert()
+        .success();
+    let path_todo = std::env::temp_dir().join("tickets-due.txt");
+    std::fs::write(&path_todo, "Pay rent, now +open due:2019-01-10 id:RENT
+Someday +open id:LATER
+").unwrap();
+    let mut cmd = main_binary();
+    cmd
+        .arg("import")
+        .arg("todotxt")
+        .arg(&path_todo)
+        .assert()
+        .success();
+
+    let path_ics = std::env::temp_dir().join("tickets.ics");
+    let mut cmd = main_binary();
+    cmd
+        .arg("export")
+        .arg("ics")
+        .arg("--output")
+        .arg(&path_ics)
+        .assert()
+        .success()
+        .stdout("tickets :: export :: ics\n\nSUCCEEDED.\n");
+    let ics = std::fs::read_to_string(&path_ics).unwrap();
+    assert!(ics.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+    assert!(ics.ends_with("END:VTODO\r\nEND:VCALENDAR\r\n"));
+    let uid = ics.lines()
+        .find(|line| line.starts_with("UID:"))
+        .unwrap()
+        .to_string();
+    assert!(uid.ends_with("@tickets"));
+    assert!(ics.contains("SUMMARY:Pay rent\\, now\r\n"));
+    assert!(ics.contains("DUE;VALUE=DATE:20190110\r\n"));
+    assert!(ics.contains("STATUS:NEEDS-ACTION\r\n"));
+    assert!(!ics.contains("LATER"));
+    // the export leaves the tickets as they are
+    let meta = std::fs::read_to_string(home().join(".tickets/open/.RENT/meta"))
+        .unwrap_or_default();
+    assert!(!meta.contains("uid: "));
+
+    // the todo stays the same after the ticket is moved
+    let mut cmd = main_binary();
+    cmd
+        .arg("new")
+        .arg("done/")
+        .assert()
+        .success();
+    let mut cmd = main_binary();
+    cmd
+        .arg("move")
+        .arg("open/RENT")
+        .arg("done/")
+        .assert()
+        .success();
+    let mut cmd = main_binary();
+    cmd
+        .arg("export")
+        .arg("ics")
+        .arg("--output")
+        .arg(&path_ics)
+        .assert()
+        .success();
+    let ics = std::fs::read_to_string(&path_ics).unwrap();
+    assert!(ics.contains(&format!("{}\r\n", uid)));
+    assert!(ics.contains("STATUS:COMPLETED\r\n"));
+}
+
+// ================= MAIL =================
+#[test]
+fn import_mail() {
+    purge().unwrap_or(());
+    let mut cmd = main_binary();
+    cmd
+        .arg("init")
+        .assert()
+        .success();
+
+    let path_maildir = std::env::temp_dir().join("tickets-maildir");
+    std::fs::remove_dir_all(&path_maildir).unwrap_or(());
+    std::fs::create_dir_all(path_maildir.join("cur")).unwrap();
+    std::fs::create_dir_all(path_maildir.join("new")).unwrap();
+    std::fs::create_dir_all(path_maildir.join("tmp")).unwrap();
+    std::fs::write(path_maildir.join("new/1546851600.1.host"), "\
+From: Alice <alice@example.com>
+Subject: The build is broken
+Message-ID: <1@example.com>
+MIME-Version: 1.0
+Content-Type: multipart/mixed; boundary=\"b\"
+
+--b
+Content-Type: text/plain
+
+It fails since yesterday.
+--b
+Content-Type: text/plain; name=\"build.log\"
+Content-Disposition: attachment; filename=\"build.log\"
+
+error: no space left
+--b--
+").unwrap();
+    let mut cmd = main_binary();
+    cmd
+        .arg("import")
+        .arg("mail")
+        .arg(&path_maildir)
+        .assert()
+        .success()
+        .stdout(format!("tickets :: import :: mail :: {}
+
+CREATED inbox/
+CREATED inbox/MAIL-096e1bde572d9c21
+
+SUCCEEDED.
+", path_maildir.display()));
+
+    let mut cmd = main_binary();
+    cmd
+        .arg("show")
+        .arg("inbox/MAIL-096e1bde572d9c21")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("The build is broken"))
+        .stdout(predicate::str::contains("It fails since yesterday."));
+    let mut cmd = main_binary();
+    cmd
+        .arg("attachments")
+        .arg("inbox/MAIL-096e1bde572d9c21")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("build.log"));
+
+    // the message is known wherever the ticket is moved
+    let mut cmd = main_binary();
+    cmd
+        .arg("new")
+        .arg("open/")
+        .assert()
+        .success();
+    let mut cmd = main_binary();
+    cmd
+        .arg("move")
+        .arg("inbox/MAIL-096e1bde572d9c21")
+        .arg("open/")
+        .assert()
+        .success();
+    let mut cmd = main_binary();
+    cmd
+        .arg("import")
+        .arg("mail")
+        .arg(&path_maildir)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "SKIPPED inbox/MAIL-096e1bde572d9c21, which is imported as \
+             open/MAIL-096e1bde572d9c21"));
+}
+
+// ================= WATCH =================
+#[test]
+fn watch_changes() {
+    purge().unwrap_or(());
+    let mut cmd = main_binary();
+    cmd
+        .arg("init")
+        .assert()
+        .success();
+    for category in &["open/", "done/"] {
+        let mut cmd = main_binary();
+        cmd
+            .arg("new")
+            .arg(category)
+            .assert()
+            .success();
+    }
+
+    let mut watcher = main_binary()
+        .arg("watch")
+        .arg("--json")
+        .stdout(std::process::Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut watcher_open = main_binary()
+        .arg("watch")
+        .arg("open/")
+        .stdout(std::process::Stdio::piped())
+        .spawn()
+        .unwrap();
+    let millis500 = time::Duration::from_millis(500);
+    thread::sleep(millis500);
+
+    let actions: [&[&str]; 4] = [&["new", "open/ID-1", "--message", "Fix it"],
+                                 &["edit", "open/ID-1", "--message", "Fix it now"],
+                                 &["move", "open/ID-1", "done/"],
+                                 &["remove", "done/ID-1"]];
+    for args in actions.iter() {
+        let mut cmd = main_binary();
+        cmd
+            .args(args.iter())
+            .assert()
+            .success();
+        thread::sleep(millis500);
+    }
+
+    let mut outputs = Vec::new();
+    for child in [&mut watcher, &mut watcher_open] {
+        child.kill().unwrap();
+        let mut output = String::new();
+        std::io::Read::read_to_string(child.stdout.as_mut().unwrap(),
+                                      &mut output).unwrap();
+        child.wait().unwrap();
+        outputs.push(output);
+    }
+    let events: Vec<serde_json::Value> = outputs[0]
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    let names: Vec<&str> = events.iter()
+        .map(|event| event["event"].as_str().unwrap())
+        .collect();
+    assert_eq!(names, vec!["created", "edited", "moved", "removed"]);
+    assert_eq!(events[1]["title"], "Fix it now");
+    assert_eq!(events[2]["from"], "open/ID-1");
+    assert_eq!(events[2]["id"], "done/ID-1");
+    assert_eq!(outputs[1], "tickets :: watch :: open/
+
+WATCHING.
+
+CREATED open/ID-1 :: Fix it
+EDITED open/ID-1 :: Fix it now
+REMOVED open/ID-1 :: Fix it now
+");
+}